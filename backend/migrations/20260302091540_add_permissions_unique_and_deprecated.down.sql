@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE permissions
+    DROP CONSTRAINT uq_permission,
+    DROP COLUMN deprecated;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE permissions
+    ADD COLUMN deprecated boolean NOT NULL DEFAULT FALSE,
+    ADD CONSTRAINT uq_permission UNIQUE (resource, action, scope);
@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE roles ADD COLUMN version integer NOT NULL DEFAULT 1;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE components (
+    component_id uuid PRIMARY key DEFAULT gen_random_uuid (),
+    code text NOT NULL UNIQUE
+);
+
+CREATE TABLE roles_components (
+    role_id uuid NOT NULL,
+    component_id uuid NOT NULL,
+    CONSTRAINT fk_role FOREIGN key (role_id) REFERENCES roles (role_id),
+    CONSTRAINT fk_component FOREIGN key (component_id) REFERENCES components (component_id),
+    PRIMARY key (role_id, component_id)
+);
@@ -0,0 +1,8 @@
+-- Add up migration script here
+CREATE TABLE users_roles (
+    user_id uuid NOT NULL,
+    role_id uuid NOT NULL,
+    CONSTRAINT fk_user FOREIGN key (user_id) REFERENCES users (user_id),
+    CONSTRAINT fk_role FOREIGN key (role_id) REFERENCES roles (role_id),
+    PRIMARY key (user_id, role_id)
+);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TRIGGER components_changed ON components;
+DROP TRIGGER permissions_changed ON permissions;
+DROP TRIGGER roles_components_changed ON roles_components;
+DROP TRIGGER roles_permissions_changed ON roles_permissions;
+DROP TRIGGER users_roles_changed ON users_roles;
+DROP FUNCTION notify_rbac_change ();
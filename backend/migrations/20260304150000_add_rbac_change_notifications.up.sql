@@ -0,0 +1,49 @@
+-- Add up migration script here
+-- Publishes every change that affects effective permissions on the
+-- `rbac_changes` channel, e.g. {"kind": "role", "id": "..."}, so the
+-- permission cache can evict exactly the users involved.
+-- TG_ARGV[0] is the kind of change, TG_ARGV[1] the column holding its id.
+CREATE FUNCTION notify_rbac_change () RETURNS trigger AS $$
+DECLARE
+    old_id text;
+    new_id text;
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        old_id := to_jsonb(OLD) ->> TG_ARGV[1];
+        PERFORM pg_notify(
+            'rbac_changes',
+            json_build_object('kind', TG_ARGV[0], 'id', old_id)::text
+        );
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        new_id := to_jsonb(NEW) ->> TG_ARGV[1];
+        IF new_id IS DISTINCT FROM old_id THEN
+            PERFORM pg_notify(
+                'rbac_changes',
+                json_build_object('kind', TG_ARGV[0], 'id', new_id)::text
+            );
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_roles_changed
+AFTER INSERT OR UPDATE OR DELETE ON users_roles
+FOR EACH ROW EXECUTE FUNCTION notify_rbac_change ('user', 'user_id');
+
+CREATE TRIGGER roles_permissions_changed
+AFTER INSERT OR UPDATE OR DELETE ON roles_permissions
+FOR EACH ROW EXECUTE FUNCTION notify_rbac_change ('role', 'role_id');
+
+CREATE TRIGGER roles_components_changed
+AFTER INSERT OR UPDATE OR DELETE ON roles_components
+FOR EACH ROW EXECUTE FUNCTION notify_rbac_change ('role', 'role_id');
+
+CREATE TRIGGER permissions_changed
+AFTER UPDATE OR DELETE ON permissions
+FOR EACH ROW EXECUTE FUNCTION notify_rbac_change ('permission', 'permission_id');
+
+CREATE TRIGGER components_changed
+AFTER UPDATE OR DELETE ON components
+FOR EACH ROW EXECUTE FUNCTION notify_rbac_change ('component', 'component_id');
@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP INDEX idx_users_roles_valid_until;
+
+DROP TABLE users_roles_audit;
+
+ALTER TABLE users_roles
+DROP CONSTRAINT ck_validity,
+DROP COLUMN valid_until,
+DROP COLUMN valid_from;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+ALTER TABLE users_roles
+ADD COLUMN valid_from timestamptz NOT NULL DEFAULT now(),
+ADD COLUMN valid_until timestamptz,
+ADD CONSTRAINT ck_validity CHECK (valid_until IS NULL OR valid_until > valid_from);
+
+CREATE TABLE users_roles_audit (
+    audit_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL,
+    role_id uuid NOT NULL,
+    valid_from timestamptz NOT NULL,
+    valid_until timestamptz,
+    event text NOT NULL CHECK (event IN ('granted', 'revoked', 'expired')),
+    occurred_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_users_roles_valid_until ON users_roles (valid_until)
+WHERE valid_until IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE access_requests;
+
+DROP TABLE roles_approvers;
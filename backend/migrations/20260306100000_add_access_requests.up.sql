@@ -0,0 +1,40 @@
+-- Add up migration script here
+CREATE TABLE roles_approvers (
+    role_id uuid NOT NULL,
+    user_id uuid NOT NULL,
+    CONSTRAINT fk_role FOREIGN key (role_id) REFERENCES roles (role_id),
+    CONSTRAINT fk_user FOREIGN key (user_id) REFERENCES users (user_id),
+    PRIMARY key (role_id, user_id)
+);
+
+CREATE TABLE access_requests (
+    access_request_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    requester_id uuid NOT NULL,
+    role_id uuid,
+    component_id uuid,
+    reason text NOT NULL,
+    -- Validity of the grant created on approval.
+    valid_until timestamptz,
+    status text NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'approved', 'rejected', 'expired', 'cancelled')
+    ),
+    -- Role granted on approval, for component requests picked by the approver.
+    granted_role_id uuid,
+    decided_by uuid,
+    decided_at timestamptz,
+    decision_comment text,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    expires_at timestamptz NOT NULL DEFAULT now() + interval '14 days',
+    CONSTRAINT fk_requester FOREIGN key (requester_id) REFERENCES users (user_id),
+    CONSTRAINT fk_role FOREIGN key (role_id) REFERENCES roles (role_id),
+    CONSTRAINT fk_component FOREIGN key (component_id) REFERENCES components (component_id),
+    CONSTRAINT fk_granted_role FOREIGN key (granted_role_id) REFERENCES roles (role_id),
+    CONSTRAINT fk_decided_by FOREIGN key (decided_by) REFERENCES users (user_id),
+    CONSTRAINT ck_requested CHECK ((role_id IS NULL) <> (component_id IS NULL))
+);
+
+-- At most one open request per requester and role or component.
+CREATE UNIQUE INDEX uq_pending_access_request ON access_requests (
+    requester_id, coalesce(role_id, component_id)
+)
+WHERE status = 'pending';
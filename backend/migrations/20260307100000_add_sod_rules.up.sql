@@ -0,0 +1,27 @@
+-- Add up migration script here
+CREATE TABLE sod_rules (
+    sod_rule_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    kind text NOT NULL CHECK (kind IN ('mutually_exclusive', 'max_holders')),
+    role_id uuid NOT NULL,
+    other_role_id uuid,
+    max_holders integer,
+    description text NOT NULL DEFAULT '',
+    CONSTRAINT fk_role FOREIGN key (role_id) REFERENCES roles (role_id),
+    CONSTRAINT fk_other_role FOREIGN key (other_role_id) REFERENCES roles (role_id),
+    CONSTRAINT ck_mutually_exclusive CHECK (
+        kind <> 'mutually_exclusive'
+        OR (other_role_id IS NOT NULL AND other_role_id <> role_id AND max_holders IS NULL)
+    ),
+    CONSTRAINT ck_max_holders CHECK (
+        kind <> 'max_holders'
+        OR (max_holders >= 1 AND other_role_id IS NULL)
+    )
+);
+
+CREATE UNIQUE INDEX uq_sod_mutually_exclusive ON sod_rules (
+    least(role_id, other_role_id), greatest(role_id, other_role_id)
+)
+WHERE kind = 'mutually_exclusive';
+
+CREATE UNIQUE INDEX uq_sod_max_holders ON sod_rules (role_id)
+WHERE kind = 'max_holders';
@@ -0,0 +1,9 @@
+-- Add down migration script here
+ALTER TABLE access_requests DROP COLUMN organization_id;
+ALTER TABLE projects DROP COLUMN organization_id;
+ALTER TABLE members DROP COLUMN organization_id;
+ALTER TABLE roles DROP COLUMN organization_id;
+
+DROP TABLE organizations_users;
+
+DROP TABLE organizations;
@@ -0,0 +1,58 @@
+-- Add up migration script here
+CREATE TABLE organizations (
+    organization_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    name text NOT NULL UNIQUE,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE organizations_users (
+    organization_id uuid NOT NULL,
+    user_id uuid NOT NULL,
+    CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id),
+    CONSTRAINT fk_user FOREIGN key (user_id) REFERENCES users (user_id),
+    PRIMARY key (organization_id, user_id)
+);
+
+-- Everything created before organizations existed moves to a default one,
+-- which every existing user joins.
+INSERT INTO organizations (name)
+SELECT 'Default'
+WHERE EXISTS (SELECT 1 FROM users)
+    OR EXISTS (SELECT 1 FROM roles)
+    OR EXISTS (SELECT 1 FROM members)
+    OR EXISTS (SELECT 1 FROM projects);
+
+INSERT INTO organizations_users (organization_id, user_id)
+SELECT o.organization_id, u.user_id
+FROM organizations as o, users as u;
+
+ALTER TABLE roles ADD COLUMN organization_id uuid;
+ALTER TABLE members ADD COLUMN organization_id uuid;
+ALTER TABLE projects ADD COLUMN organization_id uuid;
+ALTER TABLE access_requests ADD COLUMN organization_id uuid;
+
+UPDATE roles SET organization_id = (SELECT organization_id FROM organizations);
+UPDATE members SET organization_id = (SELECT organization_id FROM organizations);
+UPDATE projects SET organization_id = (SELECT organization_id FROM organizations);
+UPDATE access_requests SET organization_id = (SELECT organization_id FROM organizations);
+
+ALTER TABLE roles
+ALTER COLUMN organization_id SET NOT NULL,
+ADD CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id);
+
+ALTER TABLE members
+ALTER COLUMN organization_id SET NOT NULL,
+ADD CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id);
+
+ALTER TABLE projects
+ALTER COLUMN organization_id SET NOT NULL,
+ADD CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id);
+
+ALTER TABLE access_requests
+ALTER COLUMN organization_id SET NOT NULL,
+ADD CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id);
+
+CREATE INDEX idx_roles_organization ON roles (organization_id);
+CREATE INDEX idx_members_organization ON members (organization_id);
+CREATE INDEX idx_projects_organization ON projects (organization_id);
+CREATE INDEX idx_access_requests_organization ON access_requests (organization_id);
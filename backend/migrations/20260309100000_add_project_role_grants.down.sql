@@ -0,0 +1,12 @@
+-- Add down migration script here
+ALTER TABLE users_roles_audit DROP COLUMN project_id;
+
+DROP INDEX idx_users_roles_project_id;
+
+DELETE FROM users_roles WHERE project_id IS NOT NULL;
+
+ALTER TABLE users_roles
+DROP CONSTRAINT uq_users_roles,
+DROP CONSTRAINT fk_project,
+DROP COLUMN project_id,
+ADD PRIMARY key (user_id, role_id);
@@ -0,0 +1,14 @@
+-- Add up migration script here
+-- A grant without project_id applies to the whole organization, otherwise
+-- only to that project.
+ALTER TABLE users_roles
+ADD COLUMN project_id uuid,
+ADD CONSTRAINT fk_project FOREIGN key (project_id) REFERENCES projects (project_id) ON DELETE CASCADE,
+DROP CONSTRAINT users_roles_pkey,
+ADD CONSTRAINT uq_users_roles UNIQUE NULLS NOT DISTINCT (user_id, role_id, project_id);
+
+CREATE INDEX idx_users_roles_project_id ON users_roles (project_id)
+WHERE project_id IS NOT NULL;
+
+ALTER TABLE users_roles_audit
+ADD COLUMN project_id uuid;
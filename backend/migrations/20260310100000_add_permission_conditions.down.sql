@@ -0,0 +1,7 @@
+-- Add down migration script here
+DELETE FROM permissions WHERE condition IS NOT NULL;
+
+ALTER TABLE permissions
+    DROP CONSTRAINT uq_permission,
+    DROP COLUMN condition,
+    ADD CONSTRAINT uq_permission UNIQUE (resource, action, scope);
@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- An optional attribute condition, e.g. `resource.owner_id == user.id`,
+-- that must hold for the permission to apply.
+ALTER TABLE permissions
+    ADD COLUMN condition text,
+    DROP CONSTRAINT uq_permission,
+    ADD CONSTRAINT uq_permission UNIQUE NULLS NOT DISTINCT (resource, action, scope, condition);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TRIGGER roles_version_recorded ON roles;
+
+DROP FUNCTION record_role_version ();
+
+DROP TABLE roles_history;
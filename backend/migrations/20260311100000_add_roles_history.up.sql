@@ -0,0 +1,66 @@
+-- Add up migration script here
+-- What each version of a role held. Snapshots are taken when the
+-- transaction creating or bumping the version commits, so they reflect the
+-- permissions and components the change left behind.
+CREATE TABLE roles_history (
+    role_id uuid NOT NULL,
+    version integer NOT NULL,
+    description text NOT NULL,
+    -- The permission rows as they were, they may be edited or deleted later.
+    permissions jsonb NOT NULL,
+    components text [] NOT NULL,
+    recorded_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_role FOREIGN key (role_id) REFERENCES roles (role_id) ON DELETE CASCADE,
+    PRIMARY key (role_id, version)
+);
+
+CREATE FUNCTION record_role_version () RETURNS trigger AS $$
+BEGIN
+    INSERT INTO roles_history (role_id, version, description, permissions, components)
+    SELECT r.role_id, r.version, r.description,
+        coalesce((
+            SELECT jsonb_agg(to_jsonb(p) ORDER BY p.permission_id)
+            FROM roles_permissions as rp
+            JOIN permissions as p ON p.permission_id = rp.permission_id
+            WHERE rp.role_id = r.role_id
+        ), '[]'::jsonb),
+        ARRAY(
+            SELECT c.code
+            FROM roles_components as rc
+            JOIN components as c ON c.component_id = rc.component_id
+            WHERE rc.role_id = r.role_id
+            ORDER BY c.code
+        )
+    FROM roles as r
+    WHERE r.role_id = NEW.role_id
+    ON CONFLICT (role_id, version) DO UPDATE
+    SET description = excluded.description,
+        permissions = excluded.permissions,
+        components = excluded.components,
+        recorded_at = excluded.recorded_at;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER roles_version_recorded
+AFTER INSERT OR UPDATE OF version ON roles
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION record_role_version ();
+
+-- Existing roles start their history at their current version.
+INSERT INTO roles_history (role_id, version, description, permissions, components)
+SELECT r.role_id, r.version, r.description,
+    coalesce((
+        SELECT jsonb_agg(to_jsonb(p) ORDER BY p.permission_id)
+        FROM roles_permissions as rp
+        JOIN permissions as p ON p.permission_id = rp.permission_id
+        WHERE rp.role_id = r.role_id
+    ), '[]'::jsonb),
+    ARRAY(
+        SELECT c.code
+        FROM roles_components as rc
+        JOIN components as c ON c.component_id = rc.component_id
+        WHERE rc.role_id = r.role_id
+        ORDER BY c.code
+    )
+FROM roles as r;
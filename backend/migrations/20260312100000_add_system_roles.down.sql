@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TRIGGER system_roles_kept ON roles;
+
+DROP FUNCTION forbid_system_role_delete ();
+
+ALTER TABLE roles DROP COLUMN is_system;
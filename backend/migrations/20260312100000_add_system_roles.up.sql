@@ -0,0 +1,18 @@
+-- Add up migration script here
+-- System roles are the presets seeded by the bootstrap, they may be edited
+-- but not deleted.
+ALTER TABLE roles ADD COLUMN is_system boolean NOT NULL DEFAULT FALSE;
+
+CREATE FUNCTION forbid_system_role_delete () RETURNS trigger AS $$
+BEGIN
+    IF OLD.is_system THEN
+        RAISE EXCEPTION 'system role % cannot be deleted', OLD.role_id
+            USING ERRCODE = 'restrict_violation';
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER system_roles_kept
+BEFORE DELETE ON roles
+FOR EACH ROW EXECUTE FUNCTION forbid_system_role_delete ();
@@ -0,0 +1,13 @@
+-- Add down migration script here
+DROP TABLE rbac_cleanup_audit;
+
+ALTER TABLE permissions DROP COLUMN purge_after;
+
+ALTER TABLE components
+DROP COLUMN synced_at,
+DROP COLUMN deprecated,
+DROP COLUMN purge_after;
+
+DROP TABLE components_permissions;
+
+DROP TABLE registry_syncs;
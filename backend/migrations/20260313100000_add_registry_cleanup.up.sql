@@ -0,0 +1,45 @@
+-- Add up migration script here
+-- Each full upload of the frontend component registry. Components absent
+-- from the latest one are orphaned.
+CREATE TABLE registry_syncs (
+    sync_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    synced_by uuid NOT NULL,
+    synced_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_synced_by FOREIGN key (synced_by) REFERENCES users (user_id)
+);
+
+CREATE INDEX idx_registry_syncs_synced_at ON registry_syncs (synced_at);
+
+-- The permissions each component needs, as declared by the latest sync.
+CREATE TABLE components_permissions (
+    component_id uuid NOT NULL,
+    permission_id uuid NOT NULL,
+    CONSTRAINT fk_component FOREIGN key (component_id) REFERENCES components (component_id) ON DELETE CASCADE,
+    CONSTRAINT fk_permission FOREIGN key (permission_id) REFERENCES permissions (permission_id) ON DELETE CASCADE,
+    PRIMARY key (component_id, permission_id)
+);
+
+-- Orphans are first deprecated with a grace period, then purged once
+-- purge_after has passed.
+ALTER TABLE components
+ADD COLUMN synced_at timestamptz,
+ADD COLUMN deprecated boolean NOT NULL DEFAULT FALSE,
+ADD COLUMN purge_after timestamptz;
+
+ALTER TABLE permissions
+ADD COLUMN purge_after timestamptz;
+
+CREATE TABLE rbac_cleanup_audit (
+    audit_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    event text NOT NULL CHECK (event IN ('deprecated', 'purged')),
+    kind text NOT NULL CHECK (kind IN ('component', 'permission')),
+    object_id uuid NOT NULL,
+    -- The component code or permission, kept readable after a purge.
+    name text NOT NULL,
+    -- Roles of every organization that held the component or permission.
+    role_ids uuid [] NOT NULL,
+    purge_after timestamptz,
+    actor_id uuid NOT NULL,
+    occurred_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_actor FOREIGN key (actor_id) REFERENCES users (user_id)
+);
@@ -0,0 +1,23 @@
+-- Add up migration script here
+-- Who did what, written in the same transaction as the change itself.
+CREATE TABLE audit_events (
+    event_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    -- NULL for events outside any organization, e.g. logins.
+    organization_id uuid,
+    -- NULL for failed logins with an unknown username.
+    actor_id uuid,
+    action text NOT NULL,
+    target_type text NOT NULL,
+    -- Usually a uuid, the username for logins.
+    target_id text,
+    before jsonb,
+    after jsonb,
+    request_id text,
+    ip text,
+    occurred_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id),
+    CONSTRAINT fk_actor FOREIGN key (actor_id) REFERENCES users (user_id)
+);
+
+CREATE INDEX idx_audit_events_organization ON audit_events (organization_id, occurred_at);
+CREATE INDEX idx_audit_events_actor ON audit_events (actor_id, occurred_at);
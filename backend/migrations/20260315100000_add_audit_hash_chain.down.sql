@@ -0,0 +1,21 @@
+-- Add down migration script here
+DROP TABLE audit_checkpoints;
+
+DROP TRIGGER audit_events_append_only ON audit_events;
+
+DROP FUNCTION forbid_audit_event_change ();
+
+DROP TRIGGER audit_events_chained ON audit_events;
+
+DROP FUNCTION chain_audit_event ();
+
+DROP FUNCTION audit_event_hash (audit_events);
+
+DROP TABLE audit_chain_heads;
+
+ALTER TABLE audit_events
+DROP CONSTRAINT audit_events_chain_sequence,
+DROP COLUMN chain_id,
+DROP COLUMN sequence,
+DROP COLUMN prev_hash,
+DROP COLUMN hash;
@@ -0,0 +1,110 @@
+-- Add up migration script here
+-- Audit events form one hash chain per organization, events outside any
+-- organization share the chain of the nil uuid. Each event stores the hash of
+-- its content together with the hash of its predecessor, so altering or
+-- removing an event breaks every link after it.
+ALTER TABLE audit_events
+ADD COLUMN chain_id uuid,
+ADD COLUMN sequence bigint,
+ADD COLUMN prev_hash bytea,
+ADD COLUMN hash bytea;
+
+CREATE TABLE audit_chain_heads (
+    chain_id uuid PRIMARY key,
+    sequence bigint NOT NULL,
+    hash bytea NOT NULL
+);
+
+-- occurred_at is hashed as epoch microseconds so the result does not depend
+-- on the TimeZone of the session.
+CREATE FUNCTION audit_event_hash (e audit_events) RETURNS bytea AS $$
+    SELECT sha256(
+        e.prev_hash || convert_to(
+            jsonb_build_array(
+                e.chain_id, e.sequence, e.event_id, e.organization_id, e.actor_id,
+                e.action, e.target_type, e.target_id, e.before, e.after,
+                e.request_id, e.ip,
+                (extract(epoch FROM e.occurred_at) * 1000000)::bigint
+            )::text,
+            'UTF8'
+        )
+    );
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Locking the head serializes the writers of a chain.
+CREATE FUNCTION chain_audit_event () RETURNS trigger AS $$
+DECLARE
+    head audit_chain_heads%ROWTYPE;
+BEGIN
+    NEW.chain_id := coalesce(NEW.organization_id, '00000000-0000-0000-0000-000000000000');
+    INSERT INTO audit_chain_heads (chain_id, sequence, hash)
+    VALUES (NEW.chain_id, 0, '')
+    ON CONFLICT (chain_id) DO NOTHING;
+    SELECT * INTO head FROM audit_chain_heads WHERE chain_id = NEW.chain_id FOR UPDATE;
+
+    NEW.sequence := head.sequence + 1;
+    NEW.prev_hash := head.hash;
+    NEW.hash := audit_event_hash(NEW);
+    UPDATE audit_chain_heads SET sequence = NEW.sequence, hash = NEW.hash
+    WHERE chain_id = NEW.chain_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DO $$
+DECLARE
+    e audit_events;
+    head audit_chain_heads%ROWTYPE;
+BEGIN
+    FOR e IN SELECT * FROM audit_events ORDER BY occurred_at, event_id LOOP
+        e.chain_id := coalesce(e.organization_id, '00000000-0000-0000-0000-000000000000');
+        INSERT INTO audit_chain_heads (chain_id, sequence, hash)
+        VALUES (e.chain_id, 0, '')
+        ON CONFLICT (chain_id) DO NOTHING;
+        SELECT * INTO head FROM audit_chain_heads WHERE chain_id = e.chain_id;
+
+        e.sequence := head.sequence + 1;
+        e.prev_hash := head.hash;
+        e.hash := audit_event_hash(e);
+        UPDATE audit_events
+        SET chain_id = e.chain_id, sequence = e.sequence, prev_hash = e.prev_hash, hash = e.hash
+        WHERE event_id = e.event_id;
+        UPDATE audit_chain_heads SET sequence = e.sequence, hash = e.hash
+        WHERE chain_id = e.chain_id;
+    END LOOP;
+END;
+$$;
+
+ALTER TABLE audit_events
+ALTER COLUMN chain_id SET NOT NULL,
+ALTER COLUMN sequence SET NOT NULL,
+ALTER COLUMN prev_hash SET NOT NULL,
+ALTER COLUMN hash SET NOT NULL,
+ADD CONSTRAINT audit_events_chain_sequence UNIQUE (chain_id, sequence);
+
+CREATE TRIGGER audit_events_chained
+BEFORE INSERT ON audit_events
+FOR EACH ROW EXECUTE FUNCTION chain_audit_event ();
+
+CREATE FUNCTION forbid_audit_event_change () RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit events are append-only'
+        USING ERRCODE = 'restrict_violation';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+BEFORE UPDATE OR DELETE ON audit_events
+FOR EACH ROW EXECUTE FUNCTION forbid_audit_event_change ();
+
+-- Signed snapshots of a chain head, exported and kept outside the database
+-- they prove the chain was not rewritten as a whole since.
+CREATE TABLE audit_checkpoints (
+    checkpoint_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    chain_id uuid NOT NULL,
+    sequence bigint NOT NULL,
+    hash bytea NOT NULL,
+    signature bytea NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT audit_checkpoints_chain_sequence UNIQUE (chain_id, sequence)
+);
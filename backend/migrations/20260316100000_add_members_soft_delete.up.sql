@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- Deleted members are kept until restored, lists skip them by default.
+ALTER TABLE members ADD COLUMN deleted_at timestamptz;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+ALTER TABLE projects
+DROP CONSTRAINT fk_created_by,
+DROP COLUMN created_by,
+DROP COLUMN created_at,
+DROP COLUMN updated_at,
+DROP COLUMN archived_at;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+-- Archived projects are read-only and hidden from lists by default.
+-- created_by is NULL for projects created before it was recorded.
+ALTER TABLE projects
+ADD COLUMN created_by uuid,
+ADD COLUMN created_at timestamptz NOT NULL DEFAULT now(),
+ADD COLUMN updated_at timestamptz NOT NULL DEFAULT now(),
+ADD COLUMN archived_at timestamptz,
+ADD CONSTRAINT fk_created_by FOREIGN key (created_by) REFERENCES users (user_id) ON DELETE SET NULL;
@@ -0,0 +1,22 @@
+-- Add up migration script here
+-- Tasks of the project board. position orders the unarchived tasks of a
+-- status column from 0, archived tasks keep their last position.
+CREATE TABLE tasks (
+    task_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    organization_id uuid NOT NULL,
+    project_id uuid NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    status text NOT NULL DEFAULT 'todo' CHECK (status IN ('todo', 'in_progress', 'done')),
+    assignee_id uuid,
+    position integer NOT NULL,
+    archived boolean NOT NULL DEFAULT FALSE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id),
+    CONSTRAINT fk_project FOREIGN key (project_id) REFERENCES projects (project_id) ON DELETE CASCADE,
+    CONSTRAINT fk_assignee FOREIGN key (assignee_id) REFERENCES members (member_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_tasks_board ON tasks (project_id, status, position)
+WHERE NOT archived;
+CREATE INDEX idx_tasks_organization ON tasks (organization_id);
@@ -0,0 +1,14 @@
+-- Add up migration script here
+-- Members assigned to a project, with their role in it. Deleting a member,
+-- even softly, or a project ends the assignment.
+CREATE TABLE project_members (
+    project_id uuid NOT NULL,
+    member_id uuid NOT NULL,
+    role text NOT NULL CHECK (role IN ('owner', 'maintainer', 'contributor')),
+    added_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY key (project_id, member_id),
+    CONSTRAINT fk_project FOREIGN key (project_id) REFERENCES projects (project_id) ON DELETE CASCADE,
+    CONSTRAINT fk_member FOREIGN key (member_id) REFERENCES members (member_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_members_member ON project_members (member_id);
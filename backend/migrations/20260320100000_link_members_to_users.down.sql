@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE members DROP COLUMN user_id;
+
+DELETE FROM users WHERE password_hash IS NULL;
+ALTER TABLE users ALTER COLUMN password_hash SET NOT NULL;
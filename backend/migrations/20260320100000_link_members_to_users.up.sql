@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- Invited users are pending until they set a password, they cannot log in.
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+-- The login user of a member, at most one member per user in an organization.
+ALTER TABLE members ADD COLUMN user_id uuid REFERENCES users (user_id) ON DELETE SET NULL;
+ALTER TABLE members ADD CONSTRAINT members_organization_id_user_id_key UNIQUE (organization_id, user_id);
@@ -0,0 +1,25 @@
+-- Add up migration script here
+-- Emailed invitations of members. The pending user of the member accepts by
+-- setting a password, and is then granted `role_ids`.
+CREATE TABLE invitations (
+    invitation_id uuid PRIMARY key DEFAULT gen_random_uuid(),
+    organization_id uuid NOT NULL,
+    member_id uuid NOT NULL,
+    -- The pending user, removed along with a revoked invitation.
+    user_id uuid,
+    email text NOT NULL,
+    role_ids uuid[] NOT NULL DEFAULT '{}',
+    status text NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'revoked')),
+    invited_by uuid,
+    -- Moved on every resend, which invalidates the links sent before.
+    expires_at timestamptz NOT NULL,
+    sent_at timestamptz NOT NULL DEFAULT now(),
+    created_at timestamptz NOT NULL DEFAULT now(),
+    closed_at timestamptz,
+    CONSTRAINT fk_organization FOREIGN key (organization_id) REFERENCES organizations (organization_id) ON DELETE CASCADE,
+    CONSTRAINT fk_member FOREIGN key (member_id) REFERENCES members (member_id) ON DELETE CASCADE,
+    CONSTRAINT fk_user FOREIGN key (user_id) REFERENCES users (user_id) ON DELETE SET NULL,
+    CONSTRAINT fk_invited_by FOREIGN key (invited_by) REFERENCES users (user_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_invitations_organization ON invitations (organization_id);
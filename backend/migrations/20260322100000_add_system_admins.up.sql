@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- System admins manage what every organization shares: the permission
+-- catalog and the component registry.
+ALTER TABLE users ADD COLUMN is_system_admin boolean NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE invitations DROP COLUMN send_error;
+UPDATE invitations SET sent_at = created_at WHERE sent_at IS NULL;
+ALTER TABLE invitations ALTER COLUMN sent_at SET DEFAULT now();
+ALTER TABLE invitations ALTER COLUMN sent_at SET NOT NULL;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- Invitations are emailed once committed: `sent_at` stays empty until the
+-- email goes out, `send_error` tells why it did not.
+ALTER TABLE invitations ALTER COLUMN sent_at DROP NOT NULL;
+ALTER TABLE invitations ALTER COLUMN sent_at DROP DEFAULT;
+ALTER TABLE invitations ADD COLUMN send_error text;
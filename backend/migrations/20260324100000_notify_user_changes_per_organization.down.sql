@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP TRIGGER organizations_users_changed ON organizations_users;
+DROP TRIGGER users_roles_changed ON users_roles;
+
+CREATE TRIGGER users_roles_changed
+AFTER INSERT OR UPDATE OR DELETE ON users_roles
+FOR EACH ROW EXECUTE FUNCTION notify_rbac_change ('user', 'user_id');
+
+DROP FUNCTION notify_membership_change ();
+DROP FUNCTION notify_role_grant_change ();
+DROP FUNCTION notify_user_change (uuid, uuid);
@@ -0,0 +1,47 @@
+-- Add up migration script here
+-- Changes of a user's grants or memberships name the organization, e.g.
+-- {"kind": "user", "id": "...", "organization_id": "..."}, so the cache entry
+-- of a user leaving an organization is evicted although the membership is
+-- gone by the time the notification is handled. The organization is null
+-- when the role went with the grant.
+CREATE FUNCTION notify_user_change (user_id uuid, organization_id uuid) RETURNS void AS $$
+    SELECT pg_notify(
+        'rbac_changes',
+        json_build_object('kind', 'user', 'id', user_id, 'organization_id', organization_id)::text
+    );
+$$ LANGUAGE sql;
+
+CREATE FUNCTION notify_role_grant_change () RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM notify_user_change(
+            OLD.user_id,
+            (SELECT organization_id FROM roles WHERE role_id = OLD.role_id)
+        );
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM notify_user_change(
+            NEW.user_id,
+            (SELECT organization_id FROM roles WHERE role_id = NEW.role_id)
+        );
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION notify_membership_change () RETURNS trigger AS $$
+BEGIN
+    PERFORM notify_user_change(OLD.user_id, OLD.organization_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER users_roles_changed ON users_roles;
+
+CREATE TRIGGER users_roles_changed
+AFTER INSERT OR UPDATE OR DELETE ON users_roles
+FOR EACH ROW EXECUTE FUNCTION notify_role_grant_change ();
+
+CREATE TRIGGER organizations_users_changed
+AFTER UPDATE OR DELETE ON organizations_users
+FOR EACH ROW EXECUTE FUNCTION notify_membership_change ();
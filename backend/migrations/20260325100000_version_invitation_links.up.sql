@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- Signed into the links of an invitation and bumped by every resend, so the
+-- links sent before stop working even within the same second.
+ALTER TABLE invitations ADD COLUMN token_version integer NOT NULL DEFAULT 0;
@@ -1,6 +1,17 @@
 use sqlx::{Pool, Postgres};
 
+use crate::audit::CheckpointSigner;
+use crate::authentication::InvitationSigner;
+use crate::authorization::PermissionCache;
+use crate::email_client::EmailClient;
+
 pub struct AppState {
     pub pool: Pool<Postgres>,
     pub base_url: String,
+    pub permission_cache: PermissionCache,
+    /// Verifies the signatures of audit checkpoints when configured.
+    pub checkpoint_signer: Option<CheckpointSigner>,
+    pub email_client: EmailClient,
+    /// Signs invitation links, invitations cannot be sent without it.
+    pub invitation_signer: Option<InvitationSigner>,
 }
@@ -0,0 +1,9 @@
+pub mod chain;
+mod checkpoint;
+mod context;
+mod event;
+pub mod models;
+
+pub use checkpoint::{CheckpointSigner, create_checkpoints, run_checkpoints};
+pub use context::{Audit, TrustedProxies};
+pub use event::{Actor, AuditEvent};
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use futures::TryStreamExt;
+use sqlx::PgConnection;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::audit::CheckpointSigner;
+use crate::audit::models::{BreakReason, ChainBreak, ChainReport};
+
+/// Chain of the events of `organization_id`, or of those outside any
+/// organization.
+pub fn chain_id(organization_id: Option<Uuid>) -> Uuid {
+    organization_id.unwrap_or_default()
+}
+
+/// Walks a chain from its first event, recomputing every hash, and checks its
+/// checkpoints. Stops at the first broken link, everything after it is
+/// unverifiable. Signatures are only checked with a `signer`.
+#[instrument(name = "Verify audit chain", skip(conn, signer))]
+pub async fn verify(
+    conn: &mut PgConnection,
+    chain_id: Uuid,
+    signer: Option<&CheckpointSigner>,
+) -> Result<ChainReport, anyhow::Error> {
+    let mut checkpoints = sqlx::query!(
+        "SELECT sequence, hash, signature FROM audit_checkpoints WHERE chain_id = $1",
+        chain_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .context("Failed to fetch audit checkpoints")?
+    .into_iter()
+    .map(|c| (c.sequence, (c.hash, c.signature)))
+    .collect::<BTreeMap<_, _>>();
+    let checkpoint_count = checkpoints.len() as u64;
+
+    let mut report = ChainReport {
+        chain_id,
+        events: 0,
+        checkpoints: checkpoint_count,
+        first_break: None,
+    };
+    let mut previous = Vec::new();
+    let mut expected_sequence = 1;
+
+    let mut events = sqlx::query!(
+        r#"
+        SELECT event_id, sequence, prev_hash, hash, audit_event_hash(e) as "computed!"
+        FROM audit_events e WHERE chain_id = $1
+        ORDER BY sequence
+        "#,
+        chain_id
+    )
+    .fetch(&mut *conn);
+    while let Some(event) = events
+        .try_next()
+        .await
+        .context("Failed to fetch audit events")?
+    {
+        let reason = if event.sequence != expected_sequence {
+            Some(BreakReason::MissingEvent)
+        } else if event.prev_hash != previous {
+            Some(BreakReason::LinkBroken)
+        } else if event.computed != event.hash {
+            Some(BreakReason::ContentAltered)
+        } else {
+            checkpoints
+                .remove(&event.sequence)
+                .and_then(|(hash, signature)| {
+                    checkpoint_break(
+                        chain_id,
+                        event.sequence,
+                        &event.hash,
+                        &hash,
+                        &signature,
+                        signer,
+                    )
+                })
+        };
+        if let Some(reason) = reason {
+            report.first_break = Some(ChainBreak {
+                sequence: expected_sequence,
+                event_id: (event.sequence == expected_sequence).then_some(event.event_id),
+                reason,
+            });
+            return Ok(report);
+        }
+        report.events += 1;
+        previous = event.hash;
+        expected_sequence += 1;
+    }
+
+    // A checkpoint past the last event proves the chain was cut short.
+    if let Some(&sequence) = checkpoints.keys().next() {
+        report.first_break = Some(ChainBreak {
+            sequence,
+            event_id: None,
+            reason: BreakReason::MissingEvent,
+        });
+    }
+    Ok(report)
+}
+
+fn checkpoint_break(
+    chain_id: Uuid,
+    sequence: i64,
+    event_hash: &[u8],
+    hash: &[u8],
+    signature: &[u8],
+    signer: Option<&CheckpointSigner>,
+) -> Option<BreakReason> {
+    if hash != event_hash {
+        Some(BreakReason::CheckpointMismatch)
+    } else if signer.is_some_and(|s| !s.verify(chain_id, sequence, hash, signature)) {
+        Some(BreakReason::InvalidSignature)
+    } else {
+        None
+    }
+}
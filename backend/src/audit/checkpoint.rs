@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use sha2::Sha256;
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Signs checkpoints of the audit chains with HMAC-SHA256.
+#[derive(Clone)]
+pub struct CheckpointSigner {
+    key: SecretString,
+}
+
+impl CheckpointSigner {
+    pub fn new(key: SecretString) -> Self {
+        Self { key }
+    }
+
+    pub fn sign(&self, chain_id: Uuid, sequence: i64, hash: &[u8]) -> Vec<u8> {
+        self.mac(chain_id, sequence, hash)
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    }
+
+    pub fn verify(&self, chain_id: Uuid, sequence: i64, hash: &[u8], signature: &[u8]) -> bool {
+        self.mac(chain_id, sequence, hash)
+            .verify_slice(signature)
+            .is_ok()
+    }
+
+    fn mac(&self, chain_id: Uuid, sequence: i64, hash: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.key.expose_secret().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(format!("{chain_id}:{sequence}:{}", hex::encode(hash)).as_bytes());
+        mac
+    }
+}
+
+/// Periodically checkpoints the audit chains that grew since their last
+/// checkpoint. Runs for the lifetime of the application.
+pub async fn run_checkpoints(pool: PgPool, signer: CheckpointSigner, every: Duration) {
+    let mut interval = tokio::time::interval(every);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        match create_checkpoints(&pool, &signer).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "Checkpointed audit chains"),
+            Err(e) => tracing::error!(error = ?e, "Failed to checkpoint audit chains"),
+        }
+    }
+}
+
+/// Signs the current head of every chain without a checkpoint at it, returns
+/// the number of checkpoints taken.
+#[instrument(name = "Checkpoint audit chains", skip_all)]
+pub async fn create_checkpoints(
+    pool: &PgPool,
+    signer: &CheckpointSigner,
+) -> Result<u64, anyhow::Error> {
+    let heads = sqlx::query!(
+        r#"
+        SELECT h.chain_id, h.sequence, h.hash FROM audit_chain_heads h
+        WHERE h.sequence > 0 AND NOT EXISTS (
+            SELECT 1 FROM audit_checkpoints c
+            WHERE c.chain_id = h.chain_id AND c.sequence = h.sequence
+        )
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch audit chain heads")?;
+
+    let mut created = 0;
+    for head in heads {
+        let signature = signer.sign(head.chain_id, head.sequence, &head.hash);
+        created += sqlx::query!(
+            r#"
+            INSERT INTO audit_checkpoints (chain_id, sequence, hash, signature)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (chain_id, sequence) DO NOTHING
+            "#,
+            head.chain_id,
+            head.sequence,
+            head.hash,
+            signature
+        )
+        .execute(pool)
+        .await
+        .context("Failed to store audit checkpoint")?
+        .rows_affected();
+    }
+    Ok(created)
+}
@@ -0,0 +1,124 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::Context;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use sqlx::PgConnection;
+
+use super::event::{Actor, AuditEvent};
+
+/// Where the request comes from, recorded along with the audit events it
+/// causes. The request id is the `x-request-id` header, set by the router
+/// when the client did not. The ip is the peer address, unless the peer is
+/// one of the [`TrustedProxies`]: then it is the right-most address of
+/// `x-forwarded-for` not added by one of them.
+#[derive(Debug, Clone, Default)]
+pub struct Audit {
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+}
+
+/// The proxies in front of the backend, from `audit.trusted_proxies`. A
+/// request extension, `x-forwarded-for` is ignored without it.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(pub Vec<IpAddr>);
+
+impl TrustedProxies {
+    /// The client behind the proxies of `forwarded_for`, a `x-forwarded-for`
+    /// value of requests coming from one of them. Each proxy appends the
+    /// address it got the request from, so the first address from the right
+    /// which is not a proxy is the client; anything left of it may be forged.
+    fn client(&self, forwarded_for: &str) -> Option<IpAddr> {
+        for address in forwarded_for.rsplit(',') {
+            let ip = address.trim().parse::<IpAddr>().ok()?;
+            if !self.0.contains(&ip) {
+                return Some(ip);
+            }
+        }
+        None
+    }
+}
+
+impl<S> FromRequestParts<S> for Audit
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let request_id = header("x-request-id").map(str::to_string);
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let forwarded = match (peer, parts.extensions.get::<TrustedProxies>()) {
+            (Some(peer), Some(proxies)) if proxies.0.contains(&peer) => {
+                header("x-forwarded-for").and_then(|value| proxies.client(value))
+            }
+            _ => None,
+        };
+        let ip = forwarded.or(peer).map(|ip| ip.to_string());
+
+        Ok(Self { request_id, ip })
+    }
+}
+
+impl Audit {
+    /// Writes the event through `connection`, so within the transaction of
+    /// the change it records.
+    pub async fn record(
+        &self,
+        connection: &mut PgConnection,
+        actor: impl Into<Actor>,
+        event: AuditEvent,
+    ) -> Result<(), anyhow::Error> {
+        let actor = actor.into();
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_events (organization_id, actor_id, action, target_type, target_id,
+                before, after, request_id, ip)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            actor.organization_id,
+            actor.user_id,
+            event.action,
+            event.target_type,
+            event.target_id,
+            event.before,
+            event.after,
+            self.request_id,
+            self.ip
+        )
+        .execute(connection)
+        .await
+        .with_context(|| format!("Failed to record audit event {}", event.action))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwarded_client_is_the_right_most_untrusted_address() {
+        let proxies = TrustedProxies(vec!["10.0.0.1".parse().unwrap()]);
+        let client = |value: &str| proxies.client(value).map(|ip| ip.to_string());
+
+        assert_eq!(client("203.0.113.7"), Some("203.0.113.7".to_string()));
+        assert_eq!(
+            client("1.2.3.4, 203.0.113.7, 10.0.0.1"),
+            Some("203.0.113.7".to_string())
+        );
+        assert_eq!(client("10.0.0.1"), None);
+        assert_eq!(client("1.2.3.4, not-an-ip"), None);
+    }
+}
@@ -0,0 +1,106 @@
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::routers::session_state::TypeSession;
+
+/// Who an audit event is attributed to. Events of an organization are
+/// listed to its members, the others only to the organizations of their
+/// actor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Actor {
+    pub user_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+}
+
+impl Actor {
+    pub fn user(user_id: Uuid) -> Self {
+        Self {
+            user_id: Some(user_id),
+            organization_id: None,
+        }
+    }
+
+    /// Nobody known, e.g. a login with an unknown username.
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+}
+
+impl From<Tenant> for Actor {
+    fn from(tenant: Tenant) -> Self {
+        Self {
+            user_id: Some(tenant.user_id),
+            organization_id: Some(tenant.organization_id),
+        }
+    }
+}
+
+/// The session's user and organization, for routes that do not require
+/// either, e.g. those editing the global permission catalog.
+impl<S> FromRequestParts<S> for Actor
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let session = TypeSession::from_request_parts(parts, state)
+            .await
+            .map_err(|(_, e)| AppError::E500(anyhow::anyhow!(e)))?;
+        Ok(Self {
+            user_id: session.get_user_id(),
+            organization_id: session.get_organization_id(),
+        })
+    }
+}
+
+/// One change to record, e.g.
+/// `AuditEvent::new("role.create", "role", role_id).after(&role)`. Actions
+/// are `<target type>.<verb>`, with the state of the target before and after
+/// the change where it has one.
+#[derive(Debug)]
+pub struct AuditEvent {
+    pub(super) action: &'static str,
+    pub(super) target_type: &'static str,
+    pub(super) target_id: Option<String>,
+    pub(super) before: Option<Value>,
+    pub(super) after: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: &'static str, target_type: &'static str, target_id: impl ToString) -> Self {
+        Self {
+            action,
+            target_type,
+            target_id: Some(target_id.to_string()),
+            before: None,
+            after: None,
+        }
+    }
+
+    /// For changes to a whole catalog rather than one object of it.
+    pub fn untargeted(action: &'static str, target_type: &'static str) -> Self {
+        Self {
+            action,
+            target_type,
+            target_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before(mut self, state: impl Serialize) -> Self {
+        self.before = serde_json::to_value(state).ok();
+        self
+    }
+
+    pub fn after(mut self, state: impl Serialize) -> Self {
+        self.after = serde_json::to_value(state).ok();
+        self
+    }
+}
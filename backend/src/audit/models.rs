@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+/// A row of `audit_events`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AuditLogEntry {
+    pub event_id: uuid::Uuid,
+    pub organization_id: Option<uuid::Uuid>,
+    pub actor_id: Option<uuid::Uuid>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Filter of `GET /audit-events`. `since` and `until` bound `occurred_at`,
+/// inclusive and exclusive.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AuditEventFilter {
+    pub actor_id: Option<uuid::Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub columns: AuditColumnFilter,
+}
+
+/// The text columns of [`AuditEventFilter`], matched exactly.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AuditColumnFilter {
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub request_id: Option<String>,
+}
+
+/// Outcome of `GET /audit-events/verify`, `first_break` is `None` for an
+/// intact chain.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChainReport {
+    pub chain_id: uuid::Uuid,
+    /// Events verified before the first break.
+    pub events: u64,
+    pub checkpoints: u64,
+    pub first_break: Option<ChainBreak>,
+}
+
+/// First link of a chain that does not verify. `event_id` is `None` when the
+/// event at `sequence` is missing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChainBreak {
+    pub sequence: i64,
+    pub event_id: Option<uuid::Uuid>,
+    pub reason: BreakReason,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakReason {
+    /// The event differs from what was hashed when it was recorded.
+    ContentAltered,
+    /// The event does not follow the hash of its predecessor.
+    LinkBroken,
+    /// Events are missing before the sequence.
+    MissingEvent,
+    /// A checkpoint was taken over a different hash.
+    CheckpointMismatch,
+    /// A checkpoint was not signed with the configured key.
+    InvalidSignature,
+}
+
+/// A row of `audit_checkpoints`, hash and signature hex encoded. The
+/// signature is the HMAC-SHA256 of `"{chain_id}:{sequence}:{hash}"`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    pub checkpoint_id: uuid::Uuid,
+    pub chain_id: uuid::Uuid,
+    pub sequence: i64,
+    pub hash: String,
+    pub signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query of `GET /audit-events/export`, events from `since` on.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AuditExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One JSON [`AuditLogEntry`] per line.
+    #[default]
+    Ndjson,
+    /// A header line, then one row per event with `before` and `after` as
+    /// JSON text.
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+        }
+    }
+}
@@ -1,7 +1,8 @@
+mod invitation;
 mod middleware;
 mod password;
 
-pub use middleware::*;
-pub use password::{
-    AuthError, Credentials, change_password, validate_credentials,
-};
+pub use invitation::InvitationSigner;
+pub(crate) use middleware::*;
+pub(crate) use password::{PASSWORD_LENGTH, change_password};
+pub use password::{AuthError, Credentials, hash_password, validate_credentials};
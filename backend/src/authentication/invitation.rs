@@ -0,0 +1,112 @@
+use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// Signs the tokens of invitation links with HMAC-SHA256. A token carries the
+/// invitation, its version and its expiry, and is only good for as long as
+/// they match the stored invitation: resending one bumps the version, which
+/// invalidates the links sent before.
+#[derive(Clone)]
+pub struct InvitationSigner {
+    key: SecretString,
+    expiry: TimeDelta,
+}
+
+impl InvitationSigner {
+    /// Fails unless invitations expire in at least an hour and at most a
+    /// year.
+    pub fn new(key: SecretString, expiry_hours: u64) -> Result<Self, anyhow::Error> {
+        let expiry = i64::try_from(expiry_hours)
+            .ok()
+            .and_then(TimeDelta::try_hours)
+            .filter(|expiry| (TimeDelta::hours(1)..=TimeDelta::days(365)).contains(expiry))
+            .with_context(|| {
+                format!("invitations.expiry_hours must be between 1 and 8760, not {expiry_hours}")
+            })?;
+        Ok(Self { key, expiry })
+    }
+
+    /// Expiry of an invitation sent now, to the second as tokens carry it.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        let expires_at = Utc::now() + self.expiry;
+        DateTime::from_timestamp(expires_at.timestamp(), 0).unwrap_or(expires_at)
+    }
+
+    pub fn sign(&self, invitation_id: Uuid, version: i32, expires_at: DateTime<Utc>) -> String {
+        let mut token = payload(invitation_id, version, expires_at.timestamp());
+        token.extend(self.mac(&token).finalize().into_bytes());
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// The invitation, version and expiry of a token signed by us, expired or
+    /// not.
+    pub fn verify(&self, token: &str) -> Option<(Uuid, i32, DateTime<Utc>)> {
+        let token = URL_SAFE_NO_PAD.decode(token).ok()?;
+        if token.len() <= PAYLOAD_LEN {
+            return None;
+        }
+        let (payload, signature) = token.split_at(PAYLOAD_LEN);
+        self.mac(payload).verify_slice(signature).ok()?;
+
+        let (invitation_id, rest) = payload.split_at(16);
+        let (version, expires_at) = rest.split_at(4);
+        let invitation_id = Uuid::from_slice(invitation_id).ok()?;
+        let version = i32::from_be_bytes(version.try_into().ok()?);
+        let expires_at = i64::from_be_bytes(expires_at.try_into().ok()?);
+        Some((
+            invitation_id,
+            version,
+            DateTime::from_timestamp(expires_at, 0)?,
+        ))
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.key.expose_secret().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+const PAYLOAD_LEN: usize = 16 + 4 + 8;
+
+fn payload(invitation_id: Uuid, version: i32, expires_at: i64) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(PAYLOAD_LEN);
+    payload.extend(invitation_id.as_bytes());
+    payload.extend(version.to_be_bytes());
+    payload.extend(expires_at.to_be_bytes());
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invitation_expiry_is_bounded() {
+        let key = || SecretString::from("key".to_string());
+        assert!(InvitationSigner::new(key(), 72).is_ok());
+        for expiry_hours in [0, 24 * 366, u64::MAX] {
+            assert!(InvitationSigner::new(key(), expiry_hours).is_err());
+        }
+    }
+
+    #[test]
+    fn test_invitation_tokens_carry_their_version() {
+        let signer = InvitationSigner::new(SecretString::from("key".to_string()), 72).unwrap();
+        let other = InvitationSigner::new(SecretString::from("other".to_string()), 72).unwrap();
+        let invitation_id = Uuid::new_v4();
+        let expires_at = signer.expires_at();
+
+        let first = signer.sign(invitation_id, 0, expires_at);
+        let second = signer.sign(invitation_id, 1, expires_at);
+        assert_ne!(first, second);
+        assert_eq!(signer.verify(&second), Some((invitation_id, 1, expires_at)));
+        assert_eq!(other.verify(&second), None);
+    }
+}
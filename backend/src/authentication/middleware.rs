@@ -6,7 +6,7 @@ use axum::{
 use uuid::Uuid;
 
 #[derive(Clone, Copy, Debug)]
-pub struct UserId(Uuid);
+pub(crate) struct UserId(Uuid);
 
 impl std::fmt::Display for UserId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -23,12 +23,12 @@ impl std::ops::Deref for UserId {
 }
 
 impl UserId {
-    pub fn into_inner(self) -> Uuid {
+    pub(crate) fn into_inner(self) -> Uuid {
         self.0
     }
 }
 
-pub async fn reject_anonymous_users(request: Request, next: Next) -> Response {
+pub(crate) async fn reject_anonymous_users(request: Request, next: Next) -> Response {
     let (mut parts, body) = request.into_parts();
     let session =
         match crate::routers::session_state::TypeSession::from_request_parts(&mut parts, &()).await
@@ -7,7 +7,12 @@ use secrecy::{ExposeSecret, SecretString};
 use sqlx::PgPool;
 use tracing::instrument;
 
+use crate::audit::{Actor, Audit, AuditEvent};
 use crate::telemetry::spawn_blocking_with_tracing;
+
+/// Characters a password chosen by a user may have.
+pub(crate) const PASSWORD_LENGTH: std::ops::RangeInclusive<usize> = 8..=128;
+
 pub struct Credentials {
     pub username: String,
     pub password: SecretString,
@@ -54,6 +59,7 @@ pub async fn validate_credentials(
     user_id.ok_or_else(|| AuthError::InvalidCredentials(anyhow::anyhow!("Unknown username",)))
 }
 
+/// Pending users, who have no password yet, have no credentials either.
 #[instrument(name = "get stored credentials", skip(pool, username))]
 async fn get_stored_credentials(
     pool: &PgPool,
@@ -61,9 +67,9 @@ async fn get_stored_credentials(
 ) -> Result<Option<(uuid::Uuid, SecretString)>, anyhow::Error> {
     let user = sqlx::query!(
         r#"
-        SELECT user_id, password_hash
+        SELECT user_id, password_hash as "password_hash!"
         FROM users
-        WHERE username = $1
+        WHERE username = $1 AND password_hash IS NOT NULL
         "#,
         username
     )
@@ -96,15 +102,15 @@ fn verify_password_hash(
         .map_err(AuthError::InvalidCredentials)
 }
 
-#[tracing::instrument(name = "Change password", skip(password, pool))]
-pub async fn change_password(
+#[tracing::instrument(name = "Change password", skip(password, pool, audit))]
+pub(crate) async fn change_password(
     user_id: uuid::Uuid,
     password: SecretString,
     pool: &PgPool,
+    audit: &Audit,
 ) -> Result<(), anyhow::Error> {
-    let password_hash = spawn_blocking_with_tracing(move || compute_password_hash(password))
-        .await?
-        .context("Failed to hash password")?;
+    let password_hash = hash_password(password).await?;
+    let mut transaction = pool.begin().await.context("Failed to start transaction")?;
 
     sqlx::query!(
         r#"
@@ -115,13 +121,32 @@ pub async fn change_password(
         password_hash.expose_secret(),
         user_id
     )
-    .execute(pool)
+    .execute(&mut *transaction)
     .await
     .context("Failed to change user's password in the database.")?;
 
+    audit
+        .record(
+            &mut transaction,
+            Actor::user(user_id),
+            AuditEvent::new("auth.password_change", "user", user_id),
+        )
+        .await?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")?;
+
     Ok(())
 }
 
+/// Hashes off the async runtime, argon2 being slow on purpose.
+pub async fn hash_password(password: SecretString) -> Result<SecretString, anyhow::Error> {
+    spawn_blocking_with_tracing(move || compute_password_hash(password))
+        .await?
+        .context("Failed to hash password")
+}
+
 fn compute_password_hash(password: SecretString) -> Result<SecretString, anyhow::Error> {
     let salt = SaltString::generate(&mut OsRng);
 
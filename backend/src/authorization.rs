@@ -0,0 +1,18 @@
+mod cache;
+mod condition;
+mod expiry;
+mod invalidation;
+mod matcher;
+mod resolver;
+mod system_admin;
+mod tenant;
+
+pub use cache::{CacheMetrics, PermissionCache};
+pub use condition::{Attributes, Condition, ConditionError, SqlColumns, Value, validate_condition};
+pub(crate) use expiry::expire_stale_requests;
+pub use expiry::run_expiry;
+pub use invalidation::invalidate_on_changes;
+pub use matcher::{Target, matches, resource_matches, validate_action, validate_resource};
+pub use resolver::{EffectivePermissions, ProjectPermissions, resolve_permissions};
+pub use system_admin::{SystemAdmin, is_system_admin};
+pub use tenant::Tenant;
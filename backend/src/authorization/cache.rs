@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Context;
+use chrono::Utc;
+use redis::AsyncCommands;
+use redis_pool::SingleRedisPool;
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::resolver::{EffectivePermissions, next_grant_change, resolve_permissions};
+use crate::configuration::PermissionCacheSettings;
+
+const KEY_PREFIX: &str = "effective_permissions:";
+
+/// Caches each user's [`EffectivePermissions`] per organization in redis. Entries are evicted
+/// by [`invalidate_on_changes`](super::invalidate_on_changes) as soon as the
+/// underlying grants change; the TTL only bounds the damage of a missed
+/// notification. Entries never outlive the next start or end of one of the
+/// user's grants, as nothing notifies about those. Redis failures never fail
+/// a request, the cache is bypassed instead.
+#[derive(Clone)]
+pub struct PermissionCache {
+    redis: Option<SingleRedisPool>,
+    ttl_seconds: u64,
+    metrics: Arc<CacheMetrics>,
+}
+
+#[derive(Default, Debug)]
+pub struct CacheMetrics {
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+    pub invalidations: AtomicU64,
+}
+
+impl PermissionCache {
+    pub fn new(redis: SingleRedisPool, settings: &PermissionCacheSettings) -> Self {
+        Self {
+            redis: settings.enabled.then_some(redis),
+            ttl_seconds: settings.ttl_seconds.get(),
+            metrics: Arc::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    pub fn metrics(&self) -> &CacheMetrics {
+        &self.metrics
+    }
+
+    #[instrument(name = "Get effective permissions", skip(self, pool))]
+    pub async fn effective_permissions(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        organization_id: Uuid,
+    ) -> Result<EffectivePermissions, anyhow::Error> {
+        let Some(redis) = &self.redis else {
+            return resolve_permissions(pool, user_id, organization_id).await;
+        };
+
+        let key = Self::key(organization_id, user_id);
+        match Self::get(redis, &key).await {
+            Ok(Some(permissions)) => {
+                self.metrics.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(permissions);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = ?e, "Failed to read the permission cache"),
+        }
+        self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+
+        let permissions = resolve_permissions(pool, user_id, organization_id).await?;
+        let ttl_seconds = match next_grant_change(pool, user_id, organization_id).await? {
+            Some(at) => {
+                let until = (at - Utc::now()).num_seconds().max(0) as u64 + 1;
+                until.min(self.ttl_seconds)
+            }
+            None => self.ttl_seconds,
+        };
+        if let Err(e) = Self::set(redis, &key, &permissions, ttl_seconds).await {
+            tracing::warn!(error = ?e, "Failed to write the permission cache");
+        }
+
+        Ok(permissions)
+    }
+
+    /// Evicts the entries of the given `(organization_id, user_id)` pairs.
+    #[instrument(name = "Invalidate cached permissions", skip(self))]
+    pub async fn invalidate(&self, entries: &[(Uuid, Uuid)]) -> Result<(), anyhow::Error> {
+        let Some(redis) = &self.redis else {
+            return Ok(());
+        };
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let keys = entries
+            .iter()
+            .map(|(organization_id, user_id)| Self::key(*organization_id, *user_id))
+            .collect::<Vec<_>>();
+        let mut connection = redis.acquire().await?;
+        let _: usize = connection
+            .del(keys)
+            .await
+            .context("Failed to delete cached permissions")?;
+        self.metrics
+            .invalidations
+            .fetch_add(entries.len() as u64, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Drops every cached entry, used when change notifications may have been
+    /// missed.
+    #[instrument(name = "Invalidate all cached permissions", skip(self))]
+    pub async fn invalidate_all(&self) -> Result<(), anyhow::Error> {
+        let Some(redis) = &self.redis else {
+            return Ok(());
+        };
+
+        let mut connection = redis.acquire().await?;
+        let mut cursor = 0_u64;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(format!("{KEY_PREFIX}*"))
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut *connection)
+                .await
+                .context("Failed to scan cached permissions")?;
+            if !keys.is_empty() {
+                let count: usize = connection
+                    .del(keys)
+                    .await
+                    .context("Failed to delete cached permissions")?;
+                self.metrics
+                    .invalidations
+                    .fetch_add(count as u64, Ordering::Relaxed);
+            }
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(())
+    }
+
+    fn key(organization_id: Uuid, user_id: Uuid) -> String {
+        format!("{KEY_PREFIX}{organization_id}:{user_id}")
+    }
+
+    async fn get(
+        redis: &SingleRedisPool,
+        key: &str,
+    ) -> Result<Option<EffectivePermissions>, anyhow::Error> {
+        let mut connection = redis.acquire().await?;
+        let cached: Option<String> = connection.get(key).await?;
+        cached
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .context("Failed to deserialize cached permissions")
+    }
+
+    async fn set(
+        redis: &SingleRedisPool,
+        key: &str,
+        permissions: &EffectivePermissions,
+        ttl_seconds: u64,
+    ) -> Result<(), anyhow::Error> {
+        let value = serde_json::to_string(permissions)?;
+        let mut connection = redis.acquire().await?;
+        let _: () = connection.set_ex(key, value, ttl_seconds).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,720 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+use validator::ValidationError;
+
+/// Attribute roots a condition may refer to. `user` and `time` are filled in
+/// by the server, `resource` and `request` by whoever evaluates the
+/// condition.
+const ROOTS: [&str; 4] = ["user", "resource", "request", "time"];
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ConditionError {
+    #[error("{message} at position {position}")]
+    Syntax { position: usize, message: String },
+    #[error("unknown attribute `{0}`, attributes start with user, resource, request or time")]
+    UnknownAttribute(String),
+    #[error("attribute `{0}` has no column to filter on")]
+    UnmappedAttribute(String),
+    #[error("attribute `{attribute}` is text and cannot be {usage}")]
+    TypeMismatch {
+        attribute: String,
+        usage: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn loosely_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(_), Value::String(_)) | (Value::String(_), Value::Number(_)) => {
+                self.as_number().is_some() && self.as_number() == other.as_number()
+            }
+            _ => self == other,
+        }
+    }
+
+    fn compare(&self, other: &Value) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            _ => self.as_number()?.partial_cmp(&other.as_number()?),
+        }
+    }
+}
+
+/// Values of the attributes conditions are evaluated against, keyed by their
+/// full path such as `user.id`. Missing attributes are `null`.
+#[derive(Debug, Clone, Default)]
+pub struct Attributes(HashMap<String, Value>);
+
+impl Attributes {
+    /// `user.id`, `user.organization_id` and the current UTC `time.hour`,
+    /// `time.minute` and `time.weekday` (1 for Monday to 7 for Sunday).
+    pub fn for_user(user_id: Uuid, organization_id: Uuid, now: DateTime<Utc>) -> Self {
+        let mut attributes = Self::default();
+        attributes.insert("user.id", Value::String(user_id.to_string()));
+        attributes.insert(
+            "user.organization_id",
+            Value::String(organization_id.to_string()),
+        );
+        attributes.insert("time.hour", Value::Number(now.hour().into()));
+        attributes.insert("time.minute", Value::Number(now.minute().into()));
+        attributes.insert(
+            "time.weekday",
+            Value::Number(now.weekday().number_from_monday().into()),
+        );
+        attributes
+    }
+
+    pub fn insert(&mut self, path: &str, value: Value) {
+        self.0.insert(path.to_string(), value);
+    }
+
+    fn get(&self, path: &str) -> Value {
+        self.0.get(path).cloned().unwrap_or(Value::Null)
+    }
+}
+
+/// Maps `resource.*` attributes to SQL text expressions of the listed table,
+/// e.g. `resource.id` to `project_id::text`.
+pub type SqlColumns<'a> = &'a [(&'a str, &'a str)];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Attribute(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+}
+
+/// A boolean expression over attributes attached to a permission, e.g.
+/// `resource.owner_id == user.id && time.hour < 18`. Supports `&&`, `||`,
+/// `!`, parentheses, the comparisons `== != < <= > >=`, string literals in
+/// single or double quotes, numbers, `true`, `false` and `null`.
+///
+/// A condition holds only if it evaluates to `true`. Comparing a string with
+/// a number compares them numerically if the string is a number, any other
+/// comparison between different types or with `null` is false, except for
+/// `==` and `!=`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition(Expr);
+
+impl Condition {
+    pub fn parse(input: &str) -> Result<Self, ConditionError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: input.len(),
+        };
+        let expr = parser.or()?;
+        if let Some((position, token)) = parser.tokens.get(parser.position) {
+            return Err(ConditionError::Syntax {
+                position: *position,
+                message: format!("unexpected {token:?}"),
+            });
+        }
+        Ok(Self(expr))
+    }
+
+    pub fn evaluate(&self, attributes: &Attributes) -> bool {
+        evaluate(&self.0, attributes) == Value::Bool(true)
+    }
+
+    /// Whether every `resource.*` attribute of the condition has a column.
+    pub fn check_columns(&self, columns: SqlColumns) -> Result<(), ConditionError> {
+        check_columns(&self.0, columns)
+    }
+
+    /// Appends the condition as a SQL boolean expression selecting the rows
+    /// it holds for, which never differs from [`evaluate`](Self::evaluate).
+    /// Parts without `resource.*` attributes are evaluated and bound. Fails,
+    /// appending nothing, if a column is missing or a column is used as
+    /// anything but text: as a boolean, or compared with a number or a
+    /// boolean.
+    pub fn push_sql(
+        &self,
+        qb: &mut QueryBuilder<'_, Postgres>,
+        attributes: &Attributes,
+        columns: SqlColumns,
+    ) -> Result<(), ConditionError> {
+        check_sql(&self.0, attributes, columns)?;
+        push_sql(&self.0, qb, attributes, columns);
+        Ok(())
+    }
+}
+
+/// Validates the `condition` of a permission on save.
+pub fn validate_condition(condition: &str) -> Result<(), ValidationError> {
+    Condition::parse(condition).map(|_| ()).map_err(|e| {
+        let mut error = ValidationError::new("condition");
+        error.message = Some(e.to_string().into());
+        error
+    })
+}
+
+fn evaluate(expr: &Expr, attributes: &Attributes) -> Value {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Attribute(path) => attributes.get(path),
+        Expr::Not(inner) => match evaluate(inner, attributes) {
+            Value::Bool(b) => Value::Bool(!b),
+            _ => Value::Null,
+        },
+        Expr::And(left, right) => Value::Bool(
+            evaluate(left, attributes) == Value::Bool(true)
+                && evaluate(right, attributes) == Value::Bool(true),
+        ),
+        Expr::Or(left, right) => Value::Bool(
+            evaluate(left, attributes) == Value::Bool(true)
+                || evaluate(right, attributes) == Value::Bool(true),
+        ),
+        Expr::Compare(op, left, right) => {
+            let (left, right) = (evaluate(left, attributes), evaluate(right, attributes));
+            let result = match op {
+                CompareOp::Eq => left.loosely_equals(&right),
+                CompareOp::Ne => !left.loosely_equals(&right),
+                CompareOp::Lt => left.compare(&right).is_some_and(|o| o.is_lt()),
+                CompareOp::Le => left.compare(&right).is_some_and(|o| o.is_le()),
+                CompareOp::Gt => left.compare(&right).is_some_and(|o| o.is_gt()),
+                CompareOp::Ge => left.compare(&right).is_some_and(|o| o.is_ge()),
+            };
+            Value::Bool(result)
+        }
+    }
+}
+
+fn column<'a>(path: &str, columns: SqlColumns<'a>) -> Option<Result<&'a str, ConditionError>> {
+    let name = path.strip_prefix("resource.")?;
+    let column = columns
+        .iter()
+        .find(|(attribute, _)| *attribute == name)
+        .map(|(_, column)| *column)
+        .ok_or_else(|| ConditionError::UnmappedAttribute(path.to_string()));
+    Some(column)
+}
+
+fn check_columns(expr: &Expr, columns: SqlColumns) -> Result<(), ConditionError> {
+    match expr {
+        Expr::Literal(_) => Ok(()),
+        Expr::Attribute(path) => column(path, columns).transpose().map(|_| ()),
+        Expr::Not(inner) => check_columns(inner, columns),
+        Expr::And(left, right) | Expr::Or(left, right) | Expr::Compare(_, left, right) => {
+            check_columns(left, columns)?;
+            check_columns(right, columns)
+        }
+    }
+}
+
+fn has_columns(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_) => false,
+        Expr::Attribute(path) => path.starts_with("resource."),
+        Expr::Not(inner) => has_columns(inner),
+        Expr::And(left, right) | Expr::Or(left, right) | Expr::Compare(_, left, right) => {
+            has_columns(left) || has_columns(right)
+        }
+    }
+}
+
+/// A side of a comparison in SQL: a text column, or a value evaluated
+/// beforehand.
+enum Operand<'a> {
+    Column(&'a str),
+    Value(Value),
+}
+
+fn operand<'a>(
+    expr: &Expr,
+    attributes: &Attributes,
+    columns: SqlColumns<'a>,
+) -> Result<Operand<'a>, ConditionError> {
+    if !has_columns(expr) {
+        return Ok(Operand::Value(evaluate(expr, attributes)));
+    }
+    match expr {
+        Expr::Attribute(path) => match column(path, columns) {
+            Some(column) => column.map(Operand::Column),
+            None => unreachable!("has_columns only matches resource attributes"),
+        },
+        _ => Err(mismatch(expr, "compared with a comparison")),
+    }
+}
+
+/// Names the first `resource.*` attribute of `expr`.
+fn mismatch(expr: &Expr, usage: &'static str) -> ConditionError {
+    fn first(expr: &Expr) -> Option<&str> {
+        match expr {
+            Expr::Literal(_) => None,
+            Expr::Attribute(path) => path.starts_with("resource.").then_some(path.as_str()),
+            Expr::Not(inner) => first(inner),
+            Expr::And(left, right) | Expr::Or(left, right) | Expr::Compare(_, left, right) => {
+                first(left).or_else(|| first(right))
+            }
+        }
+    }
+    ConditionError::TypeMismatch {
+        attribute: first(expr).unwrap_or_default().to_string(),
+        usage,
+    }
+}
+
+fn check_sql(
+    expr: &Expr,
+    attributes: &Attributes,
+    columns: SqlColumns,
+) -> Result<(), ConditionError> {
+    check_columns(expr, columns)?;
+    if !has_columns(expr) {
+        return Ok(());
+    }
+    match expr {
+        Expr::Literal(_) => Ok(()),
+        Expr::Attribute(_) => Err(mismatch(expr, "used as a boolean")),
+        Expr::Not(inner) => check_sql(inner, attributes, columns),
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            check_sql(left, attributes, columns)?;
+            check_sql(right, attributes, columns)
+        }
+        Expr::Compare(_, left, right) => {
+            let operands = (
+                operand(left, attributes, columns)?,
+                operand(right, attributes, columns)?,
+            );
+            match operands {
+                (Operand::Column(_), Operand::Value(Value::Number(_)))
+                | (Operand::Value(Value::Number(_)), Operand::Column(_)) => {
+                    Err(mismatch(expr, "compared with a number"))
+                }
+                (Operand::Column(_), Operand::Value(Value::Bool(_)))
+                | (Operand::Value(Value::Bool(_)), Operand::Column(_)) => {
+                    Err(mismatch(expr, "compared with a boolean"))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+/// Expects [`check_sql`] to have passed. Never yields `NULL`, except where
+/// [`evaluate`] yields `null`: negating a value that is not a boolean.
+fn push_sql(
+    expr: &Expr,
+    qb: &mut QueryBuilder<'_, Postgres>,
+    attributes: &Attributes,
+    columns: SqlColumns,
+) {
+    if !has_columns(expr) {
+        match evaluate(expr, attributes) {
+            Value::Bool(b) => qb.push_bind(b),
+            _ => qb.push("NULL"),
+        };
+        return;
+    }
+    match expr {
+        Expr::Literal(_) | Expr::Attribute(_) => unreachable!("checked by check_sql"),
+        Expr::Not(inner) => {
+            qb.push("(NOT ");
+            push_sql(inner, qb, attributes, columns);
+            qb.push(")");
+        }
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            // `evaluate` takes `null` for false here.
+            let operator = if matches!(expr, Expr::And(..)) {
+                " AND "
+            } else {
+                " OR "
+            };
+            qb.push("COALESCE(");
+            push_sql(left, qb, attributes, columns);
+            qb.push(operator);
+            push_sql(right, qb, attributes, columns);
+            qb.push(", FALSE)");
+        }
+        Expr::Compare(op, left, right) => {
+            let (Ok(left), Ok(right)) = (
+                operand(left, attributes, columns),
+                operand(right, attributes, columns),
+            ) else {
+                unreachable!("checked by check_sql");
+            };
+            let operator = match op {
+                CompareOp::Eq => " IS NOT DISTINCT FROM ",
+                CompareOp::Ne => " IS DISTINCT FROM ",
+                CompareOp::Lt => " < ",
+                CompareOp::Le => " <= ",
+                CompareOp::Gt => " > ",
+                CompareOp::Ge => " >= ",
+            };
+            let null = |operand: &Operand| matches!(operand, Operand::Value(Value::Null));
+            if matches!(op, CompareOp::Eq | CompareOp::Ne) {
+                qb.push("(");
+                push_operand(qb, left);
+                qb.push(operator);
+                push_operand(qb, right);
+                qb.push(")");
+            } else if null(&left) || null(&right) {
+                // Nothing is ordered with `null`.
+                qb.push("FALSE");
+            } else {
+                // Strings are ordered by bytes, as in `evaluate`.
+                qb.push("COALESCE(");
+                push_operand(qb, left);
+                qb.push(" COLLATE \"C\"");
+                qb.push(operator);
+                push_operand(qb, right);
+                qb.push(", FALSE)");
+            }
+        }
+    }
+}
+
+/// Values are strings or `null` once [`check_sql`] has passed.
+fn push_operand(qb: &mut QueryBuilder<'_, Postgres>, operand: Operand) {
+    match operand {
+        Operand::Column(column) => qb.push(column),
+        Operand::Value(Value::String(s)) => qb.push_bind(s),
+        Operand::Value(_) => qb.push("NULL"),
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Literal(Value),
+    Operator(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ConditionError> {
+    // Longest first, so that `<=` is not read as `<` followed by `=`.
+    const OPERATORS: [&str; 11] = ["==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")"];
+
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            let unterminated = || ConditionError::Syntax {
+                position,
+                message: "unterminated string".to_string(),
+            };
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => value.push(escaped),
+                        None => return Err(unterminated()),
+                    },
+                    Some((_, end)) if end == c => {
+                        tokens.push((position, Token::Literal(Value::String(value))));
+                        break;
+                    }
+                    Some((_, other)) => value.push(other),
+                    None => return Err(unterminated()),
+                }
+            }
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(&(_, d)) = chars.peek() {
+                if !(d.is_ascii_digit() || d == '.') {
+                    break;
+                }
+                number.push(d);
+                chars.next();
+            }
+            let value = number.parse().map_err(|_| ConditionError::Syntax {
+                position,
+                message: format!("invalid number {number}"),
+            })?;
+            tokens.push((position, Token::Literal(Value::Number(value))));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut word = String::new();
+            while let Some(&(_, w)) = chars.peek() {
+                if !(w.is_alphanumeric() || w == '_' || w == '.') {
+                    break;
+                }
+                word.push(w);
+                chars.next();
+            }
+            let token = match word.as_str() {
+                "true" => Token::Literal(Value::Bool(true)),
+                "false" => Token::Literal(Value::Bool(false)),
+                "null" => Token::Literal(Value::Null),
+                _ => Token::Identifier(word),
+            };
+            tokens.push((position, token));
+        } else {
+            let rest = &input[position..];
+            let operator = OPERATORS
+                .into_iter()
+                .find(|op| rest.starts_with(op))
+                .ok_or_else(|| ConditionError::Syntax {
+                    position,
+                    message: format!("unexpected character {c:?}"),
+                })?;
+            for _ in 0..operator.len() {
+                chars.next();
+            }
+            tokens.push((position, Token::Operator(operator)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent, from the loosest binding `||` down to operands.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek_operator(&self) -> Option<&'static str> {
+        match self.tokens.get(self.position) {
+            Some((_, Token::Operator(op))) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn error(&self, message: &str) -> ConditionError {
+        let position = self
+            .tokens
+            .get(self.position)
+            .map_or(self.end, |(position, _)| *position);
+        ConditionError::Syntax {
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ConditionError> {
+        let mut left = self.and()?;
+        while self.peek_operator() == Some("||") {
+            self.position += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, ConditionError> {
+        let mut left = self.not()?;
+        while self.peek_operator() == Some("&&") {
+            self.position += 1;
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, ConditionError> {
+        if self.peek_operator() == Some("!") {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ConditionError> {
+        let left = self.operand()?;
+        let op = match self.peek_operator() {
+            Some("==") => CompareOp::Eq,
+            Some("!=") => CompareOp::Ne,
+            Some("<") => CompareOp::Lt,
+            Some("<=") => CompareOp::Le,
+            Some(">") => CompareOp::Gt,
+            Some(">=") => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.operand()?;
+        Ok(Expr::Compare(op, Box::new(left), Box::new(right)))
+    }
+
+    fn operand(&mut self) -> Result<Expr, ConditionError> {
+        let Some((_, token)) = self.tokens.get(self.position).cloned() else {
+            return Err(self.error("unexpected end of condition"));
+        };
+        match token {
+            Token::Literal(value) => {
+                self.position += 1;
+                Ok(Expr::Literal(value))
+            }
+            Token::Identifier(path) => {
+                let valid = path.split_once('.').is_some_and(|(root, rest)| {
+                    ROOTS.contains(&root) && rest.split('.').all(|s| !s.is_empty())
+                });
+                if !valid {
+                    return Err(ConditionError::UnknownAttribute(path));
+                }
+                self.position += 1;
+                Ok(Expr::Attribute(path))
+            }
+            Token::Operator("(") => {
+                self.position += 1;
+                let inner = self.or()?;
+                if self.peek_operator() != Some(")") {
+                    return Err(self.error("expected `)`"));
+                }
+                self.position += 1;
+                Ok(inner)
+            }
+            Token::Operator(_) => Err(self.error("expected a value or an attribute")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use sqlx::Execute;
+
+    use super::*;
+
+    fn attributes() -> Attributes {
+        let user_id = Uuid::from_u128(1);
+        let now = Utc.with_ymd_and_hms(2026, 3, 9, 17, 30, 0).unwrap();
+        let mut attributes = Attributes::for_user(user_id, Uuid::nil(), now);
+        attributes.insert("resource.owner_id", Value::String(user_id.to_string()));
+        attributes.insert("resource.budget", Value::String("250".to_string()));
+        attributes
+    }
+
+    fn holds(condition: &str) -> bool {
+        Condition::parse(condition).unwrap().evaluate(&attributes())
+    }
+
+    #[test]
+    fn test_condition_evaluate() {
+        assert!(holds("resource.owner_id == user.id && time.hour < 18"));
+        assert!(!holds("resource.owner_id != user.id || time.hour >= 18"));
+        assert!(holds("!(time.weekday > 5)"));
+        assert!(holds("resource.budget > 100 && resource.budget <= 250"));
+        assert!(holds("resource.missing == null"));
+        assert!(!holds("resource.missing < 3"));
+        assert!(!holds("!resource.missing"));
+        assert!(holds("request.channel != 'api'"));
+        assert!(!holds("user.id"));
+    }
+
+    #[test]
+    fn test_condition_parse_errors() {
+        assert_eq!(
+            Condition::parse("owner == user.id"),
+            Err(ConditionError::UnknownAttribute("owner".to_string()))
+        );
+        assert_eq!(
+            Condition::parse("session.id == 1"),
+            Err(ConditionError::UnknownAttribute("session.id".to_string()))
+        );
+        for invalid in [
+            "",
+            "user.id ==",
+            "user.id = 1",
+            "(user.id == 1",
+            "user.id == 1)",
+            "user.id == 'x",
+            "user.id == 1 & time.hour < 3",
+            "user.id == 1.2.3",
+            "user.id == 'x\\",
+        ] {
+            assert!(Condition::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_condition_to_sql() {
+        let condition = Condition::parse("resource.owner_id == user.id && time.hour < 18").unwrap();
+        let mut qb = QueryBuilder::new("SELECT * FROM projects WHERE ");
+        condition
+            .push_sql(&mut qb, &attributes(), &[("owner_id", "created_by::text")])
+            .unwrap();
+        assert_eq!(
+            qb.build().sql(),
+            "SELECT * FROM projects WHERE COALESCE((created_by::text IS NOT DISTINCT FROM $1) AND $2, FALSE)"
+        );
+    }
+
+    #[test]
+    fn test_condition_to_sql_type_mismatch() {
+        let columns: SqlColumns = &[("name", "name")];
+        for (condition, usage) in [
+            ("resource.name == 5", "compared with a number"),
+            ("resource.name > time.hour", "compared with a number"),
+            ("resource.name != true", "compared with a boolean"),
+            ("resource.name", "used as a boolean"),
+            ("!resource.name && user.id == 'x'", "used as a boolean"),
+            (
+                "(resource.name == 'a') == true",
+                "compared with a comparison",
+            ),
+        ] {
+            let mut qb = QueryBuilder::new("SELECT * FROM projects WHERE ");
+            let condition = Condition::parse(condition).unwrap();
+            assert_eq!(
+                condition.push_sql(&mut qb, &attributes(), columns),
+                Err(ConditionError::TypeMismatch {
+                    attribute: "resource.name".to_string(),
+                    usage
+                })
+            );
+            assert_eq!(qb.build().sql(), "SELECT * FROM projects WHERE ");
+        }
+    }
+
+    #[test]
+    fn test_condition_to_sql_negation_of_null() {
+        let condition = Condition::parse("!(resource.missing < 'x')").unwrap();
+        assert!(condition.evaluate(&attributes()));
+        let mut qb = QueryBuilder::new("");
+        condition
+            .push_sql(&mut qb, &attributes(), &[("missing", "NULL::text")])
+            .unwrap();
+        assert_eq!(
+            qb.build().sql(),
+            "(NOT COALESCE(NULL::text COLLATE \"C\" < $1, FALSE))"
+        );
+    }
+
+    #[test]
+    fn test_condition_to_sql_unmapped_attribute() {
+        let condition = Condition::parse("resource.owner_id == user.id").unwrap();
+        let mut qb = QueryBuilder::new("SELECT * FROM projects WHERE ");
+        assert_eq!(
+            condition.push_sql(&mut qb, &attributes(), &[]),
+            Err(ConditionError::UnmappedAttribute(
+                "resource.owner_id".to_string()
+            ))
+        );
+    }
+}
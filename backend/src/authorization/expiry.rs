@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use sqlx::PgPool;
+use tracing::instrument;
+
+/// Periodically removes role grants past their `valid_until`, recording each
+/// one as `expired` in `users_roles_audit`, and expires overdue access
+/// requests. Resolution already ignores expired grants, this only keeps the
+/// assignments of a user meaningful. Runs for the lifetime of the application.
+pub async fn run_expiry(pool: PgPool, every: Duration) {
+    let mut interval = tokio::time::interval(every);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        match purge(&pool).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "Purged expired role grants"),
+            Err(e) => tracing::error!(error = ?e, "Failed to purge expired role grants"),
+        }
+        match expire_stale_requests(&pool).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "Expired pending access requests"),
+            Err(e) => tracing::error!(error = ?e, "Failed to expire access requests"),
+        }
+    }
+}
+
+#[instrument(name = "Purge expired role grants", skip_all)]
+async fn purge(pool: &PgPool) -> Result<u64, anyhow::Error> {
+    let purged = sqlx::query!(
+        r#"
+        WITH expired AS (
+            DELETE FROM users_roles
+            WHERE valid_until <= now()
+            RETURNING user_id, role_id, project_id, valid_from, valid_until
+        )
+        INSERT INTO users_roles_audit (user_id, role_id, project_id, valid_from, valid_until, event)
+        SELECT user_id, role_id, project_id, valid_from, valid_until, 'expired'
+        FROM expired
+        "#
+    )
+    .execute(pool)
+    .await
+    .context("Failed to purge expired role grants")?;
+
+    Ok(purged.rows_affected())
+}
+
+/// Moves pending access requests past their `expires_at` to `expired`.
+#[instrument(name = "Expire access requests", skip_all)]
+pub(crate) async fn expire_stale_requests(pool: &PgPool) -> Result<u64, anyhow::Error> {
+    let expired = sqlx::query!(
+        r#"
+        UPDATE access_requests
+        SET status = 'expired'
+        WHERE status = 'pending' AND expires_at <= now()
+        "#
+    )
+    .execute(pool)
+    .await
+    .context("Failed to expire access requests")?;
+
+    Ok(expired.rows_affected())
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::Deserialize;
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::cache::PermissionCache;
+
+/// Channel the `notify_rbac_change` trigger publishes on.
+const CHANNEL: &str = "rbac_changes";
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum RbacChange {
+    /// `organization_id` is missing when the role of a revoked grant was
+    /// deleted with it, the user's current organizations are affected then.
+    User {
+        id: Uuid,
+        organization_id: Option<Uuid>,
+    },
+    Role {
+        id: Uuid,
+    },
+    Permission {
+        id: Uuid,
+    },
+    Component {
+        id: Uuid,
+    },
+}
+
+/// Evicts cached permissions of the users affected by each committed change
+/// to role assignments, memberships, role permissions/components,
+/// permissions or components. Runs for the lifetime of the application; if the connection to
+/// postgres drops, notifications may have been missed, so the whole cache is
+/// flushed once listening again.
+pub async fn invalidate_on_changes(pool: PgPool, cache: PermissionCache) {
+    loop {
+        if let Err(e) = listen(&pool, &cache).await {
+            tracing::error!(error = ?e, "Permission cache invalidation stopped, restarting");
+        }
+        if let Err(e) = cache.invalidate_all().await {
+            tracing::error!(error = ?e, "Failed to flush the permission cache");
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn listen(pool: &PgPool, cache: &PermissionCache) -> Result<(), anyhow::Error> {
+    let mut listener = PgListener::connect_with(pool)
+        .await
+        .context("Failed to connect the change listener")?;
+    listener
+        .listen(CHANNEL)
+        .await
+        .context("Failed to listen for rbac changes")?;
+
+    loop {
+        // `None` means the connection was lost and is re-established by the
+        // next call, anything sent meanwhile is gone.
+        let Some(notification) = listener.try_recv().await? else {
+            cache.invalidate_all().await?;
+            continue;
+        };
+
+        let change = match serde_json::from_str::<RbacChange>(notification.payload()) {
+            Ok(change) => change,
+            Err(e) => {
+                tracing::warn!(error = ?e, payload = notification.payload(), "Unknown rbac change");
+                continue;
+            }
+        };
+        let entries = affected_entries(pool, &change).await?;
+        cache.invalidate(&entries).await?;
+    }
+}
+
+/// `(organization_id, user_id)` pairs whose cached permissions the change
+/// may affect.
+#[instrument(skip(pool))]
+async fn affected_entries(
+    pool: &PgPool,
+    change: &RbacChange,
+) -> Result<Vec<(Uuid, Uuid)>, anyhow::Error> {
+    let entries = match *change {
+        RbacChange::User {
+            id: user_id,
+            organization_id: Some(organization_id),
+        } => vec![(organization_id, user_id)],
+        RbacChange::User {
+            id: user_id,
+            organization_id: None,
+        } => sqlx::query!(
+            "SELECT organization_id, user_id FROM organizations_users WHERE user_id = $1",
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch organizations of user")?
+        .into_iter()
+        .map(|row| (row.organization_id, row.user_id))
+        .collect(),
+        RbacChange::Role { id: role_id } => sqlx::query!(
+            r#"
+            SELECT r.organization_id, ur.user_id
+            FROM users_roles as ur
+            JOIN roles as r ON ur.role_id = r.role_id
+            WHERE ur.role_id = $1
+            "#,
+            role_id
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch users of role")?
+        .into_iter()
+        .map(|row| (row.organization_id, row.user_id))
+        .collect(),
+        RbacChange::Permission { id: permission_id } => sqlx::query!(
+            r#"
+            SELECT DISTINCT r.organization_id, ur.user_id
+            FROM users_roles as ur
+            JOIN roles as r ON ur.role_id = r.role_id
+            JOIN roles_permissions as rp ON ur.role_id = rp.role_id
+            WHERE rp.permission_id = $1
+            "#,
+            permission_id
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch users of permission")?
+        .into_iter()
+        .map(|row| (row.organization_id, row.user_id))
+        .collect(),
+        RbacChange::Component { id: component_id } => sqlx::query!(
+            r#"
+            SELECT DISTINCT r.organization_id, ur.user_id
+            FROM users_roles as ur
+            JOIN roles as r ON ur.role_id = r.role_id
+            JOIN roles_components as rc ON ur.role_id = rc.role_id
+            WHERE rc.component_id = $1
+            "#,
+            component_id
+        )
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch users of component")?
+        .into_iter()
+        .map(|row| (row.organization_id, row.user_id))
+        .collect(),
+    };
+
+    Ok(entries)
+}
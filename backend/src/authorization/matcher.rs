@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use validator::ValidationError;
+
+use crate::rbac_demo::rbac::permissions::models::Permission;
+
+/// Matches in either field everything, within a resource one segment.
+pub const WILDCARD: &str = "*";
+
+/// What a request wants to do: `action` on `resource`, within `scope`.
+/// Resources are hierarchical, `project/tasks` being a child of `project`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub resource: String,
+    pub action: String,
+    pub scope: String,
+}
+
+impl Target {
+    pub fn new(resource: &str, action: &str, scope: &str) -> Self {
+        Self {
+            resource: resource.to_string(),
+            action: action.to_string(),
+            scope: scope.to_string(),
+        }
+    }
+}
+
+/// Whether the permission covers the target. A resource pattern covers its
+/// own resource and every child of it, `*` segments standing for any one
+/// segment: `project` and `project/*` both cover `project/tasks`, `*` covers
+/// everything. Actions and scopes are either `*` or matched exactly.
+pub fn matches(permission: &Permission, target: &Target) -> bool {
+    resource_matches(&permission.resource, &target.resource)
+        && field_matches(&permission.action, &target.action)
+        && field_matches(&permission.scope, &target.scope)
+}
+
+pub fn resource_matches(pattern: &str, resource: &str) -> bool {
+    let mut segments = resource.split('/');
+    pattern.split('/').all(|expected| {
+        segments
+            .next()
+            .is_some_and(|segment| expected == WILDCARD || expected == segment)
+    })
+}
+
+fn field_matches(pattern: &str, value: &str) -> bool {
+    pattern == WILDCARD || pattern == value
+}
+
+/// `/`-separated non-empty segments, each either `*` or free of `*`, `:` and
+/// whitespace.
+pub fn validate_resource(resource: &str) -> Result<(), ValidationError> {
+    if resource.split('/').all(is_valid_segment) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("resource_pattern"))
+    }
+}
+
+/// `*` or a single segment, see [`validate_resource`].
+pub fn validate_action(action: &str) -> Result<(), ValidationError> {
+    if is_valid_segment(action) && !action.contains('/') {
+        Ok(())
+    } else {
+        Err(ValidationError::new("action_pattern"))
+    }
+}
+
+fn is_valid_segment(segment: &str) -> bool {
+    segment == WILDCARD
+        || (!segment.is_empty()
+            && !segment
+                .chars()
+                .any(|c| c == '*' || c == ':' || c.is_whitespace()))
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::{Arbitrary, Gen};
+    use quickcheck_macros::quickcheck;
+
+    use super::*;
+
+    /// Resources over a small alphabet, so that generated patterns and
+    /// targets actually overlap.
+    #[derive(Debug, Clone)]
+    struct Resource(Vec<&'static str>);
+
+    impl Resource {
+        fn path(&self) -> String {
+            self.0.join("/")
+        }
+    }
+
+    impl Arbitrary for Resource {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let len = usize::arbitrary(g) % 4 + 1;
+            let segments = (0..len)
+                .map(|_| *g.choose(&["project", "tasks", "members", "*"]).unwrap())
+                .collect();
+            Resource(segments)
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let segments = self.0.clone();
+            Box::new((1..segments.len()).map(move |len| Resource(segments[..len].to_vec())))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Field(&'static str);
+
+    impl Arbitrary for Field {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Field(g.choose(&["read", "write", "*"]).unwrap())
+        }
+    }
+
+    fn permission(resource: &str, action: &str, scope: &str) -> Permission {
+        Permission {
+            permission_id: uuid::Uuid::nil(),
+            resource: resource.to_string(),
+            action: action.to_string(),
+            scope: scope.to_string(),
+            deprecated: false,
+            condition: None,
+        }
+    }
+
+    #[quickcheck]
+    fn permissions_cover_themselves(resource: Resource, action: Field, scope: Field) -> bool {
+        let resource = resource.path();
+        matches(
+            &permission(&resource, action.0, scope.0),
+            &Target::new(&resource, action.0, scope.0),
+        )
+    }
+
+    #[quickcheck]
+    fn wildcards_cover_everything(resource: Resource, action: Field, scope: Field) -> bool {
+        matches(
+            &permission("*", "*", "*"),
+            &Target::new(&resource.path(), action.0, scope.0),
+        )
+    }
+
+    #[quickcheck]
+    fn parents_cover_their_children(parent: Resource, child: Resource) -> bool {
+        let resource = format!("{}/{}", parent.path(), child.path());
+        resource_matches(&parent.path(), &resource)
+    }
+
+    #[quickcheck]
+    fn children_never_cover_their_parents(parent: Resource, child: Resource) -> bool {
+        let resource = format!("{}/{}", parent.path(), child.path());
+        !resource_matches(&resource, &parent.path())
+    }
+
+    #[quickcheck]
+    fn wildcard_segments_cover_any_segment(resource: Resource, at: usize) -> bool {
+        let mut pattern = resource.clone();
+        let at = at % pattern.0.len();
+        pattern.0[at] = "*";
+        resource_matches(&pattern.path(), &resource.path())
+    }
+
+    #[quickcheck]
+    fn matching_is_transitive(a: Resource, b: Resource, c: Resource) -> bool {
+        let (a, b, c) = (a.path(), b.path(), c.path());
+        !(resource_matches(&a, &b) && resource_matches(&b, &c)) || resource_matches(&a, &c)
+    }
+
+    #[quickcheck]
+    fn concrete_actions_only_cover_themselves(action: Field, other: Field) -> bool {
+        action.0 == "*" || field_matches(action.0, other.0) == (action.0 == other.0)
+    }
+
+    #[quickcheck]
+    fn generated_patterns_are_valid(resource: Resource, action: Field) -> bool {
+        validate_resource(&resource.path()).is_ok() && validate_action(action.0).is_ok()
+    }
+
+    #[test]
+    fn documented_examples() {
+        assert!(resource_matches("project", "project/tasks"));
+        assert!(resource_matches("project/*", "project/tasks"));
+        assert!(!resource_matches("project/*", "project"));
+        assert!(!resource_matches("project", "projects"));
+        assert!(!resource_matches("project/tasks", "project/members"));
+    }
+
+    #[test]
+    fn malformed_patterns_are_rejected() {
+        for resource in ["", "project/", "/tasks", "pro*ject", "project:read", "a b"] {
+            assert!(validate_resource(resource).is_err(), "{resource}");
+        }
+        for action in ["", "re*d", "read/write"] {
+            assert!(validate_action(action).is_err(), "{action}");
+        }
+    }
+}
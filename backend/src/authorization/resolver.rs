@@ -0,0 +1,229 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::condition::Attributes;
+use super::matcher::{Target, matches};
+use crate::rbac_demo::rbac::permissions::models::Permission;
+
+/// Everything a user is granted through their roles in one organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePermissions {
+    /// Granted through organization-wide roles, effective on every project.
+    pub permissions: Vec<Permission>,
+    /// Component codes the UI may render for this user, whatever the scope
+    /// of the roles binding them.
+    pub components: Vec<String>,
+    /// Granted through roles bound to a single project, on top of
+    /// `permissions`.
+    #[serde(default)]
+    pub projects: Vec<ProjectPermissions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectPermissions {
+    pub project_id: Uuid,
+    pub permissions: Vec<Permission>,
+}
+
+impl EffectivePermissions {
+    /// The granted permissions covering the target, see [`matches`], whether
+    /// their conditions hold or not. Without a project only organization-wide
+    /// grants count.
+    pub fn matching(&self, target: &Target, project_id: Option<Uuid>) -> Vec<&Permission> {
+        let project = project_id.and_then(|id| self.projects.iter().find(|p| p.project_id == id));
+        self.permissions
+            .iter()
+            .chain(project.into_iter().flat_map(|p| &p.permissions))
+            .filter(|permission| matches(permission, target))
+            .collect()
+    }
+
+    /// The permissions covering the target whose conditions, if any, hold
+    /// for the attributes.
+    pub fn authorizing(
+        &self,
+        target: &Target,
+        project_id: Option<Uuid>,
+        attributes: &Attributes,
+    ) -> Vec<&Permission> {
+        self.matching(target, project_id)
+            .into_iter()
+            .filter(|permission| match permission.parsed_condition() {
+                Ok(None) => true,
+                Ok(Some(condition)) => condition.evaluate(attributes),
+                Err(e) => {
+                    tracing::warn!(error = %e, permission_id = %permission.permission_id, "Invalid permission condition");
+                    false
+                }
+            })
+            .collect()
+    }
+
+    pub fn allows(
+        &self,
+        target: &Target,
+        project_id: Option<Uuid>,
+        attributes: &Attributes,
+    ) -> bool {
+        !self.authorizing(target, project_id, attributes).is_empty()
+    }
+
+    /// The permissions effective on `project_id`: the organization-wide ones
+    /// and those of the roles bound to the project.
+    pub fn for_project(&self, project_id: Uuid) -> EffectivePermissions {
+        let mut permissions = self.permissions.clone();
+        if let Some(project) = self.projects.iter().find(|p| p.project_id == project_id) {
+            for permission in &project.permissions {
+                if !permissions
+                    .iter()
+                    .any(|p| p.permission_id == permission.permission_id)
+                {
+                    permissions.push(permission.clone());
+                }
+            }
+        }
+        permissions.sort_by(|a, b| {
+            (&a.resource, &a.action, &a.scope).cmp(&(&b.resource, &b.action, &b.scope))
+        });
+
+        EffectivePermissions {
+            permissions,
+            components: self.components.clone(),
+            projects: Vec::new(),
+        }
+    }
+}
+
+/// Joins users -> roles -> permissions/components, skipping grants outside
+/// their validity window, and splits the permissions by the project the
+/// grants are bound to. Deprecated permissions are still effective until
+/// they are removed from the roles holding them.
+#[instrument(name = "Resolve effective permissions", skip(pool))]
+pub async fn resolve_permissions(
+    pool: &PgPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<EffectivePermissions, anyhow::Error> {
+    let permissions = sqlx::query_as!(
+        Permission,
+        r#"
+        SELECT DISTINCT p.permission_id, p.resource, p.action, p.scope, p.deprecated,
+            p.condition
+        FROM permissions as p
+        JOIN roles_permissions as rp ON p.permission_id = rp.permission_id
+        JOIN users_roles as ur ON rp.role_id = ur.role_id
+        JOIN roles as r ON ur.role_id = r.role_id
+        WHERE ur.user_id = $1
+            AND r.organization_id = $2
+            AND ur.project_id IS NULL
+            AND ur.valid_from <= now()
+            AND (ur.valid_until IS NULL OR ur.valid_until > now())
+        ORDER BY p.resource, p.action, p.scope
+        "#,
+        user_id,
+        organization_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to resolve permissions of user")?;
+
+    let project_rows = sqlx::query!(
+        r#"
+        SELECT DISTINCT ur.project_id as "project_id!",
+            p.permission_id, p.resource, p.action, p.scope, p.deprecated, p.condition
+        FROM permissions as p
+        JOIN roles_permissions as rp ON p.permission_id = rp.permission_id
+        JOIN users_roles as ur ON rp.role_id = ur.role_id
+        JOIN roles as r ON ur.role_id = r.role_id
+        WHERE ur.user_id = $1
+            AND r.organization_id = $2
+            AND ur.project_id IS NOT NULL
+            AND ur.valid_from <= now()
+            AND (ur.valid_until IS NULL OR ur.valid_until > now())
+        ORDER BY ur.project_id, p.resource, p.action, p.scope
+        "#,
+        user_id,
+        organization_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to resolve project permissions of user")?;
+
+    let mut projects: Vec<ProjectPermissions> = Vec::new();
+    for row in project_rows {
+        let permission = Permission {
+            permission_id: row.permission_id,
+            resource: row.resource,
+            action: row.action,
+            scope: row.scope,
+            deprecated: row.deprecated,
+            condition: row.condition,
+        };
+        match projects.last_mut() {
+            Some(project) if project.project_id == row.project_id => {
+                project.permissions.push(permission)
+            }
+            _ => projects.push(ProjectPermissions {
+                project_id: row.project_id,
+                permissions: vec![permission],
+            }),
+        }
+    }
+
+    let components = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT c.code
+        FROM components as c
+        JOIN roles_components as rc ON c.component_id = rc.component_id
+        JOIN users_roles as ur ON rc.role_id = ur.role_id
+        JOIN roles as r ON ur.role_id = r.role_id
+        WHERE ur.user_id = $1
+            AND r.organization_id = $2
+            AND ur.valid_from <= now()
+            AND (ur.valid_until IS NULL OR ur.valid_until > now())
+        ORDER BY c.code
+        "#,
+        user_id,
+        organization_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to resolve components of user")?;
+
+    Ok(EffectivePermissions {
+        permissions,
+        components,
+        projects,
+    })
+}
+
+/// Next moment one of the user's grants in the organization starts or ends,
+/// after which their effective permissions differ without any row having
+/// changed.
+#[instrument(name = "Next grant change", skip(pool))]
+pub async fn next_grant_change(
+    pool: &PgPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<Option<DateTime<Utc>>, anyhow::Error> {
+    let next = sqlx::query_scalar!(
+        r#"
+        SELECT min(boundary)
+        FROM users_roles as ur
+        JOIN roles as r ON ur.role_id = r.role_id,
+            LATERAL (VALUES (ur.valid_from), (ur.valid_until)) as b (boundary)
+        WHERE ur.user_id = $1 AND r.organization_id = $2 AND boundary > now()
+        "#,
+        user_id,
+        organization_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch the next grant change of user")?;
+
+    Ok(next)
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::app_states::AppState;
+use crate::audit::Actor;
+use crate::errors::AppError;
+use crate::routers::session_state::TypeSession;
+
+/// The logged-in user, when a system admin. For routes changing what every
+/// organization shares, which no role of a single organization can grant.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemAdmin {
+    pub user_id: Uuid,
+}
+
+impl FromRequestParts<Arc<AppState>> for SystemAdmin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let session = TypeSession::from_request_parts(parts, state)
+            .await
+            .map_err(|(_, e)| AppError::E500(anyhow::anyhow!(e)))?;
+        let user_id = session.require_user_id()?;
+
+        if !is_system_admin(&state.pool, user_id)
+            .await
+            .map_err(AppError::E500)?
+        {
+            return Err(AppError::E403(anyhow::anyhow!(
+                "User {user_id} is not a system admin"
+            )));
+        }
+
+        Ok(Self { user_id })
+    }
+}
+
+/// Whether the user may change what every organization shares, for routes
+/// where only some requests do.
+pub async fn is_system_admin(
+    executor: impl PgExecutor<'_>,
+    user_id: Uuid,
+) -> Result<bool, anyhow::Error> {
+    let is_system_admin = sqlx::query_scalar!(
+        "SELECT is_system_admin FROM users WHERE user_id = $1",
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to check system admin")?;
+
+    Ok(is_system_admin == Some(true))
+}
+
+/// Changes made by system admins concern no organization, they are recorded
+/// in the chain of events outside any.
+impl From<SystemAdmin> for Actor {
+    fn from(admin: SystemAdmin) -> Self {
+        Actor::user(admin.user_id)
+    }
+}
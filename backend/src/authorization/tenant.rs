@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use uuid::Uuid;
+
+use crate::app_states::AppState;
+use crate::errors::AppError;
+use crate::routers::session_state::TypeSession;
+
+/// The logged-in user and the organization selected in their session.
+/// Membership is checked on every request, so users removed from an
+/// organization lose access right away.
+#[derive(Debug, Clone, Copy)]
+pub struct Tenant {
+    pub user_id: Uuid,
+    pub organization_id: Uuid,
+}
+
+impl FromRequestParts<Arc<AppState>> for Tenant {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let session = TypeSession::from_request_parts(parts, state)
+            .await
+            .map_err(|(_, e)| AppError::E500(anyhow::anyhow!(e)))?;
+        let user_id = session.require_user_id()?;
+        let organization_id = session
+            .get_organization_id()
+            .ok_or_else(|| AppError::E403(anyhow::anyhow!("No organization selected")))?;
+
+        let member = sqlx::query_scalar!(
+            "SELECT 1 FROM organizations_users WHERE organization_id = $1 AND user_id = $2",
+            organization_id,
+            user_id
+        )
+        .fetch_optional(&state.pool)
+        .await
+        .context("Failed to check organization membership")
+        .map_err(AppError::E500)?;
+        if member.is_none() {
+            return Err(AppError::E403(anyhow::anyhow!(
+                "User {user_id} is not a member of organization {organization_id}"
+            )));
+        }
+
+        Ok(Self {
+            user_id,
+            organization_id,
+        })
+    }
+}
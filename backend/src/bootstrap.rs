@@ -0,0 +1,90 @@
+use anyhow::Context;
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::authentication::hash_password;
+use crate::configuration::BootstrapSettings;
+use crate::rbac_demo::rbac::presets::{admin_role_id, seed_presets};
+use crate::rbac_demo::rbac::users::update_roles::grant_roles;
+
+/// Seeds the preset roles of the bootstrap organization, creating it if
+/// needed, and the admin user, a system admin, on first run. Does nothing
+/// unless the admin credentials are configured. Safe to run on every
+/// startup: an existing admin user is left alone, so a changed password or
+/// revoked role stays that way.
+#[instrument(skip_all, fields(organization = %settings.organization))]
+pub async fn run(pool: &PgPool, settings: &BootstrapSettings) -> Result<(), anyhow::Error> {
+    let (username, password) = match (&settings.admin_username, &settings.admin_password) {
+        (Some(username), Some(password)) => (username, password),
+        (None, None) => return Ok(()),
+        _ => anyhow::bail!("Bootstrap needs both admin_username and admin_password"),
+    };
+
+    let mut transaction = pool.begin().await.context("Failed to start transaction")?;
+
+    let organization_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO organizations (name) VALUES ($1)
+        ON CONFLICT (name) DO UPDATE SET name = excluded.name
+        RETURNING organization_id
+        "#,
+        settings.organization
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to create the bootstrap organization")?;
+
+    seed_presets(&mut transaction, organization_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e:?}"))
+        .context("Failed to seed preset roles")?;
+
+    let exists = sqlx::query_scalar!("SELECT user_id FROM users WHERE username = $1", username)
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to look up the admin user")?;
+    if exists.is_none() {
+        let password_hash = hash_password(password.clone()).await?;
+        let user_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (user_id, username, password_hash, is_system_admin)
+            VALUES (gen_random_uuid(), $1, $2, TRUE)
+            RETURNING user_id
+            "#,
+            username,
+            password_hash.expose_secret()
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .context("Failed to create the admin user")?;
+
+        sqlx::query!(
+            "INSERT INTO organizations_users (organization_id, user_id) VALUES ($1, $2)",
+            organization_id,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to add the admin user to the organization")?;
+
+        let role_id = admin_role_id(&mut transaction, organization_id).await?;
+        grant_roles(
+            &mut *transaction,
+            user_id,
+            &[role_id],
+            None,
+            chrono::Utc::now(),
+            None,
+        )
+        .await?;
+        tracing::info!("Created bootstrap admin user {username}");
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")?;
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use sqlx::{PgConnection, PgPool};
+
+use crate::rbac_demo::rbac::document::export::load_document;
+use crate::rbac_demo::rbac::document::import::apply_document;
+use crate::rbac_demo::rbac::document::models::{DocumentFormat, ImportPlan};
+use crate::rbac_demo::rbac::presets::seed_presets;
+
+#[derive(Debug, Parser)]
+#[command(name = "backend")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Runs the server, the default.
+    Serve,
+    /// Manages the RBAC configuration of an organization, see `/rbac/export`.
+    #[command(subcommand)]
+    Rbac(RbacCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RbacCommand {
+    /// Prints the configuration of the organization.
+    Export {
+        #[arg(long)]
+        organization: String,
+        #[arg(long, value_enum, default_value_t = DocumentFormat::Yaml)]
+        format: DocumentFormat,
+    },
+    /// Creates the preset roles missing from the organization, creating it
+    /// if needed.
+    Seed {
+        #[arg(long)]
+        organization: String,
+    },
+    /// Applies a document, creating the organization if needed, and prints
+    /// the plan.
+    Import {
+        #[arg(long)]
+        organization: String,
+        /// Read as JSON if it ends in `.json`, as YAML otherwise.
+        file: PathBuf,
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn run_rbac(pool: &PgPool, command: RbacCommand) -> Result<(), anyhow::Error> {
+    match command {
+        RbacCommand::Export {
+            organization,
+            format,
+        } => {
+            let mut connection = pool.acquire().await?;
+            let organization_id = sqlx::query_scalar!(
+                "SELECT organization_id FROM organizations WHERE name = $1",
+                organization
+            )
+            .fetch_optional(&mut *connection)
+            .await?
+            .with_context(|| format!("Organization {organization} not found"))?;
+            let document = load_document(&mut connection, organization_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+            print!("{}", format.render(&document)?);
+        }
+        RbacCommand::Seed { organization } => {
+            let mut transaction = pool.begin().await?;
+            let organization_id = upsert_organization(&mut transaction, &organization).await?;
+            let changes = seed_presets(&mut transaction, organization_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+            transaction.commit().await?;
+            let plan = ImportPlan {
+                applied: !changes.is_empty(),
+                changes,
+            };
+            println!("{}", serde_json::to_string_pretty(&plan)?);
+        }
+        RbacCommand::Import {
+            organization,
+            file,
+            dry_run,
+        } => {
+            let format = if file.extension().is_some_and(|e| e == "json") {
+                DocumentFormat::Json
+            } else {
+                DocumentFormat::Yaml
+            };
+            let input = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let document = format.parse(&input)?;
+
+            let mut transaction = pool.begin().await?;
+            let organization_id = upsert_organization(&mut transaction, &organization).await?;
+            let changes = apply_document(&mut transaction, organization_id, document)
+                .await
+                .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+
+            let applied = !dry_run && !changes.is_empty();
+            if applied {
+                transaction.commit().await?;
+            } else {
+                transaction.rollback().await?;
+            }
+            let plan = ImportPlan { applied, changes };
+            println!("{}", serde_json::to_string_pretty(&plan)?);
+        }
+    }
+
+    Ok(())
+}
+
+async fn upsert_organization(
+    connection: &mut PgConnection,
+    name: &str,
+) -> Result<uuid::Uuid, anyhow::Error> {
+    let organization_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO organizations (name) VALUES ($1)
+        ON CONFLICT (name) DO UPDATE SET name = excluded.name
+        RETURNING organization_id
+        "#,
+        name
+    )
+    .fetch_one(connection)
+    .await?;
+
+    Ok(organization_id)
+}
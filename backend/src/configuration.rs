@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+use std::num::NonZeroU64;
 use std::{fmt, str::FromStr};
 
 use config::{Config, ConfigError, Environment, File};
@@ -9,6 +11,13 @@ use secrecy::{ExposeSecret, SecretBox, SecretString};
 pub struct Settings {
     pub app_settings: AppSettings,
     pub database: DBSettings,
+    pub permission_cache: PermissionCacheSettings,
+    pub role_grants: RoleGrantSettings,
+    pub audit: AuditSettings,
+    pub email_client: EmailClientSettings,
+    pub invitations: InvitationSettings,
+    #[serde(default)]
+    pub bootstrap: BootstrapSettings,
 }
 
 #[derive(Deserialize)]
@@ -28,8 +37,86 @@ pub struct DBSettings {
     pub database_name: String,
 }
 
-fn default_timeout_milliseconds() -> u64 {
-    10_000
+/// Caching of users' effective permissions in redis.
+#[derive(Deserialize)]
+pub struct PermissionCacheSettings {
+    pub enabled: bool,
+    /// Upper bound for serving an entry whose invalidation got lost.
+    pub ttl_seconds: NonZeroU64,
+}
+
+#[derive(Deserialize)]
+pub struct RoleGrantSettings {
+    /// How often grants past their `valid_until` are purged.
+    pub purge_interval_seconds: NonZeroU64,
+}
+
+/// Tamper evidence of the audit log.
+#[derive(Deserialize)]
+pub struct AuditSettings {
+    /// How often the head of every audit chain is checkpointed.
+    pub checkpoint_interval_seconds: NonZeroU64,
+    /// Key signing the checkpoints, e.g. `CRAFT__AUDIT__SIGNING_KEY`. Without
+    /// it no checkpoints are taken.
+    pub signing_key: Option<SecretString>,
+    /// Addresses of the reverse proxies whose `x-forwarded-for` is believed
+    /// for the ip of audit events. None by default, the peer address is
+    /// recorded then.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// The email delivery API, e.g. Postmark.
+#[derive(Deserialize)]
+pub struct EmailClientSettings {
+    pub base_url: String,
+    pub sender_email: String,
+    /// E.g. `CRAFT__EMAIL_CLIENT__AUTHORIZATION_TOKEN`.
+    pub authorization_token: SecretString,
+    pub timeout_milliseconds: u64,
+}
+
+impl EmailClientSettings {
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_milliseconds)
+    }
+}
+
+/// Invitations to join an organization by email.
+#[derive(Deserialize)]
+pub struct InvitationSettings {
+    /// How long the link of an invitation can be used.
+    pub expiry_hours: u64,
+    /// Key signing the links, e.g. `CRAFT__INVITATIONS__SIGNING_KEY`. Without
+    /// it no invitations are sent.
+    pub signing_key: Option<SecretString>,
+}
+
+/// First-run setup. With both credentials set, e.g. through
+/// `CRAFT__BOOTSTRAP__ADMIN_USERNAME` and `CRAFT__BOOTSTRAP__ADMIN_PASSWORD`,
+/// the admin user is created on startup unless it exists already, as a
+/// member and Admin of `organization`, whose preset roles are seeded on every
+/// startup. Without them nothing is seeded; `rbac seed` does it by hand.
+#[derive(Deserialize)]
+pub struct BootstrapSettings {
+    pub admin_username: Option<String>,
+    pub admin_password: Option<SecretString>,
+    #[serde(default = "default_bootstrap_organization")]
+    pub organization: String,
+}
+
+impl Default for BootstrapSettings {
+    fn default() -> Self {
+        Self {
+            admin_username: None,
+            admin_password: None,
+            organization: default_bootstrap_organization(),
+        }
+    }
+}
+
+fn default_bootstrap_organization() -> String {
+    "Default".to_string()
 }
 
 enum RunningEnv {
@@ -152,6 +239,35 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_zero_intervals_are_rejected() {
+        let with_interval = |name: &str, seconds: &str| {
+            unsafe {
+                std::env::set_var("RUNNING_ENV", "local");
+                std::env::set_var(name, seconds);
+            }
+            let settings = get_config();
+            unsafe {
+                std::env::remove_var(name);
+            }
+            settings
+        };
+
+        let checkpoint = "CRAFT__AUDIT__CHECKPOINT_INTERVAL_SECONDS";
+        let settings = with_interval(checkpoint, "5").unwrap();
+        assert_eq!(settings.audit.checkpoint_interval_seconds.get(), 5);
+        assert!(with_interval(checkpoint, "0").is_err());
+        let purge = "CRAFT__ROLE_GRANTS__PURGE_INTERVAL_SECONDS";
+        let settings = with_interval(purge, "5").unwrap();
+        assert_eq!(settings.role_grants.purge_interval_seconds.get(), 5);
+        assert!(with_interval(purge, "0").is_err());
+        let ttl = "CRAFT__PERMISSION_CACHE__TTL_SECONDS";
+        let settings = with_interval(ttl, "5").unwrap();
+        assert_eq!(settings.permission_cache.ttl_seconds.get(), 5);
+        assert!(with_interval(ttl, "0").is_err());
+    }
+
     #[test]
     #[serial]
     fn test_get_env_config() {
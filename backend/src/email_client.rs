@@ -0,0 +1,147 @@
+use reqwest::Client;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::configuration::EmailClientSettings;
+
+/// Sends emails through the HTTP API of a Postmark-compatible provider.
+#[derive(Clone)]
+pub struct EmailClient {
+    http_client: Client,
+    base_url: String,
+    sender: String,
+    authorization_token: SecretString,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendEmailRequest<'a> {
+    from: &'a str,
+    to: &'a str,
+    subject: &'a str,
+    html_body: &'a str,
+    text_body: &'a str,
+}
+
+impl EmailClient {
+    pub fn new(settings: &EmailClientSettings) -> Self {
+        let http_client = Client::builder()
+            .timeout(settings.timeout())
+            .build()
+            .expect("Failed to build the email HTTP client");
+        Self {
+            http_client,
+            base_url: settings.base_url.clone(),
+            sender: settings.sender_email.clone(),
+            authorization_token: settings.authorization_token.clone(),
+        }
+    }
+
+    #[instrument(name = "Send an email", skip(self, html_content, text_content))]
+    pub async fn send_email(
+        &self,
+        recipient: &str,
+        subject: &str,
+        html_content: &str,
+        text_content: &str,
+    ) -> Result<(), reqwest::Error> {
+        let body = SendEmailRequest {
+            from: &self.sender,
+            to: recipient,
+            subject,
+            html_body: html_content,
+            text_body: text_content,
+        };
+        self.http_client
+            .post(format!("{}/email", self.base_url))
+            .header(
+                "X-Postmark-Server-Token",
+                self.authorization_token.expose_secret(),
+            )
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use secrecy::SecretString;
+    use wiremock::matchers::{any, header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    use super::EmailClient;
+    use crate::configuration::EmailClientSettings;
+
+    struct SendEmailBodyMatcher;
+
+    impl wiremock::Match for SendEmailBodyMatcher {
+        fn matches(&self, request: &Request) -> bool {
+            serde_json::from_slice::<serde_json::Value>(&request.body).is_ok_and(|body| {
+                ["From", "To", "Subject", "HtmlBody", "TextBody"]
+                    .iter()
+                    .all(|field| body.get(field).is_some())
+            })
+        }
+    }
+
+    fn email_client(base_url: String, timeout_milliseconds: u64) -> EmailClient {
+        EmailClient::new(&EmailClientSettings {
+            base_url,
+            sender_email: "sender@example.com".to_string(),
+            authorization_token: SecretString::from("token"),
+            timeout_milliseconds,
+        })
+    }
+
+    async fn send(client: &EmailClient) -> Result<(), reqwest::Error> {
+        client
+            .send_email("recipient@example.com", "Subject", "<p>Body</p>", "Body")
+            .await
+    }
+
+    #[tokio::test]
+    async fn send_email_posts_the_expected_request() {
+        let server = MockServer::start().await;
+        Mock::given(header_exists("X-Postmark-Server-Token"))
+            .and(header("Content-Type", "application/json"))
+            .and(path("/email"))
+            .and(method("POST"))
+            .and(SendEmailBodyMatcher)
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(send(&email_client(server.uri(), 1000)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_email_fails_on_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(send(&email_client(server.uri(), 1000)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn send_email_times_out_on_slow_servers() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(3)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(send(&email_client(server.uri(), 200)).await.is_err());
+    }
+}
@@ -10,6 +10,16 @@ pub enum AppError {
     E400(#[source] anyhow::Error),
     #[error("authorization failed")]
     E401(#[source] anyhow::Error),
+    #[error("forbidden")]
+    E403(#[source] anyhow::Error),
+    #[error("resource not found")]
+    E404(#[source] anyhow::Error),
+    #[error("conflict with the current state of the resource")]
+    E409(#[source] anyhow::Error),
+    #[error("precondition failed")]
+    E412(#[source] anyhow::Error),
+    #[error("precondition required")]
+    E428(#[source] anyhow::Error),
 }
 
 impl AppError {
@@ -18,6 +28,11 @@ impl AppError {
             Self::E500(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::E400(_) => StatusCode::BAD_REQUEST,
             Self::E401(_) => StatusCode::UNAUTHORIZED,
+            Self::E403(_) => StatusCode::FORBIDDEN,
+            Self::E404(_) => StatusCode::NOT_FOUND,
+            Self::E409(_) => StatusCode::CONFLICT,
+            Self::E412(_) => StatusCode::PRECONDITION_FAILED,
+            Self::E428(_) => StatusCode::PRECONDITION_REQUIRED,
         }
     }
 }
@@ -1,6 +1,11 @@
 pub mod app_states;
+pub mod audit;
 mod authentication;
+pub mod authorization;
+pub mod bootstrap;
+pub mod cli;
 pub mod configuration;
+pub mod email_client;
 pub mod errors;
 pub mod models;
 pub mod rbac_demo;
@@ -1,18 +1,39 @@
+use backend::cli::{Cli, Command, run_rbac};
 use backend::configuration::get_config;
 use backend::startup::Application;
 use backend::telemetry::{get_subscriber, init_subscriber};
+use clap::Parser;
 
 #[tokio::main]
 async fn main() {
-    let subscriber = get_subscriber("stitch-up".into(), "info".into(), std::io::stdout);
-    init_subscriber(subscriber);
+    let cli = Cli::parse();
 
     dotenvy::dotenv().ok();
 
     let settings = get_config().expect("Failed to load configuration");
 
-    let app = Application::build(settings)
-        .await
-        .expect("Failed to build application");
-    app.run_until_stop().await;
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => {
+            let subscriber = get_subscriber("stitch-up".into(), "info".into(), std::io::stdout);
+            init_subscriber(subscriber);
+
+            let app = Application::build(settings)
+                .await
+                .expect("Failed to build application");
+            app.run_until_stop().await;
+        }
+        Command::Rbac(command) => {
+            // stdout carries the document or the plan.
+            let subscriber = get_subscriber("stitch-up".into(), "warn".into(), std::io::stderr);
+            init_subscriber(subscriber);
+
+            let pool = sqlx::PgPool::connect(&settings.database.get_connection())
+                .await
+                .expect("Failed to connect to the database");
+            if let Err(e) = run_rbac(&pool, command).await {
+                eprintln!("{e:#}");
+                std::process::exit(1);
+            }
+        }
+    }
 }
@@ -1,8 +1,13 @@
+use anyhow::Context;
+use axum::extract::FromRequestParts;
+use axum::http::{HeaderValue, StatusCode, header, request::Parts};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, to_value};
 use sqlx::Postgres;
 use sqlx::QueryBuilder;
 
+use crate::errors::AppError;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListResponse<T> {
     pub results: Vec<T>,
@@ -19,6 +24,13 @@ pub struct ListRequest<T> {
     pub page_size: u64,
 }
 
+/// Query of the CSV exports, the filter of the matching list without the
+/// pagination.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ExportRequest<T> {
+    pub filter: Option<T>,
+}
+
 fn default_page() -> u64 {
     1
 }
@@ -80,6 +92,115 @@ impl Filter {
     }
 }
 
+/// Per-item outcome of the `/add` and `/remove` association endpoints
+/// (see docs/notes.md). Adding fills `added` and `skipped` (already
+/// associated), removing fills `removed` and `missing` (not associated).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssociationResult<T> {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<T>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<T>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<T>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<T>,
+}
+
+impl<T: PartialEq + Clone> AssociationResult<T> {
+    pub fn from_added(requested: &[T], added: Vec<T>) -> Self {
+        let skipped = Self::rest(requested, &added);
+        Self {
+            added,
+            removed: vec![],
+            skipped,
+            missing: vec![],
+        }
+    }
+
+    pub fn from_removed(requested: &[T], removed: Vec<T>) -> Self {
+        let missing = Self::rest(requested, &removed);
+        Self {
+            added: vec![],
+            removed,
+            skipped: vec![],
+            missing,
+        }
+    }
+
+    /// Whether anything was actually written.
+    pub fn is_noop(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// `409 Conflict` if an item was already associated, `412 Precondition
+    /// Failed` if an item to remove was not associated, `200 OK` otherwise.
+    pub fn status_code(&self) -> StatusCode {
+        if !self.skipped.is_empty() {
+            StatusCode::CONFLICT
+        } else if !self.missing.is_empty() {
+            StatusCode::PRECONDITION_FAILED
+        } else {
+            StatusCode::OK
+        }
+    }
+
+    fn rest(requested: &[T], applied: &[T]) -> Vec<T> {
+        let mut rest: Vec<T> = vec![];
+        for item in requested {
+            if !applied.contains(item) && !rest.contains(item) {
+                rest.push(item.clone());
+            }
+        }
+        rest
+    }
+}
+
+/// Resource version the client last saw, taken from the `If-Match` header.
+/// Guarded mutations answer `428` when the header is missing and `412` when
+/// the version is stale, so concurrent editors cannot overwrite each other.
+#[derive(Debug, Clone, Copy)]
+pub struct IfMatch(pub i32);
+
+/// `ETag` response header carrying a resource version.
+pub type ETag = [(header::HeaderName, HeaderValue); 1];
+
+impl IfMatch {
+    pub fn etag(version: i32) -> ETag {
+        let value = HeaderValue::from_str(&format!("\"{version}\""))
+            .expect("A number is a valid header value");
+        [(header::ETAG, value)]
+    }
+
+    fn parse(value: &str) -> Option<i32> {
+        let value = value.trim();
+        let value = value.strip_prefix("W/").unwrap_or(value);
+        value.strip_prefix('"')?.strip_suffix('"')?.parse().ok()
+    }
+}
+
+impl<S> FromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts
+            .headers
+            .get(header::IF_MATCH)
+            .ok_or_else(|| AppError::E428(anyhow::anyhow!("Missing If-Match header")))?
+            .to_str()
+            .context("If-Match header is not valid ASCII")
+            .map_err(AppError::E400)?;
+
+        Self::parse(value)
+            .map(Self)
+            .ok_or_else(|| AppError::E400(anyhow::anyhow!("Invalid If-Match header: {value}")))
+    }
+}
+
+#[cfg(test)]
 mod tests {
 
     use sqlx::Execute;
@@ -141,4 +262,45 @@ mod tests {
             "SELECT * FROM users WHERE TRUE AND name = $1 LIMIT $2 OFFSET $3"
         );
     }
+
+    #[test]
+    fn test_if_match_parse() {
+        assert_eq!(IfMatch::parse("\"3\""), Some(3));
+        assert_eq!(IfMatch::parse("W/\"3\""), Some(3));
+        assert_eq!(IfMatch::parse(" \"12\" "), Some(12));
+        assert_eq!(IfMatch::parse("3"), None);
+        assert_eq!(IfMatch::parse("*"), None);
+        assert_eq!(IfMatch::parse("\"abc\""), None);
+    }
+
+    #[test]
+    fn test_if_match_etag_round_trip() {
+        let [(_, etag)] = IfMatch::etag(7);
+        assert_eq!(IfMatch::parse(etag.to_str().unwrap()), Some(7));
+    }
+
+    #[test]
+    fn test_association_result_added() {
+        let result = AssociationResult::from_added(&[1, 2, 3, 3], vec![1]);
+        assert_eq!(result.added, vec![1]);
+        assert_eq!(result.skipped, vec![2, 3]);
+        assert_eq!(result.status_code(), StatusCode::CONFLICT);
+        assert!(!result.is_noop());
+
+        let result = AssociationResult::from_added(&[1, 2], vec![1, 2]);
+        assert!(result.skipped.is_empty());
+        assert_eq!(result.status_code(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_association_result_removed() {
+        let result = AssociationResult::from_removed(&[1, 2], vec![]);
+        assert_eq!(result.missing, vec![1, 2]);
+        assert_eq!(result.status_code(), StatusCode::PRECONDITION_FAILED);
+        assert!(result.is_noop());
+
+        let result = AssociationResult::from_removed(&[1, 2], vec![2, 1]);
+        assert!(result.missing.is_empty());
+        assert_eq!(result.status_code(), StatusCode::OK);
+    }
 }
@@ -1,9 +1,13 @@
 use crate::app_states::AppState;
-use axum::routing::{delete, get, post};
+use axum::routing::{delete, get, patch, post, put};
 use std::sync::Arc;
+pub mod audit_events;
+pub mod invitations;
 pub mod members;
+pub mod organizations;
 pub mod projects;
 pub mod rbac;
+pub mod tasks;
 
 pub fn router() -> axum::routing::Router<Arc<AppState>> {
     axum::Router::new()
@@ -16,19 +20,247 @@ pub fn router() -> axum::routing::Router<Arc<AppState>> {
             "/roles/{id}/permissions/remove",
             post(rbac::roles::update_permissions::remove_role_permissions),
         )
+        .route(
+            "/roles/{id}/components/add",
+            post(rbac::roles::update_components::add_role_components),
+        )
+        .route(
+            "/roles/{id}/components/remove",
+            post(rbac::roles::update_components::remove_role_components),
+        )
         .route("/roles", get(rbac::roles::get::list_roles))
+        .route("/roles/compare", get(rbac::roles::compare::compare_roles))
+        .route("/roles/{id}", get(rbac::roles::get::get_role))
+        .route(
+            "/roles/{id}/history",
+            get(rbac::roles::history::list_role_history),
+        )
+        .route(
+            "/roles/{id}/history/diff",
+            get(rbac::roles::history::diff_role_versions),
+        )
         .route(
             "/roles/{id}/permissions",
             get(rbac::roles::get::list_role_permissions),
         )
+        .route(
+            "/roles/{id}/components",
+            get(rbac::roles::get::list_role_components),
+        )
         .route(
             "/permissions",
             get(rbac::permissions::get::list_permissions),
         )
+        .route(
+            "/permissions",
+            post(rbac::permissions::post::create_new_permission),
+        )
+        .route(
+            "/permissions/{id}",
+            put(rbac::permissions::put::update_permission),
+        )
+        .route(
+            "/permissions/{id}",
+            delete(rbac::permissions::delete::delete_permission),
+        )
+        .route(
+            "/permissions/{id}/roles",
+            get(rbac::permissions::get::list_permission_roles),
+        )
+        .route("/users/{id}/roles", get(rbac::users::get::list_user_roles))
+        .route(
+            "/users/{id}/roles/add",
+            post(rbac::users::update_roles::add_user_roles),
+        )
+        .route(
+            "/users/{id}/roles/remove",
+            post(rbac::users::update_roles::remove_user_roles),
+        )
+        .route(
+            "/users/{id}/permissions",
+            get(rbac::users::get::get_user_permissions),
+        )
+        .route("/authz/check", get(rbac::authz::check::check))
+        .route("/rbac/export", get(rbac::document::export::export_document))
+        .route(
+            "/rbac/import",
+            post(rbac::document::import::import_document),
+        )
+        .route(
+            "/rbac/registry/sync",
+            post(rbac::cleanup::sync::sync_registry),
+        )
+        .route(
+            "/rbac/cleanup/report",
+            get(rbac::cleanup::report::orphan_report),
+        )
+        .route(
+            "/rbac/cleanup/deprecate",
+            post(rbac::cleanup::deprecate::deprecate_orphans),
+        )
+        .route(
+            "/rbac/cleanup/purge",
+            post(rbac::cleanup::purge::purge_orphans),
+        )
+        .route(
+            "/roles/{id}/approvers",
+            get(rbac::roles::get::list_role_approvers),
+        )
+        .route(
+            "/roles/{id}/approvers/add",
+            post(rbac::roles::update_approvers::add_role_approvers),
+        )
+        .route(
+            "/roles/{id}/approvers/remove",
+            post(rbac::roles::update_approvers::remove_role_approvers),
+        )
+        .route(
+            "/access-requests",
+            get(rbac::access_requests::get::list_access_requests)
+                .post(rbac::access_requests::post::create_access_request),
+        )
+        .route(
+            "/access-requests/{id}",
+            get(rbac::access_requests::get::get_access_request),
+        )
+        .route(
+            "/access-requests/{id}/approve",
+            post(rbac::access_requests::decide::approve_access_request),
+        )
+        .route(
+            "/access-requests/{id}/reject",
+            post(rbac::access_requests::decide::reject_access_request),
+        )
+        .route(
+            "/access-requests/{id}/cancel",
+            post(rbac::access_requests::decide::cancel_access_request),
+        )
+        .route(
+            "/sod-rules",
+            get(rbac::sod_rules::get::list_sod_rules).post(rbac::sod_rules::post::create_sod_rule),
+        )
+        .route(
+            "/sod-rules/violations",
+            get(rbac::sod_rules::get::list_sod_violations),
+        )
+        .route(
+            "/sod-rules/{id}",
+            delete(rbac::sod_rules::delete::delete_sod_rule),
+        )
+        .route(
+            "/organizations",
+            get(organizations::get::list_organizations)
+                .post(organizations::post::create_new_organization),
+        )
+        .route(
+            "/organizations/{id}/select",
+            post(organizations::select::select_organization),
+        )
+        .route(
+            "/organizations/{id}/users",
+            get(organizations::get::list_organization_users),
+        )
+        .route(
+            "/organizations/{id}/users/add",
+            post(organizations::update_users::add_organization_users),
+        )
+        .route(
+            "/organizations/{id}/users/remove",
+            post(organizations::update_users::remove_organization_users),
+        )
+        .route("/audit-events", get(audit_events::get::list_audit_events))
+        .route(
+            "/audit-events/export",
+            get(audit_events::export::export_audit_events),
+        )
+        .route(
+            "/audit-events/verify",
+            get(audit_events::verify::verify_audit_events),
+        )
+        .route(
+            "/audit-events/system/verify",
+            get(audit_events::verify::verify_system_audit_events),
+        )
+        .route(
+            "/audit-events/checkpoints/export",
+            get(audit_events::checkpoints::export_checkpoints),
+        )
         .route("/members", post(members::post::create_new_member))
         .route("/members", get(members::get::list_members))
-        .route("/members/{id}", delete(members::delete::delete_member))
+        .route("/members/export.csv", get(members::export::export_members))
+        .route("/members/import", post(members::import::import_members))
+        .route(
+            "/members/{id}",
+            get(members::get::get_member)
+                .put(members::put::update_member)
+                .patch(members::patch::patch_member)
+                .delete(members::delete::delete_member),
+        )
+        .route(
+            "/members/{id}/projects",
+            get(members::get::list_member_projects),
+        )
+        .route(
+            "/members/{id}/restore",
+            post(members::restore::restore_member),
+        )
+        .route("/me", get(members::me::get_me))
+        .route(
+            "/members/invite",
+            post(invitations::post::create_invitation),
+        )
+        .route(
+            "/invitations/accept",
+            get(invitations::accept::accept_page).post(invitations::accept::accept_invitation),
+        )
+        .route(
+            "/invitations/{id}/resend",
+            post(invitations::resend::resend_invitation),
+        )
+        .route(
+            "/invitations/{id}/revoke",
+            post(invitations::revoke::revoke_invitation),
+        )
         .route("/projects", post(projects::post::create_new_project))
         .route("/projects", get(projects::get::list_projects))
-        .route("/projects/{id}", delete(projects::delete::delete_project))
+        .route(
+            "/projects/export.csv",
+            get(projects::export::export_projects),
+        )
+        .route(
+            "/projects/{id}",
+            patch(projects::patch::patch_project).delete(projects::delete::delete_project),
+        )
+        .route(
+            "/projects/{id}/members",
+            get(projects::get::list_project_members),
+        )
+        .route(
+            "/projects/{id}/members/add",
+            post(projects::update_members::add_project_members),
+        )
+        .route(
+            "/projects/{id}/members/remove",
+            post(projects::update_members::remove_project_members),
+        )
+        .route(
+            "/projects/{id}/archive",
+            post(projects::archive::archive_project),
+        )
+        .route(
+            "/projects/{id}/restore",
+            post(projects::archive::restore_project),
+        )
+        .route(
+            "/tasks",
+            get(tasks::get::list_tasks).post(tasks::post::create_task),
+        )
+        .route("/tasks/archive", put(tasks::archive::archive_done_tasks))
+        .route(
+            "/tasks/{id}",
+            get(tasks::get::get_task)
+                .patch(tasks::patch::patch_task)
+                .delete(tasks::delete::delete_task),
+        )
+        .route("/tasks/{id}/move", post(tasks::reorder::move_task))
 }
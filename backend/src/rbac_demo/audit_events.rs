@@ -0,0 +1,4 @@
+pub mod checkpoints;
+pub mod export;
+pub mod get;
+pub mod verify;
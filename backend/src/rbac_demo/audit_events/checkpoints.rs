@@ -0,0 +1,41 @@
+use crate::app_states::AppState;
+use crate::audit::chain;
+use crate::audit::models::AuditCheckpoint;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// The signed checkpoints of the organization's chain as a file, to be kept
+/// apart from the database.
+#[instrument(skip_all)]
+pub async fn export_checkpoints(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let chain_id = chain::chain_id(Some(tenant.organization_id));
+    let checkpoints = sqlx::query_as!(
+        AuditCheckpoint,
+        r#"
+        SELECT checkpoint_id, chain_id, sequence, encode(hash, 'hex') as "hash!",
+            encode(signature, 'hex') as "signature!", created_at
+        FROM audit_checkpoints WHERE chain_id = $1
+        ORDER BY sequence
+        "#,
+        chain_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch audit checkpoints")
+    .map_err(AppError::E500)?;
+
+    let disposition = format!("attachment; filename=\"audit-checkpoints-{chain_id}.json\"");
+    Ok((
+        [(header::CONTENT_DISPOSITION, disposition)],
+        Json(checkpoints),
+    ))
+}
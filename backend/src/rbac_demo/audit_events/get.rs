@@ -0,0 +1,80 @@
+use crate::app_states::AppState;
+use crate::audit::models::{AuditEventFilter, AuditLogEntry};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::{Filter, ListRequest, ListResponse, Pagination};
+use anyhow::Context;
+use axum::extract::{Json, State};
+use serde_qs::axum::QsQuery;
+use sqlx::{Postgres, QueryBuilder};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Events of the organization, and those outside any organization (logins)
+/// of its members, most recent first.
+#[instrument(skip_all)]
+pub async fn list_audit_events(
+    tenant: Tenant,
+    QsQuery(request): QsQuery<ListRequest<AuditEventFilter>>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<ListResponse<AuditLogEntry>>, AppError> {
+    let filter = request.filter.unwrap_or_default();
+
+    let mut qb = scoped_query(
+        r#"
+        SELECT event_id, organization_id, actor_id, action, target_type, target_id,
+            before, after, request_id, ip, occurred_at
+        "#,
+        tenant.organization_id,
+        &filter,
+    );
+    qb.push(" ORDER BY occurred_at DESC, event_id");
+    Pagination::to_query(request.current_page, request.page_size, &mut qb);
+    let events = qb
+        .build_query_as::<AuditLogEntry>()
+        .fetch_all(&app_state.pool)
+        .await
+        .context("Failed to fetch audit events")
+        .map_err(AppError::E500)?;
+
+    let total: i64 = scoped_query("SELECT count(*)", tenant.organization_id, &filter)
+        .build_query_scalar()
+        .fetch_one(&app_state.pool)
+        .await
+        .context("Failed to fetch audit events count")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(ListResponse {
+        results: events,
+        total: total as u64,
+        page: request.current_page,
+    }))
+}
+
+fn scoped_query<'a>(
+    select: &str,
+    organization_id: uuid::Uuid,
+    filter: &'a AuditEventFilter,
+) -> QueryBuilder<'a, Postgres> {
+    let mut qb = QueryBuilder::new(select);
+    qb.push(" FROM (SELECT * FROM audit_events WHERE organization_id = ")
+        .push_bind(organization_id)
+        .push(
+            " OR (organization_id IS NULL AND actor_id IN \
+            (SELECT user_id FROM organizations_users WHERE organization_id = ",
+        )
+        .push_bind(organization_id)
+        .push("))) as scoped");
+
+    Filter::to_query(&mut qb, &filter.columns);
+    if let Some(actor_id) = filter.actor_id {
+        qb.push(" AND actor_id = ").push_bind(actor_id);
+    }
+    if let Some(since) = filter.since {
+        qb.push(" AND occurred_at >= ").push_bind(since);
+    }
+    if let Some(until) = filter.until {
+        qb.push(" AND occurred_at < ").push_bind(until);
+    }
+    qb
+}
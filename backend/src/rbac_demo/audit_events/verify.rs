@@ -0,0 +1,49 @@
+use crate::app_states::AppState;
+use crate::audit::chain;
+use crate::audit::models::ChainReport;
+use crate::authorization::{SystemAdmin, Tenant};
+use crate::errors::AppError;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Verifies the hash chain of the organization's events.
+#[instrument(skip_all)]
+pub async fn verify_audit_events(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<ChainReport>, AppError> {
+    verify_chain(&app_state, Some(tenant.organization_id)).await
+}
+
+/// Verifies the hash chain of the events outside any organization, such as
+/// logins and changes to the permission catalog.
+#[instrument(skip_all)]
+pub async fn verify_system_audit_events(
+    _admin: SystemAdmin,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<ChainReport>, AppError> {
+    verify_chain(&app_state, None).await
+}
+
+async fn verify_chain(
+    app_state: &AppState,
+    organization_id: Option<uuid::Uuid>,
+) -> Result<Json<ChainReport>, AppError> {
+    let mut conn = app_state
+        .pool
+        .acquire()
+        .await
+        .context("Failed to acquire connection")
+        .map_err(AppError::E500)?;
+
+    let report = chain::verify(
+        &mut conn,
+        chain::chain_id(organization_id),
+        app_state.checkpoint_signer.as_ref(),
+    )
+    .await
+    .map_err(AppError::E500)?;
+    Ok(Json(report))
+}
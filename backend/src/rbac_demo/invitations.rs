@@ -0,0 +1,6 @@
+pub mod accept;
+mod email;
+pub mod models;
+pub mod post;
+pub mod resend;
+pub mod revoke;
@@ -0,0 +1,209 @@
+use crate::app_states::AppState;
+use crate::audit::{Actor, Audit, AuditEvent};
+use crate::authentication::{PASSWORD_LENGTH, hash_password};
+use crate::errors::AppError;
+use crate::rbac_demo::invitations::email::require_signer;
+use crate::rbac_demo::invitations::models::{AcceptInvitation, Invitation, InvitationStatus};
+use crate::rbac_demo::rbac::sod_rules::check::violations_for_grant;
+use crate::rbac_demo::rbac::sod_rules::models::SodConflict;
+use crate::rbac_demo::rbac::users::update_roles::grant_roles;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use chrono::Utc;
+use secrecy::{ExposeSecret, SecretString};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// The page the emailed link opens: a password form posting the token of the
+/// link to [`accept_invitation`].
+pub async fn accept_page() -> Html<&'static str> {
+    Html(ACCEPT_PAGE)
+}
+
+const ACCEPT_PAGE: &str = r#"<!doctype html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Accept your invitation</title>
+</head>
+<body>
+  <h1>Accept your invitation</h1>
+  <form id="accept">
+    <label>Choose a password <input type="password" name="password" minlength="8" maxlength="128" required></label>
+    <button type="submit">Accept</button>
+  </form>
+  <p id="result"></p>
+  <script>
+    const form = document.getElementById("accept");
+    form.addEventListener("submit", async (event) => {
+      event.preventDefault();
+      const token = new URLSearchParams(location.search).get("token");
+      const response = await fetch(location.pathname, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ token, password: form.password.value }),
+      });
+      document.getElementById("result").textContent = response.ok
+        ? "Your invitation is accepted, you can log in now."
+        : "This link is invalid, expired or used already.";
+      form.hidden = response.ok;
+    });
+  </script>
+</body>
+</html>
+"#;
+
+/// Sets the password of the pending user of the invitation, which can log in
+/// from then on, and grants it the roles of the invitation still existing.
+/// `401` for links that are not ours, expired, or superseded by a resend.
+#[instrument(name = "Accept an invitation", skip_all)]
+pub async fn accept_invitation(
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<AcceptInvitation>,
+) -> Result<Response, AppError> {
+    let signer = require_signer(&app_state).map_err(AppError::E500)?;
+    let (invitation_id, token_version, token_expires_at) = signer
+        .verify(&request.token)
+        .ok_or_else(|| AppError::E401(anyhow::anyhow!("Invalid invitation token")))?;
+    if !PASSWORD_LENGTH.contains(&request.password.expose_secret().chars().count()) {
+        return Err(AppError::E400(anyhow::anyhow!(
+            "The password must have between {} and {} characters",
+            PASSWORD_LENGTH.start(),
+            PASSWORD_LENGTH.end()
+        )));
+    }
+    let password_hash = hash_password(request.password)
+        .await
+        .map_err(AppError::E500)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let invitation = sqlx::query!(
+        r#"
+        SELECT organization_id, user_id, role_ids, status as "status: InvitationStatus",
+            token_version, expires_at
+        FROM invitations
+        WHERE invitation_id = $1
+        FOR UPDATE
+        "#,
+        invitation_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch invitation")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E401(anyhow::anyhow!("Invitation {invitation_id} not found")))?;
+    if invitation.token_version != token_version {
+        return Err(AppError::E401(anyhow::anyhow!(
+            "The link of invitation {invitation_id} was superseded by a resend"
+        )));
+    }
+    if invitation.expires_at != token_expires_at || token_expires_at <= Utc::now() {
+        return Err(AppError::E401(anyhow::anyhow!(
+            "The link of invitation {invitation_id} expired"
+        )));
+    }
+    if invitation.status != InvitationStatus::Pending {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Invitation {invitation_id} is {:?} already",
+            invitation.status
+        )));
+    }
+    let user_id = invitation.user_id.ok_or_else(|| {
+        AppError::E409(anyhow::anyhow!(
+            "The user of invitation {invitation_id} was removed"
+        ))
+    })?;
+
+    let activated = accept(&mut transaction, user_id, password_hash)
+        .await
+        .map_err(AppError::E500)?;
+    if !activated {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "User {user_id} has a password already"
+        )));
+    }
+
+    let roles = sqlx::query_scalar!(
+        "SELECT role_id FROM roles WHERE role_id = ANY($1) AND organization_id = $2",
+        &invitation.role_ids,
+        invitation.organization_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to fetch roles of invitation")
+    .map_err(AppError::E500)?;
+    let violations = violations_for_grant(&mut transaction, user_id, &roles)
+        .await
+        .map_err(AppError::E500)?;
+    if !violations.is_empty() {
+        let body = SodConflict::new(violations);
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+    grant_roles(&mut *transaction, user_id, &roles, None, Utc::now(), None)
+        .await
+        .map_err(AppError::E500)?;
+
+    let accepted = sqlx::query_as!(
+        Invitation,
+        r#"
+        UPDATE invitations SET status = 'accepted', closed_at = now()
+        WHERE invitation_id = $1
+        RETURNING invitation_id, member_id, user_id, email, role_ids,
+            status as "status: InvitationStatus", invited_by, expires_at, sent_at,
+            send_error, token_version, created_at, closed_at
+        "#,
+        invitation_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to accept invitation")
+    .map_err(AppError::E500)?;
+
+    let actor = Actor {
+        user_id: Some(user_id),
+        organization_id: Some(invitation.organization_id),
+    };
+    audit
+        .record(
+            &mut transaction,
+            actor,
+            AuditEvent::new("invitation.accept", "invitation", invitation_id).after(&accepted),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(accepted).into_response())
+}
+
+/// Sets the password of a pending user, false if it has one already.
+async fn accept(
+    connection: &mut sqlx::PgConnection,
+    user_id: uuid::Uuid,
+    password_hash: SecretString,
+) -> Result<bool, anyhow::Error> {
+    let updated = sqlx::query!(
+        "UPDATE users SET password_hash = $2 WHERE user_id = $1 AND password_hash IS NULL",
+        user_id,
+        password_hash.expose_secret()
+    )
+    .execute(connection)
+    .await
+    .context("Failed to set password of invited user")?
+    .rows_affected();
+
+    Ok(updated == 1)
+}
@@ -0,0 +1,109 @@
+use crate::app_states::AppState;
+use crate::authentication::InvitationSigner;
+use crate::rbac_demo::invitations::models::{Invitation, InvitationStatus};
+use anyhow::Context;
+use tracing::instrument;
+
+/// Emails the invitation, once committed, and records the outcome on it: the
+/// time it was sent or why it could not be. A failed email is not an error,
+/// the invitation stays pending and can be resent.
+#[instrument(skip_all, fields(invitation_id = %invitation.invitation_id))]
+pub(super) async fn deliver_invitation(
+    app_state: &AppState,
+    signer: &InvitationSigner,
+    invitation: &Invitation,
+) -> Result<Invitation, anyhow::Error> {
+    let send_error = match send_invitation(app_state, signer, invitation).await {
+        Ok(()) => None,
+        Err(e) => {
+            tracing::error!(error = ?e, "Failed to send invitation email");
+            Some(format!("{e:#}"))
+        }
+    };
+
+    sqlx::query_as!(
+        Invitation,
+        r#"
+        UPDATE invitations
+        SET sent_at = CASE WHEN $2::text IS NULL THEN now() ELSE sent_at END,
+            send_error = $2
+        WHERE invitation_id = $1
+        RETURNING invitation_id, member_id, user_id, email, role_ids,
+            status as "status: InvitationStatus", invited_by, expires_at, sent_at,
+            send_error, token_version, created_at, closed_at
+        "#,
+        invitation.invitation_id,
+        send_error
+    )
+    .fetch_one(&app_state.pool)
+    .await
+    .context("Failed to record invitation email")
+}
+
+/// Emails the invitation with a link signed for its current version and
+/// expiry.
+async fn send_invitation(
+    app_state: &AppState,
+    signer: &InvitationSigner,
+    invitation: &Invitation,
+) -> Result<(), anyhow::Error> {
+    let organization = sqlx::query_scalar!(
+        r#"
+        SELECT o.name FROM organizations as o
+        JOIN members as m ON m.organization_id = o.organization_id
+        WHERE m.member_id = $1
+        "#,
+        invitation.member_id
+    )
+    .fetch_one(&app_state.pool)
+    .await
+    .context("Failed to fetch organization of invitation")?;
+
+    let token = signer.sign(
+        invitation.invitation_id,
+        invitation.token_version,
+        invitation.expires_at,
+    );
+    let link = format!(
+        "{}/rbac-demo/invitations/accept?token={token}",
+        app_state.base_url
+    );
+    let expires_at = invitation.expires_at.format("%Y-%m-%d %H:%M UTC");
+    let html_body = format!(
+        "You are invited to join {}.<br />\
+        Click <a href=\"{link}\">here</a> to set your password.<br />\
+        The link expires on {expires_at}.",
+        escape_html(&organization)
+    );
+    let text_body = format!(
+        "You are invited to join {organization}.\n\
+        Visit {link} to set your password.\n\
+        The link expires on {expires_at}."
+    );
+
+    app_state
+        .email_client
+        .send_email(
+            &invitation.email,
+            &format!("Your invitation to {organization}"),
+            &html_body,
+            &text_body,
+        )
+        .await
+        .context("Failed to send invitation email")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The signer of invitation links, which are only sent when configured.
+pub(super) fn require_signer(app_state: &AppState) -> Result<&InvitationSigner, anyhow::Error> {
+    app_state
+        .invitation_signer
+        .as_ref()
+        .context("Invitations need `invitations.signing_key` to be configured")
+}
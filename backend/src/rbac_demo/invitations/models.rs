@@ -0,0 +1,73 @@
+use crate::rbac_demo::projects::models::ProjectRole;
+use chrono::{DateTime, Utc};
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// Lifecycle of an invitation. Only `Pending` invitations can change, every
+/// other state is final.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+}
+
+/// An emailed invitation of a member, whose pending user accepts it through
+/// the link of the email.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct Invitation {
+    pub invitation_id: uuid::Uuid,
+    pub member_id: uuid::Uuid,
+    pub user_id: Option<uuid::Uuid>,
+    pub email: String,
+    /// Granted organization-wide on acceptance.
+    pub role_ids: Vec<uuid::Uuid>,
+    pub status: InvitationStatus,
+    pub invited_by: Option<uuid::Uuid>,
+    /// The link of the last email sent works until then.
+    pub expires_at: DateTime<Utc>,
+    /// When the last email went out, `None` until the first does.
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Why the last email could not be sent, cleared once one is.
+    pub send_error: Option<String>,
+    /// Bumped by every resend, only links of the current version work.
+    pub token_version: i32,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+/// Body of `POST /members/invite`. The member of `member_id` is linked to a
+/// pending user named after `email`, without one the member is created from
+/// the names. Either way it is assigned to `projects` right away.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateInvitation {
+    #[validate(email)]
+    pub email: String,
+    /// An existing member without a user yet.
+    pub member_id: Option<uuid::Uuid>,
+    #[validate(length(min = 1, max = 255))]
+    pub first_name: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub role_ids: Vec<uuid::Uuid>,
+    #[serde(default)]
+    pub projects: Vec<InvitedProject>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct InvitedProject {
+    pub project_id: uuid::Uuid,
+    pub role: ProjectRole,
+}
+
+/// Body of `POST /invitations/accept`, `token` coming from the emailed link.
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitation {
+    pub token: String,
+    pub password: SecretString,
+}
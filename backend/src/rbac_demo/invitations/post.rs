@@ -0,0 +1,254 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::invitations::email::{deliver_invitation, require_signer};
+use crate::rbac_demo::invitations::models::{CreateInvitation, Invitation, InvitationStatus};
+use crate::rbac_demo::members::models::Member;
+use crate::rbac_demo::projects::models::ProjectRole;
+use crate::rbac_demo::projects::patch::lock_writable_project;
+use crate::rbac_demo::rbac::sod_rules::check::violations_for_grant;
+use crate::rbac_demo::rbac::sod_rules::models::SodConflict;
+use crate::rbac_demo::rbac::users::models::UnknownRoles;
+use crate::rbac_demo::rbac::users::update_roles::find_unknown_roles;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// Creates a member with a pending user named after the email, or links the
+/// existing member of `member_id` to one, assigns it to the projects and
+/// emails it a link to accept the invitation. `409` for members that have a
+/// user already. The roles are
+/// granted on acceptance, separation-of-duties rules are checked now already.
+/// The email is sent once all of it is committed; if it cannot be, the
+/// invitation says why and can be resent.
+#[instrument(
+    name = "Invite a member by email",
+    skip(tenant, audit, app_state, request),
+    fields(email = request.email)
+)]
+pub async fn create_invitation(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<CreateInvitation>,
+) -> Result<Response, AppError> {
+    request
+        .validate()
+        .context("Invalid invitation")
+        .map_err(AppError::E400)?;
+    let signer = require_signer(&app_state).map_err(AppError::E500)?;
+
+    let unknown = find_unknown_roles(&app_state.pool, tenant.organization_id, &request.role_ids)
+        .await
+        .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        let body = UnknownRoles {
+            message: "Some roles do not exist".to_string(),
+            role_ids: unknown,
+        };
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let user_id =
+        create_pending_user(&mut transaction, tenant.organization_id, &request.email).await?;
+    let violations = violations_for_grant(&mut transaction, user_id, &request.role_ids)
+        .await
+        .map_err(AppError::E500)?;
+    if !violations.is_empty() {
+        let body = SodConflict::new(violations);
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    let (before, member) = match (request.member_id, &request.first_name, &request.last_name) {
+        (Some(member_id), _, _) => {
+            let before =
+                lock_unlinked_member(&mut transaction, tenant.organization_id, member_id).await?;
+            let member = sqlx::query_as!(
+                Member,
+                r#"
+                UPDATE members SET user_id = $2
+                WHERE member_id = $1
+                RETURNING member_id, first_name, last_name, user_id, deleted_at
+                "#,
+                member_id,
+                user_id
+            )
+            .fetch_one(&mut *transaction)
+            .await
+            .context("Failed to link member to user")
+            .map_err(AppError::E500)?;
+            (Some(before), member)
+        }
+        (None, Some(first_name), Some(last_name)) => {
+            let member = sqlx::query_as!(
+                Member,
+                r#"
+                INSERT INTO members (member_id, first_name, last_name, organization_id, user_id)
+                VALUES (gen_random_uuid(), $1, $2, $3, $4)
+                RETURNING member_id, first_name, last_name, user_id, deleted_at
+                "#,
+                first_name,
+                last_name,
+                tenant.organization_id,
+                user_id
+            )
+            .fetch_one(&mut *transaction)
+            .await
+            .context("Failed to create member")
+            .map_err(AppError::E500)?;
+            (None, member)
+        }
+        (None, _, _) => {
+            return Err(AppError::E400(anyhow::anyhow!(
+                "Invitations need a member_id or the names of the member to create"
+            )));
+        }
+    };
+
+    for project in &request.projects {
+        lock_writable_project(&mut transaction, tenant.organization_id, project.project_id).await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO project_members (project_id, member_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (project_id, member_id) DO NOTHING
+            "#,
+            project.project_id,
+            member.member_id,
+            project.role as ProjectRole
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to add member to project")
+        .map_err(AppError::E500)?;
+    }
+
+    let invitation = sqlx::query_as!(
+        Invitation,
+        r#"
+        INSERT INTO invitations
+            (organization_id, member_id, user_id, email, role_ids, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING invitation_id, member_id, user_id, email, role_ids,
+            status as "status: InvitationStatus", invited_by, expires_at, sent_at,
+            send_error, token_version, created_at, closed_at
+        "#,
+        tenant.organization_id,
+        member.member_id,
+        user_id,
+        request.email,
+        &request.role_ids as &[uuid::Uuid],
+        tenant.user_id,
+        signer.expires_at()
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to create invitation")
+    .map_err(AppError::E500)?;
+
+    let created = serde_json::json!({
+        "invitation": &invitation,
+        "member": &member,
+        "projects": &request.projects,
+    });
+    let mut event =
+        AuditEvent::new("invitation.create", "invitation", invitation.invitation_id).after(created);
+    if let Some(before) = before {
+        event = event.before(serde_json::json!({ "member": before }));
+    }
+    audit
+        .record(&mut transaction, tenant, event)
+        .await
+        .map_err(AppError::E500)?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    let invitation = deliver_invitation(&app_state, signer, &invitation)
+        .await
+        .map_err(AppError::E500)?;
+    Ok(Json(invitation).into_response())
+}
+
+/// Locks the member of the organization to link it to a user, `404` if it
+/// does not exist or is deleted, `409` if it has a user already.
+async fn lock_unlinked_member(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    member_id: uuid::Uuid,
+) -> Result<Member, AppError> {
+    let member = sqlx::query_as!(
+        Member,
+        r#"
+        SELECT member_id, first_name, last_name, user_id, deleted_at
+        FROM members
+        WHERE member_id = $1 AND organization_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        member_id,
+        organization_id
+    )
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to fetch member")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Member {member_id} not found")))?;
+    if let Some(user_id) = member.user_id {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Member {member_id} is already linked to user {user_id}"
+        )));
+    }
+
+    Ok(member)
+}
+
+/// Creates a user without a password in the organization, `409` if the
+/// username is taken.
+async fn create_pending_user(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    username: &str,
+) -> Result<uuid::Uuid, AppError> {
+    let user_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO users (user_id, username)
+        VALUES (gen_random_uuid(), $1)
+        ON CONFLICT (username) DO NOTHING
+        RETURNING user_id
+        "#,
+        username
+    )
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to create pending user")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E409(anyhow::anyhow!("Username {username} is already taken")))?;
+
+    sqlx::query!(
+        "INSERT INTO organizations_users (organization_id, user_id) VALUES ($1, $2)",
+        organization_id,
+        user_id
+    )
+    .execute(&mut *connection)
+    .await
+    .context("Failed to add user to organization")
+    .map_err(AppError::E500)?;
+
+    Ok(user_id)
+}
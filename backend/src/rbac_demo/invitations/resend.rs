@@ -0,0 +1,105 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::invitations::email::{deliver_invitation, require_signer};
+use crate::rbac_demo::invitations::models::{Invitation, InvitationStatus};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Emails a new link with a new expiry, the links sent before stop working,
+/// whether or not the email can be sent.
+#[instrument(name = "Resend an invitation", skip(tenant, audit, app_state))]
+pub async fn resend_invitation(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(invitation_id): Path<uuid::Uuid>,
+) -> Result<Json<Invitation>, AppError> {
+    let signer = require_signer(&app_state).map_err(AppError::E500)?;
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before =
+        lock_pending_invitation(&mut transaction, tenant.organization_id, invitation_id).await?;
+    let invitation = sqlx::query_as!(
+        Invitation,
+        r#"
+        UPDATE invitations SET expires_at = $2, token_version = token_version + 1
+        WHERE invitation_id = $1
+        RETURNING invitation_id, member_id, user_id, email, role_ids,
+            status as "status: InvitationStatus", invited_by, expires_at, sent_at,
+            send_error, token_version, created_at, closed_at
+        "#,
+        invitation_id,
+        signer.expires_at()
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to update invitation")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("invitation.resend", "invitation", invitation_id)
+                .before(&before)
+                .after(&invitation),
+        )
+        .await
+        .map_err(AppError::E500)?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    let invitation = deliver_invitation(&app_state, signer, &invitation)
+        .await
+        .map_err(AppError::E500)?;
+    Ok(Json(invitation))
+}
+
+/// Locks an invitation of the organization, `404` if there is none and `409`
+/// if it is accepted or revoked already.
+pub(super) async fn lock_pending_invitation(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    invitation_id: uuid::Uuid,
+) -> Result<Invitation, AppError> {
+    let invitation = sqlx::query_as!(
+        Invitation,
+        r#"
+        SELECT invitation_id, member_id, user_id, email, role_ids,
+            status as "status: InvitationStatus", invited_by, expires_at, sent_at,
+            send_error, token_version, created_at, closed_at
+        FROM invitations
+        WHERE invitation_id = $1 AND organization_id = $2
+        FOR UPDATE
+        "#,
+        invitation_id,
+        organization_id
+    )
+    .fetch_optional(connection)
+    .await
+    .context("Failed to fetch invitation")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Invitation {invitation_id} not found")))?;
+
+    if invitation.status != InvitationStatus::Pending {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Invitation {invitation_id} is {:?} already",
+            invitation.status
+        )));
+    }
+    Ok(invitation)
+}
@@ -0,0 +1,82 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::invitations::models::{Invitation, InvitationStatus};
+use crate::rbac_demo::invitations::resend::lock_pending_invitation;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Cancels a pending invitation, its link stops working. The pending user is
+/// removed, the member stays without one.
+#[instrument(name = "Revoke an invitation", skip(tenant, audit, app_state))]
+pub async fn revoke_invitation(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(invitation_id): Path<uuid::Uuid>,
+) -> Result<Json<Invitation>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before =
+        lock_pending_invitation(&mut transaction, tenant.organization_id, invitation_id).await?;
+    if let Some(user_id) = before.user_id {
+        sqlx::query!(
+            r#"
+            WITH pending AS (
+                SELECT user_id FROM users WHERE user_id = $1 AND password_hash IS NULL
+            ), left_organizations AS (
+                DELETE FROM organizations_users
+                WHERE user_id IN (SELECT user_id FROM pending)
+            )
+            DELETE FROM users WHERE user_id IN (SELECT user_id FROM pending)
+            "#,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to remove pending user")
+        .map_err(AppError::E500)?;
+    }
+
+    let invitation = sqlx::query_as!(
+        Invitation,
+        r#"
+        UPDATE invitations SET status = 'revoked', closed_at = now()
+        WHERE invitation_id = $1
+        RETURNING invitation_id, member_id, user_id, email, role_ids,
+            status as "status: InvitationStatus", invited_by, expires_at, sent_at,
+            send_error, token_version, created_at, closed_at
+        "#,
+        invitation_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to revoke invitation")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("invitation.revoke", "invitation", invitation_id)
+                .before(&before)
+                .after(&invitation),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(invitation))
+}
@@ -1,4 +1,10 @@
 pub mod delete;
+pub mod export;
 pub mod get;
+pub mod import;
+pub mod me;
 pub mod models;
+pub mod patch;
 pub mod post;
+pub mod put;
+pub mod restore;
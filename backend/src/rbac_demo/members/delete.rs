@@ -1,41 +1,88 @@
 use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
 use crate::errors::AppError;
+use crate::rbac_demo::members::models::Member;
+use anyhow::Context;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use std::sync::Arc;
 use tracing::instrument;
 
-#[instrument(name = "Delete a member", skip(app_state))]
+/// Soft deletes the member, `POST /members/{id}/restore` undoes it. The
+/// member leaves every project for good.
+#[instrument(name = "Delete a member", skip(tenant, audit, app_state))]
 pub async fn delete_member(
+    tenant: Tenant,
+    audit: Audit,
     State(app_state): State<Arc<AppState>>,
     Path(member_id): Path<uuid::Uuid>,
 ) -> Result<StatusCode, AppError> {
-    let deleted = delete_member_from_db(&app_state.pool, member_id)
+    let mut transaction = app_state
+        .pool
+        .begin()
         .await
+        .context("Failed to start transaction")
         .map_err(AppError::E500)?;
 
-    if !deleted {
+    let deleted = delete_member_from_db(&mut transaction, tenant.organization_id, member_id)
+        .await
+        .map_err(AppError::E500)?;
+
+    let Some(member) = deleted else {
         return Ok(StatusCode::NOT_FOUND);
-    }
+    };
+
+    let before = Member {
+        deleted_at: None,
+        ..member.clone()
+    };
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("member.delete", "member", member_id)
+                .before(&before)
+                .after(&member),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Project assignments are removed along with the member.
 #[instrument(name = "Try to delete the member from DB", skip_all)]
 async fn delete_member_from_db(
-    pool: &sqlx::PgPool,
+    connection: &mut sqlx::PgConnection,
+    organization_id: uuid::Uuid,
     member_id: uuid::Uuid,
-) -> Result<bool, anyhow::Error> {
-    let count = sqlx::query!(
+) -> Result<Option<Member>, anyhow::Error> {
+    let member = sqlx::query_as!(
+        Member,
         r#"
-        DELETE FROM members
-        WHERE member_id = $1
+        WITH deleted AS (
+            UPDATE members SET deleted_at = now()
+            WHERE member_id = $1 AND organization_id = $2 AND deleted_at IS NULL
+            RETURNING member_id, first_name, last_name, user_id, deleted_at
+        ), unassigned AS (
+            DELETE FROM project_members
+            WHERE member_id IN (SELECT member_id FROM deleted)
+        )
+        SELECT member_id as "member_id!", first_name as "first_name!",
+            last_name as "last_name!", user_id, deleted_at
+        FROM deleted
         "#,
-        member_id
+        member_id,
+        organization_id
     )
-    .execute(pool)
-    .await?
-    .rows_affected();
+    .fetch_optional(connection)
+    .await?;
 
-    Ok(count > 0)
+    Ok(member)
 }
@@ -0,0 +1,63 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::ExportRequest;
+use crate::rbac_demo::members::get::scoped_query;
+use crate::rbac_demo::members::models::{Member, MemberFilter};
+use crate::utils;
+use anyhow::Context;
+use axum::body::Body;
+use axum::extract::State;
+use axum::response::Response;
+use futures::TryStreamExt;
+use serde_qs::axum::QsQuery;
+use std::sync::Arc;
+use tracing::instrument;
+
+const COLUMNS: &[&str] = &[
+    "member_id",
+    "first_name",
+    "last_name",
+    "user_id",
+    "deleted_at",
+];
+
+/// Rows fetched from the cursor per chunk of the response.
+const BATCH_SIZE: usize = 500;
+
+/// Every member `GET /members` lists with the same filter, as CSV streamed
+/// through a server-side cursor.
+#[instrument(name = "Export members", skip_all)]
+pub async fn export_members(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    QsQuery(request): QsQuery<ExportRequest<MemberFilter>>,
+) -> Result<Response, AppError> {
+    let filter = request.filter.unwrap_or_default();
+
+    let mut qb = scoped_query(
+        r#"
+        DECLARE member_export NO SCROLL CURSOR FOR
+        SELECT member_id, first_name, last_name, user_id, deleted_at
+        "#,
+        tenant.organization_id,
+        &filter,
+    );
+    qb.push(" ORDER BY last_name, first_name, member_id");
+    let chunks = utils::db::stream_cursor(
+        &app_state.pool,
+        "member_export",
+        qb,
+        BATCH_SIZE,
+        |members: &[Member], first| utils::csv::encode(COLUMNS, members, first),
+    )
+    .await
+    .context("Failed to export members")
+    .map_err(AppError::E500)?
+    .inspect_err(|e| tracing::error!(error = ?e, "Failed to stream members"));
+
+    Ok(utils::csv::attachment(
+        "members.csv",
+        Body::from_stream(chunks),
+    ))
+}
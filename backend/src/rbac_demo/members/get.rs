@@ -1,20 +1,30 @@
 use crate::app_states::AppState;
+use crate::authorization::Tenant;
 use crate::errors::AppError;
-use crate::models::{ListRequest, ListResponse, Pagination};
-use crate::rbac_demo::members::models::Member;
-use crate::utils::db;
+use crate::models::{Filter, ListRequest, ListResponse, Pagination};
+use crate::rbac_demo::members::models::{Member, MemberFilter, MemberProject};
+use crate::rbac_demo::projects::models::ProjectRole;
 use anyhow::Context;
-use axum::extract::{Json, Query, State};
+use axum::extract::{Json, Path, State};
+use serde_qs::axum::QsQuery;
+use sqlx::{Postgres, QueryBuilder};
 use std::sync::Arc;
 use tracing::instrument;
 
 #[instrument(name = "List all members", skip_all)]
 pub async fn list_members(
+    tenant: Tenant,
     State(app_state): State<Arc<AppState>>,
-    Query(request): Query<ListRequest<()>>,
+    QsQuery(request): QsQuery<ListRequest<MemberFilter>>,
 ) -> Result<Json<ListResponse<Member>>, AppError> {
-    let mut qb = sqlx::QueryBuilder::new("SELECT member_id, first_name, last_name FROM members");
+    let filter = request.filter.unwrap_or_default();
 
+    let mut qb = scoped_query(
+        "SELECT member_id, first_name, last_name, user_id, deleted_at",
+        tenant.organization_id,
+        &filter,
+    );
+    qb.push(" ORDER BY last_name, first_name, member_id");
     Pagination::to_query(request.current_page, request.page_size, &mut qb);
 
     let members = qb
@@ -24,7 +34,9 @@ pub async fn list_members(
         .context("Failed to fetch members")
         .map_err(AppError::E500)?;
 
-    let total = db::count("members", &(), &app_state.pool)
+    let total: i64 = scoped_query("SELECT count(*)", tenant.organization_id, &filter)
+        .build_query_scalar()
+        .fetch_one(&app_state.pool)
         .await
         .context("Failed to fetch members count")
         .map_err(AppError::E500)?;
@@ -35,3 +47,85 @@ pub async fn list_members(
         page: request.current_page,
     }))
 }
+
+/// A member of the organization, deleted ones included.
+#[instrument(name = "Get a member", skip(tenant, app_state))]
+pub async fn get_member(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    Path(member_id): Path<uuid::Uuid>,
+) -> Result<Json<Member>, AppError> {
+    let member = sqlx::query_as!(
+        Member,
+        r#"
+        SELECT member_id, first_name, last_name, user_id, deleted_at
+        FROM members
+        WHERE member_id = $1 AND organization_id = $2
+        "#,
+        member_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&app_state.pool)
+    .await
+    .context("Failed to fetch member")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Member {member_id} not found")))?;
+
+    Ok(Json(member))
+}
+
+/// Projects the member is assigned to, by name.
+#[instrument(skip_all)]
+pub async fn list_member_projects(
+    tenant: Tenant,
+    Path(member_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<MemberProject>>, AppError> {
+    sqlx::query_scalar!(
+        "SELECT 1 FROM members WHERE member_id = $1 AND organization_id = $2",
+        member_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&app_state.pool)
+    .await
+    .context("Failed to fetch member")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Member {member_id} not found")))?;
+
+    let projects = sqlx::query_as!(
+        MemberProject,
+        r#"
+        SELECT p.project_id, p.name, pm.role as "role: ProjectRole", pm.added_at
+        FROM project_members as pm
+        JOIN projects as p ON p.project_id = pm.project_id
+        WHERE pm.member_id = $1
+        ORDER BY p.name, p.project_id
+        "#,
+        member_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch member projects")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(projects))
+}
+
+/// Members of the organization matching `filter`, without the deleted ones
+/// unless asked for.
+pub(crate) fn scoped_query<'a>(
+    select: &str,
+    organization_id: uuid::Uuid,
+    filter: &'a MemberFilter,
+) -> QueryBuilder<'a, Postgres> {
+    let mut qb = QueryBuilder::new(select);
+    qb.push(" FROM (SELECT * FROM members WHERE organization_id = ")
+        .push_bind(organization_id)
+        .push(") as scoped");
+
+    Filter::to_query(&mut qb, &filter.columns);
+    if !filter.include_deleted {
+        qb.push(" AND deleted_at IS NULL");
+    }
+    qb
+}
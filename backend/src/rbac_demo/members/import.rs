@@ -0,0 +1,162 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::members::models::{
+    CreateMember, ImportMode, ImportQuery, ImportReport, RowError,
+};
+use anyhow::Context;
+use axum::Json;
+use axum::body::Bytes;
+use axum::extract::{Multipart, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// Rows inserted per statement, binds being limited.
+const BATCH_SIZE: usize = 1000;
+
+/// Creates the members of the CSV file in the `file` field of the form, one
+/// per row under a `first_name,last_name` header. Every row is checked first,
+/// with invalid ones `all_or_nothing` imports nothing and answers `422`.
+#[instrument(name = "Import members", skip(tenant, audit, app_state, multipart))]
+pub async fn import_members(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<ImportQuery>,
+    multipart: Multipart,
+) -> Result<Response, AppError> {
+    let file = read_file(multipart).await?;
+    let (members, errors) = parse(&file)?;
+
+    if query.mode == ImportMode::AllOrNothing && !errors.is_empty() {
+        let report = ImportReport {
+            mode: query.mode,
+            imported: vec![],
+            errors,
+        };
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(report)).into_response());
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let imported = members
+        .iter()
+        .map(|_| uuid::Uuid::new_v4())
+        .collect::<Vec<_>>();
+    for (ids, members) in imported.chunks(BATCH_SIZE).zip(members.chunks(BATCH_SIZE)) {
+        let mut qb = sqlx::QueryBuilder::new(
+            "INSERT INTO members (member_id, first_name, last_name, organization_id) ",
+        );
+        qb.push_values(ids.iter().zip(members), |mut b, (id, member)| {
+            b.push_bind(id)
+                .push_bind(&member.first_name)
+                .push_bind(&member.last_name)
+                .push_bind(tenant.organization_id);
+        });
+        qb.build()
+            .execute(&mut *transaction)
+            .await
+            .context("Failed to import members")
+            .map_err(AppError::E500)?;
+    }
+
+    if !imported.is_empty() {
+        let import = json!({ "mode": query.mode, "members": &imported });
+        audit
+            .record(
+                &mut transaction,
+                tenant,
+                AuditEvent::new("member.import", "organization", tenant.organization_id)
+                    .after(import),
+            )
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    let report = ImportReport {
+        mode: query.mode,
+        imported,
+        errors,
+    };
+    Ok(Json(report).into_response())
+}
+
+async fn read_file(mut multipart: Multipart) -> Result<Bytes, AppError> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .context("Invalid multipart form")
+        .map_err(AppError::E400)?
+    {
+        if field.name() == Some("file") {
+            return field
+                .bytes()
+                .await
+                .context("Failed to read the uploaded file")
+                .map_err(AppError::E400);
+        }
+    }
+    Err(AppError::E400(anyhow::anyhow!("Missing `file` field")))
+}
+
+/// The valid rows of the file and the errors of the others. `400` for files
+/// without the expected header.
+fn parse(file: &[u8]) -> Result<(Vec<CreateMember>, Vec<RowError>), AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(file);
+    let headers = reader
+        .headers()
+        .context("Failed to read the CSV header")
+        .map_err(AppError::E400)?
+        .clone();
+    for column in ["first_name", "last_name"] {
+        if !headers.iter().any(|header| header == column) {
+            return Err(AppError::E400(anyhow::anyhow!("Missing `{column}` column")));
+        }
+    }
+
+    let mut members = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let row = record.and_then(|record| {
+            let line = record.position().map_or(0, |p| p.line());
+            record
+                .deserialize::<CreateMember>(Some(&headers))
+                .map(|member| (line, member))
+        });
+        let (line, member) = match row {
+            Ok(row) => row,
+            Err(e) => {
+                errors.push(RowError {
+                    line: e.position().map_or(0, |p| p.line()),
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        match member.validate() {
+            Ok(()) => members.push(member),
+            Err(e) => errors.push(RowError {
+                line,
+                message: e.to_string(),
+            }),
+        }
+    }
+    Ok((members, errors))
+}
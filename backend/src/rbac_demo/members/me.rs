@@ -0,0 +1,51 @@
+use crate::app_states::AppState;
+use crate::errors::AppError;
+use crate::rbac_demo::members::models::{Member, Profile};
+use crate::routers::session_state::TypeSession;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// The logged in user and the member it is linked to in the selected
+/// organization, no member without one selected.
+#[instrument(name = "Get the current user", skip_all)]
+pub async fn get_me(
+    session: TypeSession,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Profile>, AppError> {
+    let user_id = session.require_user_id()?;
+    let organization_id = session.get_organization_id();
+
+    let username = sqlx::query_scalar!("SELECT username FROM users WHERE user_id = $1", user_id)
+        .fetch_optional(&app_state.pool)
+        .await
+        .context("Failed to fetch user")
+        .map_err(AppError::E500)?
+        .ok_or_else(|| AppError::E401(anyhow::anyhow!("User {user_id} no longer exists")))?;
+
+    let member = match organization_id {
+        Some(organization_id) => sqlx::query_as!(
+            Member,
+            r#"
+            SELECT member_id, first_name, last_name, user_id, deleted_at
+            FROM members
+            WHERE user_id = $1 AND organization_id = $2 AND deleted_at IS NULL
+            "#,
+            user_id,
+            organization_id
+        )
+        .fetch_optional(&app_state.pool)
+        .await
+        .context("Failed to fetch member")
+        .map_err(AppError::E500)?,
+        None => None,
+    };
+
+    Ok(Json(Profile {
+        user_id,
+        username,
+        organization_id,
+        member,
+    }))
+}
@@ -1,15 +1,109 @@
+use crate::rbac_demo::projects::models::ProjectRole;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 pub struct Member {
     pub member_id: uuid::Uuid,
     pub first_name: String,
     pub last_name: String,
+    /// The login user of the member, see `POST /members/invite`.
+    pub user_id: Option<uuid::Uuid>,
+    /// Set while the member is deleted, see `POST /members/{id}/restore`.
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateMember {
+    #[validate(length(min = 1, max = 255))]
     pub first_name: String,
+    #[validate(length(min = 1, max = 255))]
     pub last_name: String,
 }
+
+/// How `POST /members/import` treats a file with invalid rows.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Nothing is imported.
+    #[default]
+    AllOrNothing,
+    /// The valid rows are imported.
+    BestEffort,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+/// Outcome of `POST /members/import`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    /// Members created, in the order of the file.
+    pub imported: Vec<uuid::Uuid>,
+    pub errors: Vec<RowError>,
+}
+
+/// A row of an import that was not valid, `line` counting the header.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowError {
+    pub line: u64,
+    pub message: String,
+}
+
+/// The caller of `GET /me`, with its member profile in the selected
+/// organization if it has one there.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub user_id: uuid::Uuid,
+    pub username: String,
+    pub organization_id: Option<uuid::Uuid>,
+    pub member: Option<Member>,
+}
+
+/// Body of `PATCH /members/{id}`, absent fields are kept.
+#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+pub struct PatchMember {
+    #[validate(length(min = 1, max = 255))]
+    pub first_name: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub last_name: Option<String>,
+}
+
+impl From<CreateMember> for PatchMember {
+    fn from(member: CreateMember) -> Self {
+        Self {
+            first_name: Some(member.first_name),
+            last_name: Some(member.last_name),
+        }
+    }
+}
+
+/// Filter of `GET /members`, names are matched exactly.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct MemberFilter {
+    #[serde(flatten)]
+    pub columns: MemberColumnFilter,
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct MemberColumnFilter {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+/// A project the member is assigned to, see `GET /members/{id}/projects`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MemberProject {
+    pub project_id: uuid::Uuid,
+    pub name: String,
+    pub role: ProjectRole,
+    pub added_at: DateTime<Utc>,
+}
@@ -0,0 +1,28 @@
+use crate::app_states::AppState;
+use crate::audit::Audit;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::members::models::{Member, PatchMember};
+use crate::rbac_demo::members::put::save_member;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// Updates the fields of the member present in the body.
+#[instrument(name = "Patch a member", skip(tenant, audit, app_state))]
+pub async fn patch_member(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(member_id): Path<uuid::Uuid>,
+    Json(request): Json<PatchMember>,
+) -> Result<Json<Member>, AppError> {
+    request
+        .validate()
+        .context("Invalid member")
+        .map_err(AppError::E400)?;
+
+    save_member(tenant, audit, &app_state, member_id, request).await
+}
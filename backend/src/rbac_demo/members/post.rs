@@ -1,37 +1,69 @@
 use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
 use crate::errors::AppError;
 use crate::rbac_demo::members::models::{CreateMember, Member};
 use anyhow::Context;
 use axum::extract::{Json, State};
 use std::sync::Arc;
 use tracing::instrument;
+use validator::Validate;
 
 #[instrument(
     name = "Create a new member",
-    skip(app_state),
+    skip(tenant, audit, app_state),
     fields(
         first_name = request.first_name,
         last_name = request.last_name
     )
 )]
 pub async fn create_new_member(
+    tenant: Tenant,
+    audit: Audit,
     State(app_state): State<Arc<AppState>>,
     Json(request): Json<CreateMember>,
 ) -> Result<Json<Member>, AppError> {
+    request
+        .validate()
+        .context("Invalid member")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
     let member = sqlx::query_as!(
         Member,
         r#"
-        INSERT INTO members (member_id, first_name, last_name)
-        VALUES (gen_random_uuid(), $1, $2)
-        RETURNING member_id, first_name, last_name
+        INSERT INTO members (member_id, first_name, last_name, organization_id)
+        VALUES (gen_random_uuid(), $1, $2, $3)
+        RETURNING member_id, first_name, last_name, user_id, deleted_at
         "#,
         request.first_name,
         request.last_name,
+        tenant.organization_id,
     )
-    .fetch_one(&app_state.pool)
+    .fetch_one(&mut *transaction)
     .await
     .context("Failed to create new member")
     .map_err(AppError::E500)?;
 
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("member.create", "member", member.member_id).after(&member),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
     Ok(Json(member))
 }
@@ -0,0 +1,95 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::members::models::{CreateMember, Member, PatchMember};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// Replaces the names of the member.
+#[instrument(name = "Update a member", skip(tenant, audit, app_state))]
+pub async fn update_member(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(member_id): Path<uuid::Uuid>,
+    Json(request): Json<CreateMember>,
+) -> Result<Json<Member>, AppError> {
+    request
+        .validate()
+        .context("Invalid member")
+        .map_err(AppError::E400)?;
+
+    save_member(tenant, audit, &app_state, member_id, request.into()).await
+}
+
+/// Applies `changes` to a member that is not deleted, recording the update.
+pub(super) async fn save_member(
+    tenant: Tenant,
+    audit: Audit,
+    app_state: &AppState,
+    member_id: uuid::Uuid,
+    changes: PatchMember,
+) -> Result<Json<Member>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before = sqlx::query_as!(
+        Member,
+        r#"
+        SELECT member_id, first_name, last_name, user_id, deleted_at
+        FROM members
+        WHERE member_id = $1 AND organization_id = $2 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        member_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch member")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Member {member_id} not found")))?;
+
+    let member = sqlx::query_as!(
+        Member,
+        r#"
+        UPDATE members
+        SET first_name = coalesce($2, first_name), last_name = coalesce($3, last_name)
+        WHERE member_id = $1
+        RETURNING member_id, first_name, last_name, user_id, deleted_at
+        "#,
+        member_id,
+        changes.first_name,
+        changes.last_name
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to update member")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("member.update", "member", member_id)
+                .before(&before)
+                .after(&member),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(member))
+}
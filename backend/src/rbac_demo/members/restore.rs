@@ -0,0 +1,79 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::members::models::Member;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Undoes the deletion of a member, `409` if it is not deleted.
+#[instrument(name = "Restore a member", skip(tenant, audit, app_state))]
+pub async fn restore_member(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(member_id): Path<uuid::Uuid>,
+) -> Result<Json<Member>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before = sqlx::query_as!(
+        Member,
+        r#"
+        SELECT member_id, first_name, last_name, user_id, deleted_at
+        FROM members
+        WHERE member_id = $1 AND organization_id = $2
+        FOR UPDATE
+        "#,
+        member_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch member")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Member {member_id} not found")))?;
+    if before.deleted_at.is_none() {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Member {member_id} is not deleted"
+        )));
+    }
+
+    let member = sqlx::query_as!(
+        Member,
+        r#"
+        UPDATE members SET deleted_at = NULL
+        WHERE member_id = $1
+        RETURNING member_id, first_name, last_name, user_id, deleted_at
+        "#,
+        member_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to restore member")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("member.restore", "member", member_id)
+                .before(&before)
+                .after(&member),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(member))
+}
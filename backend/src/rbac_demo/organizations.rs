@@ -0,0 +1,5 @@
+pub mod get;
+pub mod models;
+pub mod post;
+pub mod select;
+pub mod update_users;
@@ -0,0 +1,64 @@
+use crate::app_states::AppState;
+use crate::errors::AppError;
+use crate::rbac_demo::organizations::models::{Organization, OrganizationUser};
+use crate::rbac_demo::organizations::select::require_membership;
+use crate::routers::session_state::TypeSession;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Organizations the logged-in user belongs to.
+#[instrument(skip_all)]
+pub async fn list_organizations(
+    session: TypeSession,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Organization>>, AppError> {
+    let user_id = session.require_user_id()?;
+
+    let organizations = sqlx::query_as!(
+        Organization,
+        r#"
+        SELECT o.organization_id, o.name, o.created_at
+        FROM organizations as o
+        JOIN organizations_users as ou ON o.organization_id = ou.organization_id
+        WHERE ou.user_id = $1
+        ORDER BY o.name
+        "#,
+        user_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch organizations of user")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(organizations))
+}
+
+#[instrument(skip_all)]
+pub async fn list_organization_users(
+    session: TypeSession,
+    Path(organization_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<OrganizationUser>>, AppError> {
+    let user_id = session.require_user_id()?;
+    require_membership(&app_state.pool, organization_id, user_id).await?;
+
+    let users = sqlx::query_as!(
+        OrganizationUser,
+        r#"
+        SELECT u.user_id, u.username
+        FROM users as u
+        JOIN organizations_users as ou ON u.user_id = ou.user_id
+        WHERE ou.organization_id = $1
+        ORDER BY u.username
+        "#,
+        organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch users of organization")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(users))
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// A tenant. Roles, members, projects and access requests belong to exactly
+/// one organization; users can join several and hold different roles in
+/// each.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Organization {
+    pub organization_id: uuid::Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateOrganization {
+    #[validate(length(min = 1))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct OrganizationUser {
+    pub user_id: uuid::Uuid,
+    pub username: String,
+}
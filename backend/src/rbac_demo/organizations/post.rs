@@ -0,0 +1,96 @@
+use crate::app_states::AppState;
+use crate::audit::{Actor, Audit, AuditEvent};
+use crate::errors::AppError;
+use crate::rbac_demo::organizations::models::{CreateOrganization, Organization};
+use crate::rbac_demo::rbac::presets::{admin_role_id, seed_presets};
+use crate::rbac_demo::rbac::users::update_roles::grant_roles;
+use crate::routers::session_state::TypeSession;
+use crate::utils::db;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// The creator joins the new organization, which starts with the preset
+/// roles, as its Admin.
+#[instrument(name = "Create a new organization", skip(session, audit, app_state))]
+pub async fn create_new_organization(
+    session: TypeSession,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<CreateOrganization>,
+) -> Result<Json<Organization>, AppError> {
+    let user_id = session.require_user_id()?;
+    request
+        .validate()
+        .context("Invalid organization")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let organization = sqlx::query_as!(
+        Organization,
+        r#"
+        INSERT INTO organizations (name)
+        VALUES ($1)
+        RETURNING organization_id, name, created_at
+        "#,
+        request.name
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(|e| {
+        if db::is_unique_violation(&e) {
+            AppError::E409(anyhow::anyhow!(e).context("Organization already exists"))
+        } else {
+            AppError::E500(anyhow::anyhow!(e).context("Failed to create new organization"))
+        }
+    })?;
+
+    sqlx::query!(
+        "INSERT INTO organizations_users (organization_id, user_id) VALUES ($1, $2)",
+        organization.organization_id,
+        user_id
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to join the new organization")
+    .map_err(AppError::E500)?;
+
+    seed_presets(&mut transaction, organization.organization_id).await?;
+    let admin = admin_role_id(&mut transaction, organization.organization_id)
+        .await
+        .map_err(AppError::E500)?;
+    grant_roles(&mut *transaction, user_id, &[admin], None, Utc::now(), None)
+        .await
+        .map_err(AppError::E500)?;
+
+    let actor = Actor {
+        user_id: Some(user_id),
+        organization_id: Some(organization.organization_id),
+    };
+    let event = AuditEvent::new(
+        "organization.create",
+        "organization",
+        organization.organization_id,
+    );
+    audit
+        .record(&mut transaction, actor, event.after(&organization))
+        .await
+        .map_err(AppError::E500)?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(organization))
+}
@@ -0,0 +1,58 @@
+use crate::app_states::AppState;
+use crate::errors::AppError;
+use crate::routers::session_state::TypeSession;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Switches the session to another organization of the user.
+#[instrument(name = "Select organization", skip(session, app_state))]
+pub async fn select_organization(
+    session: TypeSession,
+    Path(organization_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<StatusCode, AppError> {
+    let user_id = session.require_user_id()?;
+    require_membership(&app_state.pool, organization_id, user_id).await?;
+
+    session.insert_organization_id(organization_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `404` for unknown organizations, `403` if the user is not a member.
+#[instrument(skip(pool))]
+pub(crate) async fn require_membership(
+    pool: &PgPool,
+    organization_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    let membership = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM organizations_users WHERE organization_id = $1 AND user_id = $2
+        ) as "member!"
+        FROM organizations
+        WHERE organization_id = $1
+        "#,
+        organization_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to check organization membership")
+    .map_err(AppError::E500)?;
+
+    match membership {
+        None => Err(AppError::E404(anyhow::anyhow!(
+            "Organization {organization_id} not found"
+        ))),
+        Some(false) => Err(AppError::E403(anyhow::anyhow!(
+            "User {user_id} is not a member of organization {organization_id}"
+        ))),
+        Some(true) => Ok(()),
+    }
+}
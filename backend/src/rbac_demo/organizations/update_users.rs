@@ -0,0 +1,185 @@
+use crate::app_states::AppState;
+use crate::audit::{Actor, Audit, AuditEvent};
+use crate::errors::AppError;
+use crate::models::AssociationResult;
+use crate::rbac_demo::organizations::select::require_membership;
+use crate::routers::session_state::TypeSession;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(
+    name = "Add users to organization",
+    skip(session, audit, app_state),
+    fields(organization_id = %organization_id, users = ?users),
+)]
+pub async fn add_organization_users(
+    session: TypeSession,
+    audit: Audit,
+    Path(organization_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(users): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    let caller_id = session.require_user_id()?;
+    require_membership(&app_state.pool, organization_id, caller_id).await?;
+
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT requested.user_id as "user_id!"
+        FROM unnest($1::uuid[]) as requested (user_id)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM users as u WHERE u.user_id = requested.user_id
+        )
+        "#,
+        &users as &[uuid::Uuid]
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to check if users exist")
+    .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        return Err(AppError::E404(anyhow::anyhow!(
+            "Users {unknown:?} not found"
+        )));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let added = sqlx::query_scalar!(
+        r#"
+        INSERT INTO organizations_users (organization_id, user_id)
+        SELECT $1, unnest($2::uuid[])
+        ON CONFLICT (organization_id, user_id) DO NOTHING
+        RETURNING user_id
+        "#,
+        organization_id,
+        &users as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to add users to organization")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_added(&users, added);
+    if !result.is_noop() {
+        let actor = Actor {
+            user_id: Some(caller_id),
+            organization_id: Some(organization_id),
+        };
+        let event = AuditEvent::new("organization.users.add", "organization", organization_id);
+        audit
+            .record(&mut transaction, actor, event.after(&result))
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok((result.status_code(), Json(result)).into_response())
+}
+
+/// Removed users also lose every role they held in the organization.
+#[instrument(
+    name = "Remove users from organization",
+    skip(session, audit, app_state),
+    fields(organization_id = %organization_id, users = ?users),
+)]
+pub async fn remove_organization_users(
+    session: TypeSession,
+    audit: Audit,
+    Path(organization_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(users): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    let caller_id = session.require_user_id()?;
+    require_membership(&app_state.pool, organization_id, caller_id).await?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    sqlx::query!(
+        r#"
+        WITH revoked AS (
+            DELETE FROM users_roles as ur
+            USING roles as r
+            WHERE ur.role_id = r.role_id
+                AND r.organization_id = $1
+                AND ur.user_id = ANY($2)
+            RETURNING ur.user_id, ur.role_id, ur.project_id, ur.valid_from, ur.valid_until
+        )
+        INSERT INTO users_roles_audit (user_id, role_id, project_id, valid_from, valid_until, event)
+        SELECT user_id, role_id, project_id, valid_from, valid_until, 'revoked'
+        FROM revoked
+        "#,
+        organization_id,
+        &users as &[uuid::Uuid]
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to revoke roles in organization")
+    .map_err(AppError::E500)?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM roles_approvers as ra
+        USING roles as r
+        WHERE ra.role_id = r.role_id
+            AND r.organization_id = $1
+            AND ra.user_id = ANY($2)
+        "#,
+        organization_id,
+        &users as &[uuid::Uuid]
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to remove approvers in organization")
+    .map_err(AppError::E500)?;
+
+    let removed = sqlx::query_scalar!(
+        r#"
+        DELETE FROM organizations_users
+        WHERE organization_id = $1 AND user_id = ANY($2)
+        RETURNING user_id
+        "#,
+        organization_id,
+        &users as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to remove users from organization")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_removed(&users, removed);
+    if !result.is_noop() {
+        let actor = Actor {
+            user_id: Some(caller_id),
+            organization_id: Some(organization_id),
+        };
+        let event = AuditEvent::new("organization.users.remove", "organization", organization_id);
+        audit
+            .record(&mut transaction, actor, event.after(&result))
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok((result.status_code(), Json(result)).into_response())
+}
@@ -1,4 +1,8 @@
+pub mod archive;
 pub mod delete;
+pub mod export;
 pub mod get;
 pub mod models;
+pub mod patch;
 pub mod post;
+pub mod update_members;
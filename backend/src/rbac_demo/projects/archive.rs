@@ -0,0 +1,96 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::projects::models::Project;
+use crate::rbac_demo::projects::patch::lock_project;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Makes the project read-only and hides it from default lists, `409` if it
+/// is archived already.
+#[instrument(name = "Archive a project", skip(tenant, audit, app_state))]
+pub async fn archive_project(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(project_id): Path<uuid::Uuid>,
+) -> Result<Json<Project>, AppError> {
+    set_archived(tenant, audit, &app_state, project_id, true).await
+}
+
+/// Undoes the archiving of a project, `409` if it is not archived.
+#[instrument(name = "Restore a project", skip(tenant, audit, app_state))]
+pub async fn restore_project(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(project_id): Path<uuid::Uuid>,
+) -> Result<Json<Project>, AppError> {
+    set_archived(tenant, audit, &app_state, project_id, false).await
+}
+
+async fn set_archived(
+    tenant: Tenant,
+    audit: Audit,
+    app_state: &AppState,
+    project_id: uuid::Uuid,
+    archived: bool,
+) -> Result<Json<Project>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before = lock_project(&mut transaction, tenant.organization_id, project_id).await?;
+    if before.archived_at.is_some() == archived {
+        let state = if archived { "archived" } else { "not archived" };
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Project {project_id} is {state}"
+        )));
+    }
+
+    let project = sqlx::query_as!(
+        Project,
+        r#"
+        UPDATE projects
+        SET archived_at = CASE WHEN $2 THEN now() END, updated_at = now()
+        WHERE project_id = $1
+        RETURNING project_id, name, description, created_by, created_at, updated_at,
+            archived_at
+        "#,
+        project_id,
+        archived
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to archive project")
+    .map_err(AppError::E500)?;
+
+    let action = if archived {
+        "project.archive"
+    } else {
+        "project.restore"
+    };
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new(action, "project", project_id)
+                .before(&before)
+                .after(&project),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(project))
+}
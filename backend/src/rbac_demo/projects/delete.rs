@@ -1,41 +1,87 @@
 use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
 use crate::errors::AppError;
+use crate::rbac_demo::projects::models::Project;
+use anyhow::Context;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use std::sync::Arc;
 use tracing::instrument;
 
-#[instrument(name = "Delete a project", skip(app_state))]
+#[instrument(name = "Delete a project", skip(tenant, audit, app_state))]
 pub async fn delete_project(
+    tenant: Tenant,
+    audit: Audit,
     State(app_state): State<Arc<AppState>>,
     Path(project_id): Path<uuid::Uuid>,
 ) -> Result<StatusCode, AppError> {
-    let deleted = delete_project_from_db(&app_state.pool, project_id)
+    let mut transaction = app_state
+        .pool
+        .begin()
         .await
+        .context("Failed to start transaction")
         .map_err(AppError::E500)?;
 
-    if !deleted {
+    let deleted = delete_project_from_db(&mut transaction, tenant.organization_id, project_id)
+        .await
+        .map_err(AppError::E500)?;
+
+    let Some(project) = deleted else {
         return Ok(StatusCode::NOT_FOUND);
-    }
+    };
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("project.delete", "project", project_id).before(&project),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Role grants bound to the project are revoked along with it.
 #[instrument(name = "Try to delete the project from DB", skip_all)]
 async fn delete_project_from_db(
-    pool: &sqlx::PgPool,
+    connection: &mut sqlx::PgConnection,
+    organization_id: uuid::Uuid,
     project_id: uuid::Uuid,
-) -> Result<bool, anyhow::Error> {
-    let count = sqlx::query!(
+) -> Result<Option<Project>, anyhow::Error> {
+    let deleted = sqlx::query_as!(
+        Project,
         r#"
-        DELETE FROM projects
-        WHERE project_id = $1
+        WITH deleted AS (
+            DELETE FROM projects
+            WHERE project_id = $1 AND organization_id = $2
+            RETURNING project_id, name, description, created_by, created_at, updated_at,
+                archived_at
+        ), revoked AS (
+            DELETE FROM users_roles
+            WHERE project_id IN (SELECT project_id FROM deleted)
+            RETURNING user_id, role_id, project_id, valid_from, valid_until
+        ), audited AS (
+            INSERT INTO users_roles_audit
+                (user_id, role_id, project_id, valid_from, valid_until, event)
+            SELECT user_id, role_id, project_id, valid_from, valid_until, 'revoked'
+            FROM revoked
+        )
+        SELECT project_id as "project_id!", name as "name!", description as "description!",
+            created_by, created_at as "created_at!", updated_at as "updated_at!", archived_at
+        FROM deleted
         "#,
-        project_id
+        project_id,
+        organization_id
     )
-    .execute(pool)
-    .await?
-    .rows_affected();
+    .fetch_optional(connection)
+    .await?;
 
-    Ok(count > 0)
+    Ok(deleted)
 }
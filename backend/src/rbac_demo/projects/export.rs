@@ -0,0 +1,68 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::ExportRequest;
+use crate::rbac_demo::projects::get::{push_filter, push_visible, visible_to};
+use crate::rbac_demo::projects::models::{Project, ProjectFilter};
+use crate::utils;
+use anyhow::Context;
+use axum::body::Body;
+use axum::extract::State;
+use axum::response::Response;
+use futures::TryStreamExt;
+use serde_qs::axum::QsQuery;
+use sqlx::QueryBuilder;
+use std::sync::Arc;
+use tracing::instrument;
+
+const COLUMNS: &[&str] = &[
+    "project_id",
+    "name",
+    "description",
+    "created_by",
+    "created_at",
+    "updated_at",
+    "archived_at",
+];
+
+/// Rows fetched from the cursor per chunk of the response.
+const BATCH_SIZE: usize = 500;
+
+/// Every project `GET /projects` lists to the caller with the same filter, as
+/// CSV streamed through a server-side cursor.
+#[instrument(name = "Export projects", skip_all)]
+pub async fn export_projects(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    QsQuery(request): QsQuery<ExportRequest<ProjectFilter>>,
+) -> Result<Response, AppError> {
+    let filter = request.filter.unwrap_or_default();
+    let (visibility, attributes) = visible_to(&app_state, &tenant).await?;
+
+    let mut qb = QueryBuilder::new(
+        r#"
+        DECLARE project_export NO SCROLL CURSOR FOR
+        SELECT project_id, name, description, created_by, created_at, updated_at, archived_at
+        FROM projects
+        "#,
+    );
+    push_visible(&mut qb, &tenant, &visibility, &attributes);
+    push_filter(&mut qb, &filter);
+    qb.push(" ORDER BY name, project_id");
+    let chunks = utils::db::stream_cursor(
+        &app_state.pool,
+        "project_export",
+        qb,
+        BATCH_SIZE,
+        |projects: &[Project], first| utils::csv::encode(COLUMNS, projects, first),
+    )
+    .await
+    .context("Failed to export projects")
+    .map_err(AppError::E500)?
+    .inspect_err(|e| tracing::error!(error = ?e, "Failed to stream projects"));
+
+    Ok(utils::csv::attachment(
+        "projects.csv",
+        Body::from_stream(chunks),
+    ))
+}
@@ -1,25 +1,55 @@
 use crate::app_states::AppState;
+use crate::authorization::{Attributes, Condition, SqlColumns, Target, Tenant};
 use crate::errors::AppError;
 use crate::models::{ListRequest, ListResponse, Pagination};
-use crate::rbac_demo::projects::models::Project;
-use crate::utils::db;
+use crate::rbac_demo::projects::models::{Project, ProjectFilter, ProjectMember, ProjectRole};
+use crate::rbac_demo::rbac::permissions::models::Permission;
 use anyhow::Context;
-use axum::extract::{Json, Query, State};
+use axum::extract::{Json, Path, State};
+use chrono::Utc;
+use serde_qs::axum::QsQuery;
+use sqlx::{Postgres, QueryBuilder};
 use std::sync::Arc;
 use tracing::instrument;
 
+/// `resource.*` attributes conditions on `project:read` may filter on.
+const PROJECT_COLUMNS: SqlColumns = &[
+    ("id", "project_id::text"),
+    ("name", "name"),
+    ("description", "description"),
+    ("created_by", "created_by::text"),
+];
+
+/// Which projects of the organization a caller may list.
+pub(super) enum Visibility {
+    All,
+    /// Those the caller holds any role in, and those any of the conditions
+    /// of their `project:read:*` permissions hold for.
+    Limited(Vec<Condition>),
+}
+
+/// Projects of the organization the caller holds any role in, or all of them
+/// if an organization-wide role covers `project:read:*`, e.g. `*:*:*`. A
+/// covering permission with a condition only adds the projects it holds for.
+/// Archived projects are skipped unless asked for.
 #[instrument(name = "List all projects", skip_all)]
 pub async fn list_projects(
+    tenant: Tenant,
     State(app_state): State<Arc<AppState>>,
-    Query(request): Query<ListRequest<()>>,
+    QsQuery(request): QsQuery<ListRequest<ProjectFilter>>,
 ) -> Result<Json<ListResponse<Project>>, AppError> {
-    let mut qb = sqlx::QueryBuilder::new(
+    let filter = request.filter.unwrap_or_default();
+    let (visibility, attributes) = visible_to(&app_state, &tenant).await?;
+
+    let mut qb = QueryBuilder::new(
         r#"
-        SELECT project_id, name, description
+        SELECT project_id, name, description, created_by, created_at, updated_at, archived_at
         FROM projects
         "#,
     );
-
+    push_visible(&mut qb, &tenant, &visibility, &attributes);
+    push_filter(&mut qb, &filter);
+    qb.push(" ORDER BY name, project_id");
     Pagination::to_query(request.current_page, request.page_size, &mut qb);
 
     let projects = qb
@@ -29,7 +59,12 @@ pub async fn list_projects(
         .context("Failed to fetch projects")
         .map_err(AppError::E500)?;
 
-    let total = db::count("projects", &(), &app_state.pool)
+    let mut qb = QueryBuilder::new("SELECT count(*) FROM projects");
+    push_visible(&mut qb, &tenant, &visibility, &attributes);
+    push_filter(&mut qb, &filter);
+    let total: i64 = qb
+        .build_query_scalar()
+        .fetch_one(&app_state.pool)
         .await
         .context("Failed to fetch projects count")
         .map_err(AppError::E500)?;
@@ -40,3 +75,118 @@ pub async fn list_projects(
         page: request.current_page,
     }))
 }
+
+/// Members assigned to the project, by name.
+#[instrument(skip_all)]
+pub async fn list_project_members(
+    tenant: Tenant,
+    Path(project_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ProjectMember>>, AppError> {
+    sqlx::query_scalar!(
+        "SELECT 1 FROM projects WHERE project_id = $1 AND organization_id = $2",
+        project_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&app_state.pool)
+    .await
+    .context("Failed to fetch project")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Project {project_id} not found")))?;
+
+    let members = sqlx::query_as!(
+        ProjectMember,
+        r#"
+        SELECT m.member_id, m.first_name, m.last_name, pm.role as "role: ProjectRole",
+            pm.added_at
+        FROM project_members as pm
+        JOIN members as m ON m.member_id = pm.member_id
+        WHERE pm.project_id = $1
+        ORDER BY m.last_name, m.first_name, m.member_id
+        "#,
+        project_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch project members")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(members))
+}
+
+/// Which projects the caller may list, and the attributes its conditions
+/// are evaluated with.
+pub(super) async fn visible_to(
+    app_state: &AppState,
+    tenant: &Tenant,
+) -> Result<(Visibility, Attributes), AppError> {
+    let permissions = app_state
+        .permission_cache
+        .effective_permissions(&app_state.pool, tenant.user_id, tenant.organization_id)
+        .await
+        .map_err(AppError::E500)?;
+    let visibility = visibility(permissions.matching(&Target::new("project", "read", "*"), None));
+    let attributes = Attributes::for_user(tenant.user_id, tenant.organization_id, Utc::now());
+    Ok((visibility, attributes))
+}
+
+/// Conditions that cannot be expressed in SQL grant nothing, see also
+/// [`push_visible`].
+fn visibility(readable: Vec<&Permission>) -> Visibility {
+    let mut conditions = Vec::new();
+    for permission in readable {
+        match permission.parsed_condition() {
+            Ok(None) => return Visibility::All,
+            Ok(Some(condition)) => match condition.check_columns(PROJECT_COLUMNS) {
+                Ok(()) => conditions.push(condition),
+                Err(e) => tracing::warn!(error = %e, "Ignoring project read condition"),
+            },
+            Err(e) => tracing::warn!(error = %e, "Ignoring project read condition"),
+        }
+    }
+    Visibility::Limited(conditions)
+}
+
+pub(super) fn push_visible(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    tenant: &Tenant,
+    visibility: &Visibility,
+    attributes: &Attributes,
+) {
+    qb.push(" WHERE organization_id = ")
+        .push_bind(tenant.organization_id);
+    let Visibility::Limited(conditions) = visibility else {
+        return;
+    };
+
+    qb.push(
+        r#"
+        AND (project_id IN (
+            SELECT project_id FROM users_roles
+            WHERE valid_from <= now()
+                AND (valid_until IS NULL OR valid_until > now())
+                AND user_id = "#,
+    )
+    .push_bind(tenant.user_id)
+    .push(")");
+    for condition in conditions {
+        qb.push(" OR ");
+        if let Err(e) = condition.push_sql(qb, attributes, PROJECT_COLUMNS) {
+            tracing::warn!(error = %e, "Ignoring project read condition");
+            qb.push("FALSE");
+        }
+    }
+    qb.push(")");
+}
+
+pub(super) fn push_filter<'a>(qb: &mut QueryBuilder<'a, Postgres>, filter: &'a ProjectFilter) {
+    if let Some(name) = &filter.name {
+        qb.push(" AND name = ").push_bind(name);
+    }
+    if let Some(created_by) = filter.created_by {
+        qb.push(" AND created_by = ").push_bind(created_by);
+    }
+    if !filter.include_archived {
+        qb.push(" AND archived_at IS NULL");
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -6,6 +7,12 @@ pub struct Project {
     pub project_id: uuid::Uuid,
     pub name: String,
     pub description: String,
+    /// The user who created the project, `resource.created_by` in conditions.
+    pub created_by: Option<uuid::Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Set while the project is archived, which makes it read-only.
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,3 +20,46 @@ pub struct CreateProject {
     pub name: String,
     pub description: String,
 }
+
+/// Body of `PATCH /projects/{id}`, absent fields are kept.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PatchProject {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Filter of `GET /projects`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProjectFilter {
+    pub name: Option<String>,
+    pub created_by: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// Role of a member in a project.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum ProjectRole {
+    Owner,
+    Maintainer,
+    Contributor,
+}
+
+/// A member assigned to the project, see `GET /projects/{id}/members`.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ProjectMember {
+    pub member_id: uuid::Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: ProjectRole,
+    pub added_at: DateTime<Utc>,
+}
+
+/// An item of the body of `POST /projects/{id}/members/add`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectMemberAssignment {
+    pub member_id: uuid::Uuid,
+    pub role: ProjectRole,
+}
@@ -0,0 +1,107 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::projects::models::{PatchProject, Project};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Updates the fields of the project present in the body, `409` for an
+/// archived project.
+#[instrument(name = "Patch a project", skip(tenant, audit, app_state))]
+pub async fn patch_project(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(project_id): Path<uuid::Uuid>,
+    Json(request): Json<PatchProject>,
+) -> Result<Json<Project>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before =
+        lock_writable_project(&mut transaction, tenant.organization_id, project_id).await?;
+
+    let project = sqlx::query_as!(
+        Project,
+        r#"
+        UPDATE projects
+        SET name = coalesce($2, name), description = coalesce($3, description),
+            updated_at = now()
+        WHERE project_id = $1
+        RETURNING project_id, name, description, created_by, created_at, updated_at,
+            archived_at
+        "#,
+        project_id,
+        request.name,
+        request.description
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to update project")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("project.update", "project", project_id)
+                .before(&before)
+                .after(&project),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(project))
+}
+
+/// Like [`lock_project`], `409` for an archived project as those are
+/// read-only, along with their tasks and members.
+pub(crate) async fn lock_writable_project(
+    connection: &mut sqlx::PgConnection,
+    organization_id: uuid::Uuid,
+    project_id: uuid::Uuid,
+) -> Result<Project, AppError> {
+    let project = lock_project(connection, organization_id, project_id).await?;
+    if project.archived_at.is_some() {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Project {project_id} is archived"
+        )));
+    }
+    Ok(project)
+}
+
+/// The project of the organization, locked for the rest of the transaction.
+pub(super) async fn lock_project(
+    connection: &mut sqlx::PgConnection,
+    organization_id: uuid::Uuid,
+    project_id: uuid::Uuid,
+) -> Result<Project, AppError> {
+    sqlx::query_as!(
+        Project,
+        r#"
+        SELECT project_id, name, description, created_by, created_at, updated_at, archived_at
+        FROM projects
+        WHERE project_id = $1 AND organization_id = $2
+        FOR UPDATE
+        "#,
+        project_id,
+        organization_id
+    )
+    .fetch_optional(connection)
+    .await
+    .context("Failed to fetch project")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Project {project_id} not found")))
+}
@@ -1,4 +1,6 @@
 use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
 use crate::errors::AppError;
 use crate::rbac_demo::projects::models::{CreateProject, Project};
 use anyhow::Context;
@@ -8,27 +10,53 @@ use tracing::instrument;
 
 #[instrument(
     name = "Create a new project",
-    skip(app_state, request),
+    skip(tenant, audit, app_state, request),
     fields(name = request.name)
 )]
 pub async fn create_new_project(
+    tenant: Tenant,
+    audit: Audit,
     State(app_state): State<Arc<AppState>>,
     Json(request): Json<CreateProject>,
 ) -> Result<Json<Project>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
     let project = sqlx::query_as!(
         Project,
         r#"
-        INSERT INTO projects (project_id, name, description)
-        VALUES (gen_random_uuid(), $1, $2)
-        RETURNING project_id, name, description
+        INSERT INTO projects (project_id, name, description, organization_id, created_by)
+        VALUES (gen_random_uuid(), $1, $2, $3, $4)
+        RETURNING project_id, name, description, created_by, created_at, updated_at,
+            archived_at
         "#,
         request.name,
         request.description,
+        tenant.organization_id,
+        tenant.user_id,
     )
-    .fetch_one(&app_state.pool)
+    .fetch_one(&mut *transaction)
     .await
     .context("Failed to create new project")
     .map_err(AppError::E500)?;
 
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("project.create", "project", project.project_id).after(&project),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
     Ok(Json(project))
 }
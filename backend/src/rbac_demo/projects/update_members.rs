@@ -0,0 +1,164 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::AssociationResult;
+use crate::rbac_demo::projects::models::{ProjectMemberAssignment, ProjectRole};
+use crate::rbac_demo::projects::patch::lock_writable_project;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Assigns members to the project with a role each. Members already assigned
+/// keep their role and are reported as skipped.
+#[instrument(
+    name = "Add members to project",
+    skip(tenant, audit, app_state),
+    fields(project_id = %project_id)
+)]
+pub async fn add_project_members(
+    tenant: Tenant,
+    audit: Audit,
+    Path(project_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(assignments): Json<Vec<ProjectMemberAssignment>>,
+) -> Result<Response, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    lock_writable_project(&mut transaction, tenant.organization_id, project_id).await?;
+    let members = assignments
+        .iter()
+        .map(|assignment| assignment.member_id)
+        .collect::<Vec<_>>();
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT requested.member_id as "member_id!"
+        FROM unnest($1::uuid[]) as requested (member_id)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM members as m
+            WHERE m.member_id = requested.member_id
+                AND m.organization_id = $2
+                AND m.deleted_at IS NULL
+        )
+        "#,
+        &members as &[uuid::Uuid],
+        tenant.organization_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to check if members exist")
+    .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        return Err(AppError::E404(anyhow::anyhow!(
+            "Members {unknown:?} not found"
+        )));
+    }
+
+    let added = if assignments.is_empty() {
+        vec![]
+    } else {
+        let mut qb =
+            sqlx::QueryBuilder::new("INSERT INTO project_members (project_id, member_id, role) ");
+        qb.push_values(&assignments, |mut b, assignment| {
+            b.push_bind(project_id)
+                .push_bind(assignment.member_id)
+                .push_bind(assignment.role);
+        });
+        qb.push(" ON CONFLICT (project_id, member_id) DO NOTHING RETURNING member_id");
+        qb.build_query_scalar()
+            .fetch_all(&mut *transaction)
+            .await
+            .context("Failed to add members to project")
+            .map_err(AppError::E500)?
+    };
+
+    let result = AssociationResult::from_added(&members, added);
+    if !result.is_noop() {
+        let added = assignments
+            .iter()
+            .filter(|assignment| result.added.contains(&assignment.member_id))
+            .collect::<Vec<_>>();
+        let event = AuditEvent::new("project.members.add", "project", project_id);
+        audit
+            .record(&mut transaction, tenant, event.after(&added))
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok((result.status_code(), Json(result)).into_response())
+}
+
+#[instrument(
+    name = "Remove members from project",
+    skip(tenant, audit, app_state),
+    fields(project_id = %project_id, members = ?members)
+)]
+pub async fn remove_project_members(
+    tenant: Tenant,
+    audit: Audit,
+    Path(project_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(members): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    lock_writable_project(&mut transaction, tenant.organization_id, project_id).await?;
+    let removed = sqlx::query!(
+        r#"
+        DELETE FROM project_members
+        WHERE project_id = $1 AND member_id = ANY($2)
+        RETURNING member_id, role as "role: ProjectRole"
+        "#,
+        project_id,
+        &members as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to remove members from project")
+    .map_err(AppError::E500)?
+    .into_iter()
+    .map(|row| ProjectMemberAssignment {
+        member_id: row.member_id,
+        role: row.role,
+    })
+    .collect::<Vec<_>>();
+
+    let result = AssociationResult::from_removed(
+        &members,
+        removed
+            .iter()
+            .map(|assignment| assignment.member_id)
+            .collect(),
+    );
+    if !result.is_noop() {
+        let event = AuditEvent::new("project.members.remove", "project", project_id);
+        audit
+            .record(&mut transaction, tenant, event.before(&removed))
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok((result.status_code(), Json(result)).into_response())
+}
@@ -1,2 +1,10 @@
+pub mod access_requests;
+pub mod authz;
+pub mod cleanup;
+pub mod components;
+pub mod document;
 pub mod permissions;
+pub mod presets;
 pub mod roles;
+pub mod sod_rules;
+pub mod users;
@@ -0,0 +1,4 @@
+pub mod decide;
+pub mod get;
+pub mod models;
+pub mod post;
@@ -0,0 +1,345 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::{Tenant, expire_stale_requests};
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::access_requests::models::{
+    AccessRequest, AccessRequestStatus, Decision,
+};
+use crate::rbac_demo::rbac::sod_rules::check::violations_for_grant;
+use crate::rbac_demo::rbac::sod_rules::models::SodConflict;
+use crate::rbac_demo::rbac::users::update_roles::grant_roles;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Grants the requested role, or for a component request the role picked by
+/// the approver among those bound to the component. A grant breaking
+/// separation-of-duties rules is refused with `409`, leaving the request
+/// pending.
+#[instrument(name = "Approve access request", skip(tenant, audit, app_state))]
+pub async fn approve_access_request(
+    tenant: Tenant,
+    audit: Audit,
+    Path(access_request_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(decision): Json<Decision>,
+) -> Result<Response, AppError> {
+    let approver_id = tenant.user_id;
+    let mut transaction = begin_decision(&app_state).await?;
+    let request = lock_pending(&mut transaction, tenant.organization_id, access_request_id).await?;
+    if request.requester_id == approver_id {
+        return Err(AppError::E403(anyhow::anyhow!(
+            "Requesters cannot decide their own requests"
+        )));
+    }
+    if request.valid_until.is_some_and(|until| until <= Utc::now()) {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "The requested access window has ended"
+        )));
+    }
+
+    let role_id = match (request.role_id, decision.role_id) {
+        (Some(requested), None) => requested,
+        (Some(requested), Some(role_id)) if role_id == requested => requested,
+        (Some(_), Some(_)) => {
+            return Err(AppError::E400(anyhow::anyhow!(
+                "role_id differs from the requested role"
+            )));
+        }
+        // Component request, the database guarantees `component_id` is set.
+        (None, Some(role_id)) => {
+            let component_id = request.component_id;
+            let binds = sqlx::query_scalar!(
+                r#"
+                SELECT 1 as binds
+                FROM roles_components as rc
+                JOIN roles as r ON rc.role_id = r.role_id
+                WHERE rc.role_id = $1 AND rc.component_id = $2 AND r.organization_id = $3
+                "#,
+                role_id,
+                component_id,
+                tenant.organization_id
+            )
+            .fetch_optional(&mut *transaction)
+            .await
+            .context("Failed to check the roles of component")
+            .map_err(AppError::E500)?;
+            if binds.is_none() {
+                return Err(AppError::E400(anyhow::anyhow!(
+                    "Role {role_id} does not provide the requested component"
+                )));
+            }
+            role_id
+        }
+        (None, None) => {
+            return Err(AppError::E400(anyhow::anyhow!(
+                "role_id is required to approve a component request"
+            )));
+        }
+    };
+
+    let approves = sqlx::query_scalar!(
+        "SELECT 1 FROM roles_approvers WHERE role_id = $1 AND user_id = $2",
+        role_id,
+        approver_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to check approvers of role")
+    .map_err(AppError::E500)?;
+    if approves.is_none() {
+        return Err(AppError::E403(anyhow::anyhow!(
+            "User {approver_id} is not an approver of role {role_id}"
+        )));
+    }
+
+    let violations = violations_for_grant(&mut transaction, request.requester_id, &[role_id])
+        .await
+        .map_err(AppError::E500)?;
+    if !violations.is_empty() {
+        let body = SodConflict::new(violations);
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    grant_roles(
+        &mut *transaction,
+        request.requester_id,
+        &[role_id],
+        None,
+        Utc::now(),
+        request.valid_until,
+    )
+    .await
+    .map_err(AppError::E500)?;
+
+    let decided = decide(
+        &mut transaction,
+        access_request_id,
+        AccessRequestStatus::Approved,
+        Some(role_id),
+        approver_id,
+        decision.comment,
+    )
+    .await?;
+    let event = AuditEvent::new(
+        "access_request.approve",
+        "access_request",
+        access_request_id,
+    )
+    .before(&request)
+    .after(&decided);
+    audit
+        .record(&mut transaction, tenant, event)
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(decided).into_response())
+}
+
+#[instrument(name = "Reject access request", skip(tenant, audit, app_state))]
+pub async fn reject_access_request(
+    tenant: Tenant,
+    audit: Audit,
+    Path(access_request_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(decision): Json<Decision>,
+) -> Result<Json<AccessRequest>, AppError> {
+    let approver_id = tenant.user_id;
+    let mut transaction = begin_decision(&app_state).await?;
+    let request = lock_pending(&mut transaction, tenant.organization_id, access_request_id).await?;
+    if request.requester_id == approver_id {
+        return Err(AppError::E403(anyhow::anyhow!(
+            "Requesters cannot decide their own requests"
+        )));
+    }
+
+    let approves = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM roles_approvers
+            WHERE user_id = $1 AND (
+                role_id = $2
+                OR role_id IN (
+                    SELECT rc.role_id
+                    FROM roles_components as rc
+                    JOIN roles as r ON rc.role_id = r.role_id
+                    WHERE rc.component_id = $3 AND r.organization_id = $4
+                )
+            )
+        ) as "approves!"
+        "#,
+        approver_id,
+        request.role_id,
+        request.component_id,
+        tenant.organization_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to check approvers of request")
+    .map_err(AppError::E500)?;
+    if !approves {
+        return Err(AppError::E403(anyhow::anyhow!(
+            "User {approver_id} cannot decide access request {access_request_id}"
+        )));
+    }
+
+    let decided = decide(
+        &mut transaction,
+        access_request_id,
+        AccessRequestStatus::Rejected,
+        None,
+        approver_id,
+        decision.comment,
+    )
+    .await?;
+    let event = AuditEvent::new("access_request.reject", "access_request", access_request_id)
+        .before(&request)
+        .after(&decided);
+    audit
+        .record(&mut transaction, tenant, event)
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(decided))
+}
+
+#[instrument(name = "Cancel access request", skip(tenant, audit, app_state))]
+pub async fn cancel_access_request(
+    tenant: Tenant,
+    audit: Audit,
+    Path(access_request_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    let user_id = tenant.user_id;
+    let mut transaction = begin_decision(&app_state).await?;
+    let request = lock_pending(&mut transaction, tenant.organization_id, access_request_id).await?;
+    if request.requester_id != user_id {
+        return Err(AppError::E403(anyhow::anyhow!(
+            "Only the requester can cancel a request"
+        )));
+    }
+
+    let decided = decide(
+        &mut transaction,
+        access_request_id,
+        AccessRequestStatus::Cancelled,
+        None,
+        user_id,
+        None,
+    )
+    .await?;
+    let event = AuditEvent::new("access_request.cancel", "access_request", access_request_id)
+        .before(&request)
+        .after(&decided);
+    audit
+        .record(&mut transaction, tenant, event)
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(decided))
+}
+
+/// Expires overdue requests first, so a request past its deadline is never
+/// decided.
+async fn begin_decision(app_state: &AppState) -> Result<Transaction<'static, Postgres>, AppError> {
+    expire_stale_requests(&app_state.pool)
+        .await
+        .map_err(AppError::E500)?;
+
+    app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)
+}
+
+/// Locks the request until the decision is committed, `409` unless pending.
+async fn lock_pending(
+    transaction: &mut Transaction<'_, Postgres>,
+    organization_id: uuid::Uuid,
+    access_request_id: uuid::Uuid,
+) -> Result<AccessRequest, AppError> {
+    let request = sqlx::query_as!(
+        AccessRequest,
+        r#"
+        SELECT access_request_id, requester_id, role_id, component_id, reason, valid_until,
+            status as "status: _", granted_role_id, decided_by, decided_at, decision_comment,
+            created_at, expires_at
+        FROM access_requests
+        WHERE access_request_id = $1 AND organization_id = $2
+        FOR UPDATE
+        "#,
+        access_request_id,
+        organization_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to fetch access request")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| {
+        AppError::E404(anyhow::anyhow!(
+            "Access request {access_request_id} not found"
+        ))
+    })?;
+
+    if request.status != AccessRequestStatus::Pending {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Access request {access_request_id} is already {:?}",
+            request.status
+        )));
+    }
+
+    Ok(request)
+}
+
+async fn decide(
+    transaction: &mut Transaction<'_, Postgres>,
+    access_request_id: uuid::Uuid,
+    status: AccessRequestStatus,
+    granted_role_id: Option<uuid::Uuid>,
+    decided_by: uuid::Uuid,
+    comment: Option<String>,
+) -> Result<AccessRequest, AppError> {
+    sqlx::query_as!(
+        AccessRequest,
+        r#"
+        UPDATE access_requests
+        SET status = $2, granted_role_id = $3, decided_by = $4, decided_at = now(),
+            decision_comment = $5
+        WHERE access_request_id = $1
+        RETURNING access_request_id, requester_id, role_id, component_id, reason, valid_until,
+            status as "status: _", granted_role_id, decided_by, decided_at, decision_comment,
+            created_at, expires_at
+        "#,
+        access_request_id,
+        status as AccessRequestStatus,
+        granted_role_id,
+        decided_by,
+        comment
+    )
+    .fetch_one(&mut **transaction)
+    .await
+    .context("Failed to record the decision")
+    .map_err(AppError::E500)
+}
@@ -0,0 +1,87 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::{Filter, ListRequest, ListResponse, Pagination};
+use crate::rbac_demo::rbac::access_requests::models::{AccessRequest, AccessRequestFilter};
+use crate::utils::db;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use serde_qs::axum::QsQuery;
+use sqlx::QueryBuilder;
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(skip_all)]
+pub async fn list_access_requests(
+    tenant: Tenant,
+    QsQuery(request): QsQuery<ListRequest<AccessRequestFilter>>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<ListResponse<AccessRequest>>, AppError> {
+    let mut qb = QueryBuilder::new(
+        r#"
+        SELECT access_request_id, requester_id, role_id, component_id, reason, valid_until,
+            status, granted_role_id, decided_by, decided_at, decision_comment,
+            created_at, expires_at
+        FROM (SELECT * FROM access_requests WHERE organization_id = "#,
+    );
+    qb.push_bind(tenant.organization_id).push(") as scoped");
+    if let Some(filter) = &request.filter {
+        Filter::to_query(&mut qb, filter);
+    }
+    qb.push(" ORDER BY created_at DESC");
+    Pagination::to_query(request.current_page, request.page_size, &mut qb);
+
+    let access_requests = qb
+        .build_query_as::<AccessRequest>()
+        .fetch_all(&app_state.pool)
+        .await
+        .context("Failed to fetch access requests from db")
+        .map_err(AppError::E500)?;
+
+    let total = db::count_in_tenant(
+        "access_requests",
+        tenant.organization_id,
+        request.filter,
+        &app_state.pool,
+    )
+    .await
+    .context("Failed to fetch access requests from db")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(ListResponse {
+        results: access_requests,
+        total: total as u64,
+        page: request.current_page,
+    }))
+}
+
+#[instrument(skip_all)]
+pub async fn get_access_request(
+    tenant: Tenant,
+    Path(access_request_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<AccessRequest>, AppError> {
+    let access_request = sqlx::query_as!(
+        AccessRequest,
+        r#"
+        SELECT access_request_id, requester_id, role_id, component_id, reason, valid_until,
+            status as "status: _", granted_role_id, decided_by, decided_at, decision_comment,
+            created_at, expires_at
+        FROM access_requests
+        WHERE access_request_id = $1 AND organization_id = $2
+        "#,
+        access_request_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&app_state.pool)
+    .await
+    .context("Failed to fetch access request")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| {
+        AppError::E404(anyhow::anyhow!(
+            "Access request {access_request_id} not found"
+        ))
+    })?;
+
+    Ok(Json(access_request))
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// Lifecycle of an access request. Only `Pending` requests can change, every
+/// other state is final.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum AccessRequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+    Cancelled,
+}
+
+/// A user asking to be granted a role, or a component through one of the
+/// roles bound to it.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct AccessRequest {
+    pub access_request_id: uuid::Uuid,
+    pub requester_id: uuid::Uuid,
+    pub role_id: Option<uuid::Uuid>,
+    pub component_id: Option<uuid::Uuid>,
+    pub reason: String,
+    /// End of the grant created on approval, if it should not be permanent.
+    pub valid_until: Option<DateTime<Utc>>,
+    pub status: AccessRequestStatus,
+    pub granted_role_id: Option<uuid::Uuid>,
+    pub decided_by: Option<uuid::Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Pending requests expire at this moment.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Exactly one of `role_id` and `component_id` must be given.
+#[derive(Deserialize, Debug, Validate)]
+pub struct CreateAccessRequest {
+    pub role_id: Option<uuid::Uuid>,
+    pub component_id: Option<uuid::Uuid>,
+    #[validate(length(min = 1))]
+    pub reason: String,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Decision {
+    /// Role to grant, required when approving a component request.
+    pub role_id: Option<uuid::Uuid>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccessRequestFilter {
+    pub status: Option<AccessRequestStatus>,
+}
@@ -0,0 +1,128 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::access_requests::models::{AccessRequest, CreateAccessRequest};
+use crate::utils::db;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+#[instrument(name = "Request access", skip(tenant, audit, app_state))]
+pub async fn create_access_request(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<CreateAccessRequest>,
+) -> Result<Json<AccessRequest>, AppError> {
+    let requester_id = tenant.user_id;
+
+    request
+        .validate()
+        .context("Invalid access request")
+        .map_err(AppError::E400)?;
+    if request.role_id.is_some() == request.component_id.is_some() {
+        return Err(AppError::E400(anyhow::anyhow!(
+            "Exactly one of role_id and component_id is required"
+        )));
+    }
+    if request.valid_until.is_some_and(|until| until <= Utc::now()) {
+        return Err(AppError::E400(anyhow::anyhow!(
+            "valid_until must be in the future"
+        )));
+    }
+
+    if let Some(role_id) = request.role_id {
+        let role = sqlx::query!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM users_roles
+                WHERE user_id = $2 AND role_id = $1
+                    AND project_id IS NULL
+                    AND valid_from <= now()
+                    AND (valid_until IS NULL OR valid_until > now())
+            ) as "held!"
+            FROM roles WHERE role_id = $1 AND organization_id = $3
+            "#,
+            role_id,
+            requester_id,
+            tenant.organization_id
+        )
+        .fetch_optional(&app_state.pool)
+        .await
+        .context("Failed to check the requested role")
+        .map_err(AppError::E500)?
+        .ok_or_else(|| AppError::E404(anyhow::anyhow!("Role {role_id} not found")))?;
+        if role.held {
+            return Err(AppError::E409(anyhow::anyhow!(
+                "Role {role_id} is already granted"
+            )));
+        }
+    }
+    if let Some(component_id) = request.component_id {
+        sqlx::query_scalar!(
+            "SELECT 1 FROM components WHERE component_id = $1",
+            component_id
+        )
+        .fetch_optional(&app_state.pool)
+        .await
+        .context("Failed to check the requested component")
+        .map_err(AppError::E500)?
+        .ok_or_else(|| AppError::E404(anyhow::anyhow!("Component {component_id} not found")))?;
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let access_request = sqlx::query_as!(
+        AccessRequest,
+        r#"
+        INSERT INTO access_requests (
+            requester_id, role_id, component_id, reason, valid_until, organization_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING access_request_id, requester_id, role_id, component_id, reason, valid_until,
+            status as "status: _", granted_role_id, decided_by, decided_at, decision_comment,
+            created_at, expires_at
+        "#,
+        requester_id,
+        request.role_id,
+        request.component_id,
+        request.reason,
+        request.valid_until,
+        tenant.organization_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(|e| {
+        if db::is_unique_violation(&e) {
+            AppError::E409(anyhow::anyhow!(e).context("A pending request for this access exists"))
+        } else {
+            AppError::E500(anyhow::anyhow!(e).context("Failed to create access request"))
+        }
+    })?;
+
+    let event = AuditEvent::new(
+        "access_request.create",
+        "access_request",
+        access_request.access_request_id,
+    );
+    audit
+        .record(&mut transaction, tenant, event.after(&access_request))
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(access_request))
+}
@@ -0,0 +1,61 @@
+use crate::app_states::AppState;
+use crate::authorization::{Attributes, Target, Tenant, Value};
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::authz::models::{AuthzDecision, AuthzQuery};
+use crate::rbac_demo::rbac::users::update_roles::check_user_exists;
+use axum::extract::{Json, State};
+use chrono::Utc;
+use serde_qs::axum::QsQuery;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Evaluates a request with the same matching and conditions the
+/// enforcement uses.
+#[instrument(name = "Check authorization", skip(tenant, app_state))]
+pub async fn check(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    QsQuery(query): QsQuery<AuthzQuery>,
+) -> Result<Json<AuthzDecision>, AppError> {
+    let user_id = query.user_id.unwrap_or(tenant.user_id);
+    if !check_user_exists(&app_state.pool, tenant.organization_id, user_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!("User {user_id} not found")));
+    }
+
+    let mut attributes = Attributes::for_user(user_id, tenant.organization_id, Utc::now());
+    for (path, value) in query.attributes {
+        if !(path.starts_with("resource.") || path.starts_with("request.")) {
+            return Err(AppError::E400(anyhow::anyhow!(
+                "Only resource and request attributes can be given, not {path}"
+            )));
+        }
+        attributes.insert(&path, Value::String(value));
+    }
+
+    let permissions = app_state
+        .permission_cache
+        .effective_permissions(&app_state.pool, user_id, tenant.organization_id)
+        .await
+        .map_err(AppError::E500)?;
+    let target = Target::new(&query.resource, &query.action, &query.scope);
+    let matched = permissions
+        .authorizing(&target, query.project_id, &attributes)
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    let unmet = permissions
+        .matching(&target, query.project_id)
+        .into_iter()
+        .filter(|p| !matched.iter().any(|m| m.permission_id == p.permission_id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Ok(Json(AuthzDecision {
+        allowed: !matched.is_empty(),
+        matched,
+        unmet,
+    }))
+}
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rbac_demo::rbac::permissions::models::Permission;
+
+/// Query of `GET /authz/check`: may `user_id`, the caller by default, do
+/// `action` on `resource` within `scope`, optionally on one project?
+/// `attributes[resource.owner_id]=...` and `attributes[request.*]=...` feed
+/// the conditions of the permissions, `user.*` and `time.*` are set by the
+/// server.
+#[derive(Debug, Deserialize)]
+pub struct AuthzQuery {
+    pub user_id: Option<uuid::Uuid>,
+    pub resource: String,
+    pub action: String,
+    pub scope: String,
+    pub project_id: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthzDecision {
+    pub allowed: bool,
+    /// The granted permissions allowing the request.
+    pub matched: Vec<Permission>,
+    /// Granted permissions covering the request whose conditions do not
+    /// hold.
+    pub unmet: Vec<Permission>,
+}
@@ -0,0 +1,6 @@
+pub mod audit;
+pub mod deprecate;
+pub mod models;
+pub mod purge;
+pub mod report;
+pub mod sync;
@@ -0,0 +1,85 @@
+use crate::rbac_demo::rbac::cleanup::models::{CleanupAuditEntry, CleanupEvent, CleanupKind};
+use crate::rbac_demo::rbac::permissions::models::Permission;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+
+/// What a cleanup step did to one component or permission.
+pub struct AuditRecord<'a> {
+    pub event: CleanupEvent,
+    pub kind: CleanupKind,
+    pub object_id: uuid::Uuid,
+    pub name: &'a str,
+    pub role_ids: &'a [uuid::Uuid],
+    pub purge_after: Option<DateTime<Utc>>,
+    pub actor_id: uuid::Uuid,
+}
+
+pub async fn record(
+    connection: &mut PgConnection,
+    record: AuditRecord<'_>,
+) -> Result<CleanupAuditEntry, anyhow::Error> {
+    sqlx::query_as!(
+        CleanupAuditEntry,
+        r#"
+        INSERT INTO rbac_cleanup_audit (event, kind, object_id, name, role_ids, purge_after, actor_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING audit_id, event as "event: CleanupEvent", kind as "kind: CleanupKind",
+            object_id, name, role_ids, purge_after, actor_id, occurred_at
+        "#,
+        record.event as CleanupEvent,
+        record.kind as CleanupKind,
+        record.object_id,
+        record.name,
+        record.role_ids,
+        record.purge_after,
+        record.actor_id
+    )
+    .fetch_one(connection)
+    .await
+    .context("Failed to record cleanup audit entry")
+}
+
+/// Roles of every organization holding the component.
+pub async fn component_holders(
+    connection: &mut PgConnection,
+    component_id: uuid::Uuid,
+) -> Result<Vec<uuid::Uuid>, anyhow::Error> {
+    sqlx::query_scalar!(
+        "SELECT role_id FROM roles_components WHERE component_id = $1 ORDER BY role_id",
+        component_id
+    )
+    .fetch_all(connection)
+    .await
+    .context("Failed to fetch roles of component")
+}
+
+/// Roles of every organization holding the permission.
+pub async fn permission_holders(
+    connection: &mut PgConnection,
+    permission_id: uuid::Uuid,
+) -> Result<Vec<uuid::Uuid>, anyhow::Error> {
+    sqlx::query_scalar!(
+        "SELECT role_id FROM roles_permissions WHERE permission_id = $1 ORDER BY role_id",
+        permission_id
+    )
+    .fetch_all(connection)
+    .await
+    .context("Failed to fetch roles of permission")
+}
+
+/// The name a permission is recorded under, `resource:action:scope` followed
+/// by its condition if any.
+pub fn permission_name(permission: &Permission) -> String {
+    let Permission {
+        resource,
+        action,
+        scope,
+        condition,
+        ..
+    } = permission;
+    match condition {
+        Some(condition) => format!("{resource}:{action}:{scope} if {condition}"),
+        None => format!("{resource}:{action}:{scope}"),
+    }
+}
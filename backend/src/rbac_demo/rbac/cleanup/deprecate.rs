@@ -0,0 +1,136 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::SystemAdmin;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::cleanup::audit::{
+    AuditRecord, component_holders, permission_holders, permission_name, record,
+};
+use crate::rbac_demo::rbac::cleanup::models::{
+    CleanupAuditEntry, CleanupEvent, CleanupKind, DeprecateOrphans,
+};
+use crate::rbac_demo::rbac::cleanup::report::find_orphans;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// First phase of the cleanup: orphans not deprecated by it yet are
+/// deprecated, and become purgeable once the grace period is over. Roles
+/// keep them until the purge.
+#[instrument(name = "Deprecate orphans", skip(admin, audit, app_state))]
+pub async fn deprecate_orphans(
+    admin: SystemAdmin,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(body): Json<DeprecateOrphans>,
+) -> Result<Json<Vec<CleanupAuditEntry>>, AppError> {
+    body.validate()
+        .context("Invalid grace period")
+        .map_err(AppError::E400)?;
+    let days = i32::try_from(body.grace_period_days)
+        .context("Invalid grace period")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+    let orphans = find_orphans(&mut transaction)
+        .await
+        .map_err(AppError::E500)?;
+
+    let mut entries = Vec::new();
+    for component in orphans.components {
+        if component.purge_after.is_some() {
+            continue;
+        }
+        let purge_after = sqlx::query_scalar!(
+            r#"
+            UPDATE components
+            SET deprecated = TRUE, purge_after = now() + make_interval(days => $2)
+            WHERE component_id = $1
+            RETURNING purge_after as "purge_after!"
+            "#,
+            component.component_id,
+            days
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .context("Failed to deprecate component")
+        .map_err(AppError::E500)?;
+        let role_ids = component_holders(&mut transaction, component.component_id)
+            .await
+            .map_err(AppError::E500)?;
+        let entry = record(
+            &mut transaction,
+            AuditRecord {
+                event: CleanupEvent::Deprecated,
+                kind: CleanupKind::Component,
+                object_id: component.component_id,
+                name: &component.code,
+                role_ids: &role_ids,
+                purge_after: Some(purge_after),
+                actor_id: admin.user_id,
+            },
+        )
+        .await
+        .map_err(AppError::E500)?;
+        entries.push(entry);
+    }
+
+    for (permission, purge_after) in orphans.permissions {
+        if purge_after.is_some() {
+            continue;
+        }
+        let purge_after = sqlx::query_scalar!(
+            r#"
+            UPDATE permissions
+            SET deprecated = TRUE, purge_after = now() + make_interval(days => $2)
+            WHERE permission_id = $1
+            RETURNING purge_after as "purge_after!"
+            "#,
+            permission.permission_id,
+            days
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .context("Failed to deprecate permission")
+        .map_err(AppError::E500)?;
+        let role_ids = permission_holders(&mut transaction, permission.permission_id)
+            .await
+            .map_err(AppError::E500)?;
+        let entry = record(
+            &mut transaction,
+            AuditRecord {
+                event: CleanupEvent::Deprecated,
+                kind: CleanupKind::Permission,
+                object_id: permission.permission_id,
+                name: &permission_name(&permission),
+                role_ids: &role_ids,
+                purge_after: Some(purge_after),
+                actor_id: admin.user_id,
+            },
+        )
+        .await
+        .map_err(AppError::E500)?;
+        entries.push(entry);
+    }
+
+    if !entries.is_empty() {
+        let event = AuditEvent::untargeted("registry.cleanup.deprecate", "registry");
+        audit
+            .record(&mut transaction, admin, event.after(&entries))
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(entries))
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::authorization::{validate_action, validate_resource};
+use crate::rbac_demo::rbac::permissions::models::Permission;
+
+/// Body of `POST /rbac/registry/sync`: the whole frontend registry. Every
+/// component missing from it becomes an orphan.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct Registry {
+    #[validate(nested)]
+    pub components: Vec<RegistryComponent>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct RegistryComponent {
+    #[validate(length(min = 1))]
+    pub code: String,
+    #[validate(nested)]
+    #[serde(default)]
+    pub permissions: Vec<RegistryPermission>,
+}
+
+/// A permission a component needs, created if it does not exist yet.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct RegistryPermission {
+    #[validate(custom(function = validate_resource))]
+    pub resource: String,
+    #[validate(custom(function = validate_action))]
+    pub action: String,
+    #[validate(custom(function = validate_action))]
+    pub scope: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RegistrySync {
+    pub sync_id: uuid::Uuid,
+    pub synced_at: DateTime<Utc>,
+    pub components: usize,
+}
+
+/// Response of `GET /rbac/cleanup/report`. Orphaned components are those
+/// missing from the latest registry sync, none before the first sync.
+/// Orphaned permissions are needed neither by a route nor by a component of
+/// that registry. Roles are those of the caller's organization.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OrphanReport {
+    pub registry_synced_at: Option<DateTime<Utc>>,
+    pub components: Vec<OrphanComponent>,
+    pub permissions: Vec<OrphanPermission>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OrphanComponent {
+    pub component_id: uuid::Uuid,
+    pub code: String,
+    pub deprecated: bool,
+    pub purge_after: Option<DateTime<Utc>>,
+    pub roles: Vec<RoleRef>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OrphanPermission {
+    pub permission: Permission,
+    pub purge_after: Option<DateTime<Utc>>,
+    pub roles: Vec<RoleRef>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone, PartialEq)]
+pub struct RoleRef {
+    pub role_id: uuid::Uuid,
+    pub name: String,
+}
+
+/// Body of `POST /rbac/cleanup/deprecate`. The grace period is at most ten
+/// years.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct DeprecateOrphans {
+    #[serde(default = "default_grace_period_days")]
+    #[validate(range(max = 3650))]
+    pub grace_period_days: u32,
+}
+
+fn default_grace_period_days() -> u32 {
+    14
+}
+
+#[derive(Debug, Deserialize, Serialize, sqlx::Type, Clone, Copy, PartialEq)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupEvent {
+    Deprecated,
+    Purged,
+}
+
+#[derive(Debug, Deserialize, Serialize, sqlx::Type, Clone, Copy, PartialEq)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupKind {
+    Component,
+    Permission,
+}
+
+/// A row of `rbac_cleanup_audit`, what the cleanup endpoints return.
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct CleanupAuditEntry {
+    pub audit_id: uuid::Uuid,
+    pub event: CleanupEvent,
+    pub kind: CleanupKind,
+    pub object_id: uuid::Uuid,
+    pub name: String,
+    pub role_ids: Vec<uuid::Uuid>,
+    pub purge_after: Option<DateTime<Utc>>,
+    pub actor_id: uuid::Uuid,
+    pub occurred_at: DateTime<Utc>,
+}
@@ -0,0 +1,153 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::SystemAdmin;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::cleanup::audit::{AuditRecord, permission_name, record};
+use crate::rbac_demo::rbac::cleanup::models::{CleanupAuditEntry, CleanupEvent, CleanupKind};
+use crate::rbac_demo::rbac::cleanup::report::find_orphans;
+use anyhow::Context;
+use axum::Json;
+use axum::extract::State;
+use chrono::Utc;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Second phase of the cleanup: orphans whose grace period is over are
+/// removed from the roles holding them, then deleted. Each role affected
+/// gets one new version. Components some access request refers to are kept
+/// until the request history no longer needs them.
+#[instrument(name = "Purge orphans", skip_all)]
+pub async fn purge_orphans(
+    admin: SystemAdmin,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CleanupAuditEntry>>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+    let orphans = find_orphans(&mut transaction)
+        .await
+        .map_err(AppError::E500)?;
+    let now = Utc::now();
+
+    let mut entries = Vec::new();
+    let mut affected_roles = BTreeSet::new();
+    for component in orphans.components {
+        if component.purge_after.is_none_or(|at| at > now) {
+            continue;
+        }
+        let requested = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM access_requests WHERE component_id = $1) as "exists!""#,
+            component.component_id
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .context("Failed to check access requests")
+        .map_err(AppError::E500)?;
+        if requested {
+            continue;
+        }
+
+        let role_ids = sqlx::query_scalar!(
+            "DELETE FROM roles_components WHERE component_id = $1 RETURNING role_id",
+            component.component_id
+        )
+        .fetch_all(&mut *transaction)
+        .await
+        .context("Failed to remove component from roles")
+        .map_err(AppError::E500)?;
+        affected_roles.extend(role_ids.iter().copied());
+        sqlx::query!(
+            "DELETE FROM components WHERE component_id = $1",
+            component.component_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to delete component")
+        .map_err(AppError::E500)?;
+
+        let entry = record(
+            &mut transaction,
+            AuditRecord {
+                event: CleanupEvent::Purged,
+                kind: CleanupKind::Component,
+                object_id: component.component_id,
+                name: &component.code,
+                role_ids: &role_ids,
+                purge_after: component.purge_after,
+                actor_id: admin.user_id,
+            },
+        )
+        .await
+        .map_err(AppError::E500)?;
+        entries.push(entry);
+    }
+
+    for (permission, purge_after) in orphans.permissions {
+        if purge_after.is_none_or(|at| at > now) {
+            continue;
+        }
+        let role_ids = sqlx::query_scalar!(
+            "DELETE FROM roles_permissions WHERE permission_id = $1 RETURNING role_id",
+            permission.permission_id
+        )
+        .fetch_all(&mut *transaction)
+        .await
+        .context("Failed to remove permission from roles")
+        .map_err(AppError::E500)?;
+        affected_roles.extend(role_ids.iter().copied());
+        sqlx::query!(
+            "DELETE FROM permissions WHERE permission_id = $1",
+            permission.permission_id
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to delete permission")
+        .map_err(AppError::E500)?;
+
+        let entry = record(
+            &mut transaction,
+            AuditRecord {
+                event: CleanupEvent::Purged,
+                kind: CleanupKind::Permission,
+                object_id: permission.permission_id,
+                name: &permission_name(&permission),
+                role_ids: &role_ids,
+                purge_after,
+                actor_id: admin.user_id,
+            },
+        )
+        .await
+        .map_err(AppError::E500)?;
+        entries.push(entry);
+    }
+
+    let affected_roles = affected_roles.into_iter().collect::<Vec<_>>();
+    sqlx::query!(
+        "UPDATE roles SET version = version + 1 WHERE role_id = ANY($1)",
+        &affected_roles
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to bump role versions")
+    .map_err(AppError::E500)?;
+
+    if !entries.is_empty() {
+        let event = AuditEvent::untargeted("registry.cleanup.purge", "registry");
+        audit
+            .record(&mut transaction, admin, event.after(&entries))
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(entries))
+}
@@ -0,0 +1,166 @@
+use crate::app_states::AppState;
+use crate::authorization::{Tenant, resource_matches};
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::cleanup::models::{
+    OrphanComponent, OrphanPermission, OrphanReport, RoleRef,
+};
+use crate::rbac_demo::rbac::permissions::models::Permission;
+use crate::rbac_demo::rbac::presets::ROUTE_PERMISSIONS;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(name = "Report orphaned components and permissions", skip_all)]
+pub async fn orphan_report(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<OrphanReport>, AppError> {
+    let mut connection = app_state
+        .pool
+        .acquire()
+        .await
+        .context("Failed to acquire connection")
+        .map_err(AppError::E500)?;
+    let orphans = find_orphans(&mut connection)
+        .await
+        .map_err(AppError::E500)?;
+
+    let mut components = Vec::new();
+    for component in orphans.components {
+        let roles = sqlx::query_as!(
+            RoleRef,
+            r#"
+            SELECT r.role_id, r.name FROM roles as r
+            JOIN roles_components as rc ON rc.role_id = r.role_id
+            WHERE rc.component_id = $1 AND r.organization_id = $2
+            ORDER BY r.name
+            "#,
+            component.component_id,
+            tenant.organization_id
+        )
+        .fetch_all(&mut *connection)
+        .await
+        .context("Failed to fetch roles of component")
+        .map_err(AppError::E500)?;
+        components.push(OrphanComponent {
+            component_id: component.component_id,
+            code: component.code,
+            deprecated: component.deprecated,
+            purge_after: component.purge_after,
+            roles,
+        });
+    }
+
+    let mut permissions = Vec::new();
+    for (permission, purge_after) in orphans.permissions {
+        let roles = sqlx::query_as!(
+            RoleRef,
+            r#"
+            SELECT r.role_id, r.name FROM roles as r
+            JOIN roles_permissions as rp ON rp.role_id = r.role_id
+            WHERE rp.permission_id = $1 AND r.organization_id = $2
+            ORDER BY r.name
+            "#,
+            permission.permission_id,
+            tenant.organization_id
+        )
+        .fetch_all(&mut *connection)
+        .await
+        .context("Failed to fetch roles of permission")
+        .map_err(AppError::E500)?;
+        permissions.push(OrphanPermission {
+            permission,
+            purge_after,
+            roles,
+        });
+    }
+
+    Ok(Json(OrphanReport {
+        registry_synced_at: orphans.synced_at,
+        components,
+        permissions,
+    }))
+}
+
+pub(super) struct Orphans {
+    pub synced_at: Option<DateTime<Utc>>,
+    pub components: Vec<ComponentRow>,
+    /// With their `purge_after`.
+    pub permissions: Vec<(Permission, Option<DateTime<Utc>>)>,
+}
+
+pub(super) struct ComponentRow {
+    pub component_id: uuid::Uuid,
+    pub code: String,
+    pub deprecated: bool,
+    pub purge_after: Option<DateTime<Utc>>,
+}
+
+/// Components and permissions the cleanup applies to, see
+/// [`OrphanReport`]. Permissions covering a route, e.g. `project:*`, are
+/// needed whatever their scope.
+pub(super) async fn find_orphans(connection: &mut PgConnection) -> Result<Orphans, anyhow::Error> {
+    let synced_at = sqlx::query_scalar!("SELECT max(synced_at) FROM registry_syncs")
+        .fetch_one(&mut *connection)
+        .await
+        .context("Failed to fetch latest registry sync")?;
+
+    let components = sqlx::query_as!(
+        ComponentRow,
+        r#"
+        SELECT component_id, code, deprecated, purge_after
+        FROM components
+        WHERE $1::timestamptz IS NOT NULL AND (synced_at IS NULL OR synced_at < $1)
+        ORDER BY code
+        "#,
+        synced_at
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to fetch orphaned components")?;
+
+    let candidates = sqlx::query!(
+        r#"
+        SELECT permission_id, resource, action, scope, deprecated, condition, purge_after
+        FROM permissions as p
+        WHERE NOT EXISTS (
+            SELECT 1 FROM components_permissions as cp WHERE cp.permission_id = p.permission_id
+        )
+        ORDER BY resource, action, scope, condition
+        "#
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to fetch permissions")?;
+    let permissions = candidates
+        .into_iter()
+        .map(|row| {
+            let permission = Permission {
+                permission_id: row.permission_id,
+                resource: row.resource,
+                action: row.action,
+                scope: row.scope,
+                deprecated: row.deprecated,
+                condition: row.condition,
+            };
+            (permission, row.purge_after)
+        })
+        .filter(|(permission, _)| !covers_a_route(permission))
+        .collect();
+
+    Ok(Orphans {
+        synced_at,
+        components,
+        permissions,
+    })
+}
+
+fn covers_a_route(permission: &Permission) -> bool {
+    ROUTE_PERMISSIONS.iter().any(|(resource, action)| {
+        resource_matches(&permission.resource, resource)
+            && (permission.action == "*" || permission.action == *action)
+    })
+}
@@ -0,0 +1,119 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::SystemAdmin;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::cleanup::models::{Registry, RegistrySync};
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// Records the registry as the latest one. Its components and the
+/// permissions they declare are created if needed, and taken back from the
+/// cleanup if it had deprecated them. Permissions deprecated by hand stay
+/// deprecated.
+#[instrument(
+    name = "Sync component registry",
+    skip(admin, audit, app_state, registry)
+)]
+pub async fn sync_registry(
+    admin: SystemAdmin,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(registry): Json<Registry>,
+) -> Result<Json<RegistrySync>, AppError> {
+    registry
+        .validate()
+        .context("Invalid registry")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let sync = sqlx::query!(
+        "INSERT INTO registry_syncs (synced_by) VALUES ($1) RETURNING sync_id, synced_at",
+        admin.user_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to record registry sync")
+    .map_err(AppError::E500)?;
+
+    let codes = registry
+        .components
+        .iter()
+        .map(|c| c.code.clone())
+        .collect::<Vec<_>>();
+    sqlx::query!(
+        r#"
+        INSERT INTO components (code, synced_at)
+        SELECT unnest($1::text[]), $2
+        ON CONFLICT (code) DO UPDATE
+        SET synced_at = excluded.synced_at, deprecated = FALSE, purge_after = NULL
+        "#,
+        &codes as &[String],
+        sync.synced_at
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to register components")
+    .map_err(AppError::E500)?;
+
+    sqlx::query!("DELETE FROM components_permissions")
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to clear component permissions")
+        .map_err(AppError::E500)?;
+
+    for component in &registry.components {
+        for permission in &component.permissions {
+            sqlx::query!(
+                r#"
+                WITH permission AS (
+                    INSERT INTO permissions (permission_id, resource, action, scope)
+                    VALUES (gen_random_uuid(), $2, $3, $4)
+                    ON CONFLICT ON CONSTRAINT uq_permission DO UPDATE
+                    SET deprecated = permissions.deprecated AND permissions.purge_after IS NULL,
+                        purge_after = NULL
+                    RETURNING permission_id
+                )
+                INSERT INTO components_permissions (component_id, permission_id)
+                SELECT c.component_id, p.permission_id
+                FROM components as c, permission as p
+                WHERE c.code = $1
+                ON CONFLICT DO NOTHING
+                "#,
+                component.code,
+                permission.resource,
+                permission.action,
+                permission.scope
+            )
+            .execute(&mut *transaction)
+            .await
+            .context("Failed to record component permissions")
+            .map_err(AppError::E500)?;
+        }
+    }
+
+    let event = AuditEvent::new("registry.sync", "registry_sync", sync.sync_id);
+    audit
+        .record(&mut transaction, admin, event.after(&registry))
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(RegistrySync {
+        sync_id: sync.sync_id,
+        synced_at: sync.synced_at,
+        components: registry.components.len(),
+    }))
+}
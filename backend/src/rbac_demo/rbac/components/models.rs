@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A UI feature the frontend registry knows by its `code`, e.g.
+/// `comp_member_list`. Roles carry components alongside their permissions so
+/// the configuration intent survives overlapping permission sets.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Component {
+    pub component_id: uuid::Uuid,
+    pub code: String,
+}
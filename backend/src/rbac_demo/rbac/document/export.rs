@@ -0,0 +1,152 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::document::models::{
+    Binding, DOCUMENT_VERSION, ExportQuery, PermissionKey, RbacDocument, RoleDocument,
+};
+use anyhow::Context;
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use sqlx::PgConnection;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(name = "Export RBAC configuration", skip(tenant, app_state))]
+pub async fn export_document(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let mut connection = app_state
+        .pool
+        .acquire()
+        .await
+        .context("Failed to acquire connection")
+        .map_err(AppError::E500)?;
+    let document = load_document(&mut connection, tenant.organization_id).await?;
+    let body = query.format.render(&document).map_err(AppError::E500)?;
+
+    Ok(([(header::CONTENT_TYPE, query.format.content_type())], body).into_response())
+}
+
+/// The current configuration of the organization. The catalog holds every
+/// component but only the permissions its roles use, as the conditions of
+/// other permissions may belong to other organizations. Roles are identified
+/// by name, so duplicate role names make the organization unexportable.
+#[instrument(skip(connection))]
+pub async fn load_document(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+) -> Result<RbacDocument, AppError> {
+    let components = sqlx::query_scalar!("SELECT code FROM components")
+        .fetch_all(&mut *connection)
+        .await
+        .context("Failed to load components")
+        .map_err(AppError::E500)?;
+
+    let roles = sqlx::query!(
+        "SELECT role_id, name, description FROM roles WHERE organization_id = $1",
+        organization_id
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to load roles")
+    .map_err(AppError::E500)?;
+
+    let mut by_id = BTreeMap::new();
+    let mut names = BTreeMap::new();
+    for role in roles {
+        if names.insert(role.name.clone(), role.role_id).is_some() {
+            return Err(AppError::E409(anyhow::anyhow!(
+                "Several roles are named {:?}, rename them before exporting",
+                role.name
+            )));
+        }
+        let document = RoleDocument {
+            name: role.name,
+            description: role.description,
+            permissions: Vec::new(),
+            components: Vec::new(),
+        };
+        by_id.insert(role.role_id, document);
+    }
+
+    let role_permissions = sqlx::query!(
+        r#"
+        SELECT rp.role_id, p.resource, p.action, p.scope, p.condition
+        FROM roles_permissions as rp
+        JOIN roles as r ON r.role_id = rp.role_id
+        JOIN permissions as p ON p.permission_id = rp.permission_id
+        WHERE r.organization_id = $1
+        "#,
+        organization_id
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to load role permissions")
+    .map_err(AppError::E500)?;
+    for row in role_permissions {
+        if let Some(role) = by_id.get_mut(&row.role_id) {
+            role.permissions.push(PermissionKey {
+                resource: row.resource,
+                action: row.action,
+                scope: row.scope,
+                condition: row.condition,
+            });
+        }
+    }
+
+    let role_components = sqlx::query!(
+        r#"
+        SELECT rc.role_id, c.code
+        FROM roles_components as rc
+        JOIN roles as r ON r.role_id = rc.role_id
+        JOIN components as c ON c.component_id = rc.component_id
+        WHERE r.organization_id = $1
+        "#,
+        organization_id
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to load role components")
+    .map_err(AppError::E500)?;
+    for row in role_components {
+        if let Some(role) = by_id.get_mut(&row.role_id) {
+            role.components.push(row.code);
+        }
+    }
+
+    let bindings = sqlx::query_as!(
+        Binding,
+        r#"
+        SELECT u.username as user, r.name as role, p.name as "project?"
+        FROM users_roles as ur
+        JOIN users as u ON u.user_id = ur.user_id
+        JOIN roles as r ON r.role_id = ur.role_id
+        LEFT JOIN projects as p ON p.project_id = ur.project_id
+        WHERE r.organization_id = $1 AND ur.valid_until IS NULL
+        "#,
+        organization_id
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to load role bindings")
+    .map_err(AppError::E500)?;
+
+    let permissions = by_id
+        .values()
+        .flat_map(|role| role.permissions.iter().cloned())
+        .collect();
+    let mut document = RbacDocument {
+        version: DOCUMENT_VERSION,
+        permissions,
+        components,
+        roles: by_id.into_values().collect(),
+        bindings,
+    };
+    document.normalize();
+
+    Ok(document)
+}
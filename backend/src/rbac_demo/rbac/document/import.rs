@@ -0,0 +1,662 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::{Tenant, is_system_admin};
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::document::export::load_document;
+use crate::rbac_demo::rbac::document::models::{
+    Binding, Change, ChangeKind, DocumentFormat, ImportPlan, ImportQuery, ObjectKind,
+    PermissionKey, RbacDocument, RoleDocument,
+};
+use crate::rbac_demo::rbac::sod_rules::check::violations_for_grant;
+use crate::rbac_demo::rbac::users::update_roles::grant_roles;
+use anyhow::Context;
+use axum::extract::{Json, Query, State};
+use axum::http::{HeaderMap, header};
+use chrono::Utc;
+use sqlx::PgConnection;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Brings the organization in line with the document, in a single
+/// transaction. JSON bodies are recognized by their content type, anything
+/// else is read as YAML. Only system admins may add permissions or
+/// components to the shared catalog. With `dry_run` every change is still applied, so
+/// that the plan is checked like a real import, and then rolled back.
+#[instrument(
+    name = "Import RBAC configuration",
+    skip(tenant, audit, app_state, body)
+)]
+pub async fn import_document(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<ImportPlan>, AppError> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"));
+    let format = if is_json {
+        DocumentFormat::Json
+    } else {
+        DocumentFormat::Yaml
+    };
+    let document = format
+        .parse(&body)
+        .context("Failed to parse RBAC document")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let changes = apply_document(&mut transaction, tenant.organization_id, document).await?;
+    let additions = changes
+        .iter()
+        .filter(|c| {
+            c.change == ChangeKind::Create
+                && matches!(c.kind, ObjectKind::Permission | ObjectKind::Component)
+        })
+        .map(|c| c.key.as_str())
+        .collect::<Vec<_>>();
+    if !additions.is_empty()
+        && !is_system_admin(&mut *transaction, tenant.user_id)
+            .await
+            .map_err(AppError::E500)?
+    {
+        return Err(AppError::E403(anyhow::anyhow!(
+            "Only system admins add to the shared catalog: {}",
+            additions.join(", ")
+        )));
+    }
+
+    let applied = !query.dry_run && !changes.is_empty();
+    if applied {
+        let event = AuditEvent::new("rbac.import", "organization", tenant.organization_id);
+        audit
+            .record(&mut transaction, tenant, event.after(&changes))
+            .await
+            .map_err(AppError::E500)?;
+        transaction.commit().await
+    } else {
+        transaction.rollback().await
+    }
+    .context("Failed to end transaction")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(ImportPlan { applied, changes }))
+}
+
+/// Diffs the document against the current configuration and applies the
+/// difference on `connection`, returning the changes in the order they were
+/// made. Callers decide whether to commit.
+#[instrument(skip(connection, desired))]
+pub async fn apply_document(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    mut desired: RbacDocument,
+) -> Result<Vec<Change>, AppError> {
+    desired.normalize();
+    desired
+        .validate()
+        .map_err(|e| AppError::E400(anyhow::anyhow!(e)))?;
+
+    let current = load_document(connection, organization_id).await?;
+    let mut plan = Plan::default();
+
+    for permission in missing(&desired.permissions, &current.permissions) {
+        if create_permission(connection, permission).await? {
+            plan.push(ChangeKind::Create, ObjectKind::Permission, permission);
+        }
+    }
+
+    let components = missing(&desired.components, &current.components).collect::<Vec<_>>();
+    sqlx::query!(
+        "INSERT INTO components (code) SELECT unnest($1::text[]) ON CONFLICT (code) DO NOTHING",
+        &components.iter().map(|c| c.to_string()).collect::<Vec<_>>() as &[String]
+    )
+    .execute(&mut *connection)
+    .await
+    .context("Failed to register components")
+    .map_err(AppError::E500)?;
+    for component in components {
+        plan.push(ChangeKind::Create, ObjectKind::Component, component);
+    }
+
+    let mut role_ids = role_ids(connection, organization_id).await?;
+    let existing = current
+        .roles
+        .iter()
+        .map(|role| (role.name.as_str(), role))
+        .collect::<HashMap<_, _>>();
+    let empty = RoleDocument {
+        name: String::new(),
+        description: String::new(),
+        permissions: Vec::new(),
+        components: Vec::new(),
+    };
+    for role in &desired.roles {
+        let old = match existing.get(role.name.as_str()) {
+            Some(old) => *old,
+            None => {
+                let role_id = sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO roles (role_id, name, description, organization_id)
+                    VALUES (gen_random_uuid(), $1, $2, $3)
+                    RETURNING role_id
+                    "#,
+                    role.name,
+                    role.description,
+                    organization_id
+                )
+                .fetch_one(&mut *connection)
+                .await
+                .context("Failed to create role")
+                .map_err(AppError::E500)?;
+                role_ids.insert(role.name.clone(), role_id);
+                plan.push(ChangeKind::Create, ObjectKind::Role, &role.name);
+                &empty
+            }
+        };
+        let role_id = role_ids[&role.name];
+        let before = plan.changes.len();
+
+        if existing.contains_key(role.name.as_str()) && old.description != role.description {
+            plan.push(ChangeKind::Update, ObjectKind::Role, &role.name);
+        }
+        for permission in missing(&role.permissions, &old.permissions) {
+            if !link_permission(connection, role_id, permission, true).await? {
+                return Err(AppError::E409(anyhow::anyhow!(
+                    "Permission {permission} is deprecated and cannot be granted to {}",
+                    role.name
+                )));
+            }
+            plan.push_link(
+                ChangeKind::Create,
+                ObjectKind::RolePermission,
+                role,
+                permission,
+            );
+        }
+        for permission in missing(&old.permissions, &role.permissions) {
+            link_permission(connection, role_id, permission, false).await?;
+            plan.push_link(
+                ChangeKind::Delete,
+                ObjectKind::RolePermission,
+                role,
+                permission,
+            );
+        }
+        for component in missing(&role.components, &old.components) {
+            link_component(connection, role_id, component, true).await?;
+            plan.push_link(
+                ChangeKind::Create,
+                ObjectKind::RoleComponent,
+                role,
+                component,
+            );
+        }
+        for component in missing(&old.components, &role.components) {
+            link_component(connection, role_id, component, false).await?;
+            plan.push_link(
+                ChangeKind::Delete,
+                ObjectKind::RoleComponent,
+                role,
+                component,
+            );
+        }
+
+        if existing.contains_key(role.name.as_str()) && plan.changes.len() > before {
+            sqlx::query!(
+                "UPDATE roles SET description = $2, version = version + 1 WHERE role_id = $1",
+                role_id,
+                role.description
+            )
+            .execute(&mut *connection)
+            .await
+            .context("Failed to update role")
+            .map_err(AppError::E500)?;
+        }
+    }
+
+    for binding in missing(&current.bindings, &desired.bindings) {
+        let (user_id, project_id) = resolve_binding(connection, organization_id, binding).await?;
+        sqlx::query!(
+            r#"
+            WITH revoked AS (
+                DELETE FROM users_roles
+                WHERE user_id = $1 AND role_id = $2
+                    AND project_id IS NOT DISTINCT FROM $3
+                    AND valid_until IS NULL
+                RETURNING user_id, role_id, project_id, valid_from, valid_until
+            )
+            INSERT INTO users_roles_audit
+                (user_id, role_id, project_id, valid_from, valid_until, event)
+            SELECT user_id, role_id, project_id, valid_from, valid_until, 'revoked'
+            FROM revoked
+            "#,
+            user_id,
+            role_ids[&binding.role],
+            project_id
+        )
+        .execute(&mut *connection)
+        .await
+        .context("Failed to revoke role")
+        .map_err(AppError::E500)?;
+        plan.push(ChangeKind::Delete, ObjectKind::Binding, binding);
+    }
+
+    for binding in missing(&desired.bindings, &current.bindings) {
+        let (user_id, project_id) = resolve_binding(connection, organization_id, binding).await?;
+        let role_id = role_ids[&binding.role];
+        let violations = violations_for_grant(connection, user_id, &[role_id])
+            .await
+            .map_err(AppError::E500)?;
+        if !violations.is_empty() {
+            let messages = violations
+                .into_iter()
+                .map(|v| v.message)
+                .collect::<Vec<_>>();
+            return Err(AppError::E409(anyhow::anyhow!(
+                "Binding {binding} breaks separation-of-duties rules: {}",
+                messages.join("; ")
+            )));
+        }
+        grant_roles(
+            &mut *connection,
+            user_id,
+            &[role_id],
+            project_id,
+            Utc::now(),
+            None,
+        )
+        .await
+        .map_err(AppError::E500)?;
+        plan.push(ChangeKind::Create, ObjectKind::Binding, binding);
+    }
+
+    let desired_roles = desired
+        .roles
+        .iter()
+        .map(|role| role.name.as_str())
+        .collect::<BTreeSet<_>>();
+    for role in &current.roles {
+        if !desired_roles.contains(role.name.as_str()) {
+            delete_role(connection, &role.name, role_ids[&role.name]).await?;
+            plan.push(ChangeKind::Delete, ObjectKind::Role, &role.name);
+        }
+    }
+
+    Ok(plan.changes)
+}
+
+/// Adds the permissions, components and roles of the document the catalog
+/// and the organization miss, never changing or deleting anything: roles
+/// already present by name are left as they are. Bindings are ignored. Unlike
+/// [`apply_document`], the rest of the configuration is not looked at.
+#[instrument(skip(connection, document))]
+pub async fn add_document(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    mut document: RbacDocument,
+) -> Result<Vec<Change>, AppError> {
+    document.normalize();
+    document
+        .validate()
+        .map_err(|e| AppError::E400(anyhow::anyhow!(e)))?;
+    let mut plan = Plan::default();
+
+    for permission in &document.permissions {
+        if create_permission(connection, permission).await? {
+            plan.push(ChangeKind::Create, ObjectKind::Permission, permission);
+        }
+    }
+
+    let components = sqlx::query_scalar!(
+        r#"
+        INSERT INTO components (code) SELECT unnest($1::text[])
+        ON CONFLICT (code) DO NOTHING
+        RETURNING code
+        "#,
+        &document.components
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to register components")
+    .map_err(AppError::E500)?;
+    for component in components {
+        plan.push(ChangeKind::Create, ObjectKind::Component, component);
+    }
+
+    let existing = role_ids(connection, organization_id).await?;
+    for role in &document.roles {
+        if existing.contains_key(&role.name) {
+            continue;
+        }
+        let role_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO roles (role_id, name, description, organization_id)
+            VALUES (gen_random_uuid(), $1, $2, $3)
+            RETURNING role_id
+            "#,
+            role.name,
+            role.description,
+            organization_id
+        )
+        .fetch_one(&mut *connection)
+        .await
+        .context("Failed to create role")
+        .map_err(AppError::E500)?;
+        plan.push(ChangeKind::Create, ObjectKind::Role, &role.name);
+
+        for permission in &role.permissions {
+            if !link_permission(connection, role_id, permission, true).await? {
+                tracing::warn!(%permission, role = %role.name, "Skipping deprecated permission");
+                continue;
+            }
+            plan.push_link(
+                ChangeKind::Create,
+                ObjectKind::RolePermission,
+                role,
+                permission,
+            );
+        }
+        for component in &role.components {
+            link_component(connection, role_id, component, true).await?;
+            plan.push_link(
+                ChangeKind::Create,
+                ObjectKind::RoleComponent,
+                role,
+                component,
+            );
+        }
+    }
+
+    Ok(plan.changes)
+}
+
+#[derive(Default)]
+struct Plan {
+    changes: Vec<Change>,
+}
+
+impl Plan {
+    fn push(&mut self, change: ChangeKind, kind: ObjectKind, key: impl ToString) {
+        self.changes.push(Change {
+            change,
+            kind,
+            key: key.to_string(),
+        });
+    }
+
+    fn push_link(
+        &mut self,
+        change: ChangeKind,
+        kind: ObjectKind,
+        role: &RoleDocument,
+        item: impl std::fmt::Display,
+    ) {
+        self.push(change, kind, format!("{} -> {item}", role.name));
+    }
+}
+
+/// Items of `wanted` missing from `present`, both being sorted.
+fn missing<'a, T: Ord>(wanted: &'a [T], present: &'a [T]) -> impl Iterator<Item = &'a T> {
+    wanted
+        .iter()
+        .filter(|item| present.binary_search(item).is_err())
+}
+
+/// Creates the permission unless the catalog has it, returning whether it
+/// did. Deprecated permissions stay deprecated, other organizations may rely
+/// on that.
+async fn create_permission(
+    connection: &mut PgConnection,
+    permission: &PermissionKey,
+) -> Result<bool, AppError> {
+    let created = sqlx::query_scalar!(
+        r#"
+        INSERT INTO permissions (permission_id, resource, action, scope, condition)
+        VALUES (gen_random_uuid(), $1, $2, $3, $4)
+        ON CONFLICT ON CONSTRAINT uq_permission DO NOTHING
+        RETURNING permission_id
+        "#,
+        permission.resource,
+        permission.action,
+        permission.scope,
+        permission.condition
+    )
+    .fetch_optional(connection)
+    .await
+    .context("Failed to create permission")
+    .map_err(AppError::E500)?;
+
+    Ok(created.is_some())
+}
+
+async fn role_ids(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+) -> Result<BTreeMap<String, uuid::Uuid>, AppError> {
+    let roles = sqlx::query!(
+        "SELECT name, role_id FROM roles WHERE organization_id = $1",
+        organization_id
+    )
+    .fetch_all(connection)
+    .await
+    .context("Failed to load roles")
+    .map_err(AppError::E500)?;
+
+    Ok(roles.into_iter().map(|r| (r.name, r.role_id)).collect())
+}
+
+/// Links or unlinks the permission, returning whether anything changed.
+/// Deprecated permissions are never linked.
+async fn link_permission(
+    connection: &mut PgConnection,
+    role_id: uuid::Uuid,
+    permission: &PermissionKey,
+    link: bool,
+) -> Result<bool, AppError> {
+    let query = if link {
+        sqlx::query!(
+            r#"
+            INSERT INTO roles_permissions (role_id, permission_id)
+            SELECT $1, permission_id FROM permissions
+            WHERE resource = $2 AND action = $3 AND scope = $4
+                AND condition IS NOT DISTINCT FROM $5 AND NOT deprecated
+            ON CONFLICT (role_id, permission_id) DO NOTHING
+            "#,
+            role_id,
+            permission.resource,
+            permission.action,
+            permission.scope,
+            permission.condition
+        )
+    } else {
+        sqlx::query!(
+            r#"
+            DELETE FROM roles_permissions
+            WHERE role_id = $1 AND permission_id IN (
+                SELECT permission_id FROM permissions
+                WHERE resource = $2 AND action = $3 AND scope = $4
+                    AND condition IS NOT DISTINCT FROM $5
+            )
+            "#,
+            role_id,
+            permission.resource,
+            permission.action,
+            permission.scope,
+            permission.condition
+        )
+    };
+    let result = query
+        .execute(connection)
+        .await
+        .context("Failed to change role permissions")
+        .map_err(AppError::E500)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+async fn link_component(
+    connection: &mut PgConnection,
+    role_id: uuid::Uuid,
+    code: &str,
+    link: bool,
+) -> Result<(), AppError> {
+    let query = if link {
+        sqlx::query!(
+            r#"
+            INSERT INTO roles_components (role_id, component_id)
+            SELECT $1, component_id FROM components WHERE code = $2
+            ON CONFLICT (role_id, component_id) DO NOTHING
+            "#,
+            role_id,
+            code
+        )
+    } else {
+        sqlx::query!(
+            r#"
+            DELETE FROM roles_components
+            WHERE role_id = $1
+                AND component_id IN (SELECT component_id FROM components WHERE code = $2)
+            "#,
+            role_id,
+            code
+        )
+    };
+    query
+        .execute(connection)
+        .await
+        .context("Failed to change role components")
+        .map_err(AppError::E500)?;
+
+    Ok(())
+}
+
+/// The user and project of a binding, `400` unless the user is a member of
+/// the organization and the project name designates exactly one of its
+/// projects.
+async fn resolve_binding(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    binding: &Binding,
+) -> Result<(uuid::Uuid, Option<uuid::Uuid>), AppError> {
+    let user_id = sqlx::query_scalar!(
+        r#"
+        SELECT u.user_id
+        FROM users as u
+        JOIN organizations_users as ou ON ou.user_id = u.user_id
+        WHERE u.username = $1 AND ou.organization_id = $2
+        "#,
+        binding.user,
+        organization_id
+    )
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to look up user")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| {
+        AppError::E400(anyhow::anyhow!(
+            "User {} is not a member of the organization",
+            binding.user
+        ))
+    })?;
+
+    let Some(project) = &binding.project else {
+        return Ok((user_id, None));
+    };
+    let projects = sqlx::query_scalar!(
+        "SELECT project_id FROM projects WHERE name = $1 AND organization_id = $2",
+        project,
+        organization_id
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to look up project")
+    .map_err(AppError::E500)?;
+    match projects.as_slice() {
+        [project_id] => Ok((user_id, Some(*project_id))),
+        [] => Err(AppError::E400(anyhow::anyhow!(
+            "Project {project} not found"
+        ))),
+        _ => Err(AppError::E400(anyhow::anyhow!(
+            "Several projects are named {project}"
+        ))),
+    }
+}
+
+/// Deletes the role with everything attached to it. System roles, and roles
+/// referenced by access requests which are kept for their history, are a
+/// `409`.
+async fn delete_role(
+    connection: &mut PgConnection,
+    name: &str,
+    role_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    let is_system = sqlx::query_scalar!("SELECT is_system FROM roles WHERE role_id = $1", role_id)
+        .fetch_one(&mut *connection)
+        .await
+        .context("Failed to fetch role")
+        .map_err(AppError::E500)?;
+    if is_system {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Role {name} is a system role and cannot be deleted"
+        )));
+    }
+
+    let requested = sqlx::query_scalar!(
+        "SELECT 1 FROM access_requests WHERE role_id = $1 OR granted_role_id = $1 LIMIT 1",
+        role_id
+    )
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to check access requests")
+    .map_err(AppError::E500)?;
+    if requested.is_some() {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Role {name} is referenced by access requests and cannot be deleted"
+        )));
+    }
+
+    sqlx::query!(
+        r#"
+        WITH revoked AS (
+            DELETE FROM users_roles WHERE role_id = $1
+            RETURNING user_id, role_id, project_id, valid_from, valid_until
+        ), audited AS (
+            INSERT INTO users_roles_audit
+                (user_id, role_id, project_id, valid_from, valid_until, event)
+            SELECT user_id, role_id, project_id, valid_from, valid_until, 'revoked'
+            FROM revoked
+        ), permissions AS (
+            DELETE FROM roles_permissions WHERE role_id = $1
+        ), components AS (
+            DELETE FROM roles_components WHERE role_id = $1
+        ), approvers AS (
+            DELETE FROM roles_approvers WHERE role_id = $1
+        )
+        DELETE FROM sod_rules WHERE role_id = $1 OR other_role_id = $1
+        "#,
+        role_id
+    )
+    .execute(&mut *connection)
+    .await
+    .context("Failed to detach role")
+    .map_err(AppError::E500)?;
+
+    sqlx::query!("DELETE FROM roles WHERE role_id = $1", role_id)
+        .execute(&mut *connection)
+        .await
+        .context("Failed to delete role")
+        .map_err(AppError::E500)?;
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::authorization::{validate_action, validate_condition, validate_resource};
+
+pub const DOCUMENT_VERSION: u32 = 1;
+
+/// The RBAC configuration of one organization, as exported by
+/// `GET /rbac/export` and applied by `POST /rbac/import`. Every list is
+/// sorted, so exporting the same state always produces the same document.
+///
+/// `permissions` and `components` are the shared catalog: importing creates
+/// the missing entries, for system admins only, but never deletes or
+/// reactivates any, as other organizations may use them. Exports only list the permissions the roles
+/// use. Roles and bindings of the organization missing from the document
+/// are deleted. Only permanent grants are bindings, grants with an end date
+/// are left alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RbacDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub permissions: Vec<PermissionKey>,
+    #[serde(default)]
+    pub components: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<RoleDocument>,
+    #[serde(default)]
+    pub bindings: Vec<Binding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PermissionKey {
+    pub resource: String,
+    pub action: String,
+    pub scope: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleDocument {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub permissions: Vec<PermissionKey>,
+    #[serde(default)]
+    pub components: Vec<String>,
+}
+
+/// A permanent grant of a role to a user, by username and role name,
+/// organization-wide or on the project of that name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Binding {
+    pub user: String,
+    pub role: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl DocumentFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Yaml => "application/yaml",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn render(&self, document: &RbacDocument) -> Result<String, anyhow::Error> {
+        let rendered = match self {
+            Self::Yaml => serde_yaml::to_string(document)?,
+            Self::Json => serde_json::to_string_pretty(document)? + "\n",
+        };
+        Ok(rendered)
+    }
+
+    pub fn parse(&self, input: &str) -> Result<RbacDocument, anyhow::Error> {
+        let document = match self {
+            Self::Yaml => serde_yaml::from_str(input)?,
+            Self::Json => serde_json::from_str(input)?,
+        };
+        Ok(document)
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: DocumentFormat,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Permission,
+    Component,
+    Role,
+    RolePermission,
+    RoleComponent,
+    Binding,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub change: ChangeKind,
+    pub kind: ObjectKind,
+    pub key: String,
+}
+
+/// Response of `POST /rbac/import`: the changes, in the order they are
+/// applied, and whether they were.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportPlan {
+    pub applied: bool,
+    pub changes: Vec<Change>,
+}
+
+impl fmt::Display for PermissionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.resource, self.action, self.scope)?;
+        if let Some(condition) = &self.condition {
+            write!(f, " if {condition}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.user, self.role)?;
+        if let Some(project) = &self.project {
+            write!(f, " on {project}")?;
+        }
+        Ok(())
+    }
+}
+
+impl RbacDocument {
+    /// Sorts and deduplicates every list.
+    pub fn normalize(&mut self) {
+        sort_dedup(&mut self.permissions);
+        sort_dedup(&mut self.components);
+        sort_dedup(&mut self.bindings);
+        for role in &mut self.roles {
+            sort_dedup(&mut role.permissions);
+            sort_dedup(&mut role.components);
+        }
+        self.roles.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Checks the document is self-contained: roles only use declared
+    /// permissions and components, bindings only declared roles.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version != DOCUMENT_VERSION {
+            return Err(format!(
+                "Unsupported version {}, expected {DOCUMENT_VERSION}",
+                self.version
+            ));
+        }
+        for permission in &self.permissions {
+            let valid = validate_resource(&permission.resource).is_ok()
+                && validate_action(&permission.action).is_ok()
+                && validate_action(&permission.scope).is_ok()
+                && permission
+                    .condition
+                    .as_deref()
+                    .is_none_or(|c| validate_condition(c).is_ok());
+            if !valid {
+                return Err(format!("Invalid permission {permission}"));
+            }
+        }
+        if let Some(component) = self.components.iter().find(|c| c.is_empty()) {
+            return Err(format!("Invalid component code {component:?}"));
+        }
+
+        let permissions = self.permissions.iter().collect::<HashSet<_>>();
+        let components = self.components.iter().collect::<HashSet<_>>();
+        let mut roles = HashSet::new();
+        for role in &self.roles {
+            if role.name.is_empty() || !roles.insert(role.name.as_str()) {
+                return Err(format!(
+                    "Role names must be unique and non-empty: {:?}",
+                    role.name
+                ));
+            }
+            if let Some(permission) = role.permissions.iter().find(|p| !permissions.contains(p)) {
+                return Err(format!(
+                    "Role {} uses undeclared permission {permission}",
+                    role.name
+                ));
+            }
+            if let Some(component) = role.components.iter().find(|c| !components.contains(c)) {
+                return Err(format!(
+                    "Role {} uses undeclared component {component}",
+                    role.name
+                ));
+            }
+        }
+        if let Some(binding) = self
+            .bindings
+            .iter()
+            .find(|b| !roles.contains(b.role.as_str()))
+        {
+            return Err(format!("Binding {binding} uses undeclared role"));
+        }
+
+        Ok(())
+    }
+}
+
+fn sort_dedup<T: Ord>(items: &mut Vec<T>) {
+    items.sort();
+    items.dedup();
+}
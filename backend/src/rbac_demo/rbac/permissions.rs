@@ -1,2 +1,5 @@
+pub mod delete;
 pub mod get;
 pub mod models;
+pub mod post;
+pub mod put;
@@ -0,0 +1,118 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::SystemAdmin;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::permissions::models::Permission;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Hard deletes a permission nobody references yet. Permissions still held by
+/// roles are only flagged as deprecated so existing grants keep working until
+/// the roles are cleaned up; the flagged row is returned with `200 OK`.
+#[instrument(name = "Delete a permission", skip(admin, audit, app_state))]
+pub async fn delete_permission(
+    admin: SystemAdmin,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(permission_id): Path<uuid::Uuid>,
+) -> Result<Response, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let referenced = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM roles_permissions WHERE permission_id = $1
+        ) as "referenced!"
+        "#,
+        permission_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to check if permission is referenced")
+    .map_err(AppError::E500)?;
+
+    let (response, event) = if referenced {
+        match deprecate_permission(&mut transaction, permission_id)
+            .await
+            .map_err(AppError::E500)?
+        {
+            Some(permission) => {
+                let event = AuditEvent::new("permission.deprecate", "permission", permission_id)
+                    .after(&permission);
+                (
+                    (StatusCode::OK, Json(permission)).into_response(),
+                    Some(event),
+                )
+            }
+            None => (StatusCode::NOT_FOUND.into_response(), None),
+        }
+    } else {
+        let deleted = sqlx::query_as!(
+            Permission,
+            r#"
+            DELETE FROM permissions
+            WHERE permission_id = $1
+            RETURNING permission_id, resource, action, scope, deprecated, condition
+            "#,
+            permission_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .context("Failed to delete permission")
+        .map_err(AppError::E500)?;
+
+        match deleted {
+            Some(permission) => {
+                let event = AuditEvent::new("permission.delete", "permission", permission_id)
+                    .before(&permission);
+                (StatusCode::NO_CONTENT.into_response(), Some(event))
+            }
+            None => (StatusCode::NOT_FOUND.into_response(), None),
+        }
+    };
+
+    if let Some(event) = event {
+        audit
+            .record(&mut transaction, admin, event)
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(response)
+}
+
+#[instrument(name = "Mark the permission as deprecated", skip_all)]
+async fn deprecate_permission(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    permission_id: uuid::Uuid,
+) -> Result<Option<Permission>, anyhow::Error> {
+    let permission = sqlx::query_as!(
+        Permission,
+        r#"
+        UPDATE permissions
+        SET deprecated = TRUE
+        WHERE permission_id = $1
+        RETURNING permission_id, resource, action, scope, deprecated, condition
+        "#,
+        permission_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await
+    .context("Failed to deprecate permission")?;
+
+    Ok(permission)
+}
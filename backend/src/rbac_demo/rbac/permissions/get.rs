@@ -1,31 +1,35 @@
 use crate::utils::db;
 use anyhow::Context;
-use axum::extract::{Json, Query, State};
+use axum::extract::{Json, Path, State};
 use serde::{Deserialize, Serialize};
 use serde_qs::axum::QsQuery;
-use sqlx::QueryBuilder;
+use sqlx::{PgPool, QueryBuilder};
 use std::sync::Arc;
 use tracing::instrument;
 
 use crate::app_states::AppState;
+use crate::authorization::Tenant;
 use crate::errors::AppError;
 use crate::models::{Filter, ListRequest, ListResponse, Pagination};
 use crate::rbac_demo::rbac::permissions::models::Permission;
+use crate::rbac_demo::rbac::roles::models::Role;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PermissionFilter {
     pub resource: Option<String>,
+    pub deprecated: Option<bool>,
 }
 
 #[axum::debug_handler]
 #[instrument(name = "get_permissions", skip_all)]
 pub async fn list_permissions(
+    _tenant: Tenant,
     QsQuery(request): QsQuery<ListRequest<PermissionFilter>>,
     State(app_state): State<Arc<AppState>>,
 ) -> Result<Json<ListResponse<Permission>>, AppError> {
     let mut qb = QueryBuilder::new(
         r#"
-        SELECT permission_id, resource, action, scope
+        SELECT permission_id, resource, action, scope, deprecated, condition
         FROM permissions
         "#,
     );
@@ -52,3 +56,53 @@ pub async fn list_permissions(
         page: request.current_page,
     }))
 }
+
+/// Permissions are shared by all organizations, only the roles of the
+/// current one are listed.
+#[instrument(skip_all)]
+pub async fn list_permission_roles(
+    tenant: Tenant,
+    Path(permission_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Role>>, AppError> {
+    if !check_permission_exists(&app_state.pool, permission_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!(
+            "Permission {permission_id} not found"
+        )));
+    }
+
+    let roles = sqlx::query_as!(
+        Role,
+        r#"SELECT r.role_id, r.name, r.description, r.version, r.is_system
+        FROM roles as r
+        JOIN roles_permissions as rp ON r.role_id = rp.role_id
+        WHERE rp.permission_id = $1 AND r.organization_id = $2"#,
+        permission_id,
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch roles of permission")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(roles))
+}
+
+#[instrument(skip_all)]
+async fn check_permission_exists(
+    pool: &PgPool,
+    permission_id: uuid::Uuid,
+) -> Result<bool, anyhow::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT 1 FROM permissions WHERE permission_id = $1",
+        permission_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to check if permission exists")?;
+
+    Ok(exists.is_some())
+}
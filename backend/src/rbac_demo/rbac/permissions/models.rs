@@ -1,4 +1,10 @@
+use serde::Deserialize;
 use sqlx::FromRow;
+use validator::Validate;
+
+use crate::authorization::{
+    Condition, ConditionError, validate_action, validate_condition, validate_resource,
+};
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, FromRow, Clone)]
 pub struct Permission {
@@ -6,4 +12,31 @@ pub struct Permission {
     pub resource: String,
     pub action: String,
     pub scope: String,
+    pub deprecated: bool,
+    /// See [`Condition`](crate::authorization::Condition), the permission
+    /// applies only where it holds.
+    pub condition: Option<String>,
+}
+
+impl Permission {
+    /// Conditions are validated on save, so stored ones parse.
+    pub fn parsed_condition(&self) -> Result<Option<Condition>, ConditionError> {
+        self.condition.as_deref().map(Condition::parse).transpose()
+    }
+}
+
+/// Body of both `POST /permissions` and `PUT /permissions/{id}`.
+/// `(resource, action, scope)` is unique across the table. Resources may be
+/// hierarchical (`project/tasks`) and any part may use `*` wildcards, see
+/// [`matches`](crate::authorization::matches).
+#[derive(Debug, Deserialize, Validate)]
+pub struct PermissionData {
+    #[validate(custom(function = validate_resource))]
+    pub resource: String,
+    #[validate(custom(function = validate_action))]
+    pub action: String,
+    #[validate(custom(function = validate_action))]
+    pub scope: String,
+    #[validate(custom(function = validate_condition))]
+    pub condition: Option<String>,
 }
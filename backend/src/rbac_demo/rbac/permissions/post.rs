@@ -0,0 +1,78 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::SystemAdmin;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::permissions::models::{Permission, PermissionData};
+use crate::utils::db;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+#[instrument(
+    name = "Create a new permission",
+    skip(admin, audit, app_state),
+    fields(
+        resource = request.resource,
+        action = request.action,
+        scope = request.scope
+    )
+)]
+pub async fn create_new_permission(
+    admin: SystemAdmin,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<PermissionData>,
+) -> Result<Json<Permission>, AppError> {
+    request
+        .validate()
+        .context("Invalid permission")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let permission = sqlx::query_as!(
+        Permission,
+        r#"
+        INSERT INTO permissions (permission_id, resource, action, scope, condition)
+        VALUES (gen_random_uuid(), $1, $2, $3, $4)
+        RETURNING permission_id, resource, action, scope, deprecated, condition
+        "#,
+        request.resource,
+        request.action,
+        request.scope,
+        request.condition,
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(|e| {
+        if db::is_unique_violation(&e) {
+            AppError::E409(anyhow::anyhow!(e).context("Permission already exists"))
+        } else {
+            AppError::E500(anyhow::anyhow!(e).context("Failed to create new permission"))
+        }
+    })?;
+
+    audit
+        .record(
+            &mut transaction,
+            admin,
+            AuditEvent::new("permission.create", "permission", permission.permission_id)
+                .after(&permission),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(permission))
+}
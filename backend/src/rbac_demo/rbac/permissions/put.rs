@@ -0,0 +1,90 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::SystemAdmin;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::permissions::models::{Permission, PermissionData};
+use crate::utils::db;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+#[instrument(name = "Update a permission", skip(admin, audit, app_state))]
+pub async fn update_permission(
+    admin: SystemAdmin,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(permission_id): Path<uuid::Uuid>,
+    Json(request): Json<PermissionData>,
+) -> Result<Json<Permission>, AppError> {
+    request
+        .validate()
+        .context("Invalid permission")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before = sqlx::query_as!(
+        Permission,
+        r#"
+        SELECT permission_id, resource, action, scope, deprecated, condition
+        FROM permissions
+        WHERE permission_id = $1
+        FOR UPDATE
+        "#,
+        permission_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to fetch permission")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Permission {permission_id} not found")))?;
+
+    let permission = sqlx::query_as!(
+        Permission,
+        r#"
+        UPDATE permissions
+        SET resource = $2, action = $3, scope = $4, condition = $5
+        WHERE permission_id = $1
+        RETURNING permission_id, resource, action, scope, deprecated, condition
+        "#,
+        permission_id,
+        request.resource,
+        request.action,
+        request.scope,
+        request.condition,
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(|e| {
+        if db::is_unique_violation(&e) {
+            AppError::E409(anyhow::anyhow!(e).context("Permission already exists"))
+        } else {
+            AppError::E500(anyhow::anyhow!(e).context("Failed to update permission"))
+        }
+    })?;
+
+    audit
+        .record(
+            &mut transaction,
+            admin,
+            AuditEvent::new("permission.update", "permission", permission_id)
+                .before(&before)
+                .after(&permission),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(permission))
+}
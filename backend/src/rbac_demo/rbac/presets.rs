@@ -0,0 +1,151 @@
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::document::import::add_document;
+use crate::rbac_demo::rbac::document::models::{
+    Change, DOCUMENT_VERSION, PermissionKey, RbacDocument, RoleDocument,
+};
+use anyhow::Context;
+use sqlx::PgConnection;
+use tracing::instrument;
+
+pub const ADMIN: &str = "Admin";
+pub const MANAGER: &str = "Manager";
+pub const VIEWER: &str = "Viewer";
+pub const EXTERNAL_AUDITOR: &str = "External Auditor";
+
+/// What the frontend binds to routes of the backend, as `(resource, action)`.
+/// The routes do not enforce them; cleanup keeps them off the orphan list.
+/// Admins hold all of it.
+pub const ROUTE_PERMISSIONS: &[(&str, &str)] = &[
+    ("member", "read"),
+    ("member", "create"),
+    ("member", "update"),
+    ("member", "delete"),
+    ("project", "read"),
+    ("project", "create"),
+    ("project", "delete"),
+    ("project/tasks", "archive"),
+    ("rbac", "read"),
+    ("rbac", "write"),
+];
+
+/// A preset role, its permissions given as `(resource, action)` in scope
+/// `*`. Components follow the frontend registry.
+struct Preset {
+    name: &'static str,
+    description: &'static str,
+    permissions: &'static [(&'static str, &'static str)],
+    components: &'static [&'static str],
+}
+
+/// The roles of docs/rbac_demo_requirements.md §2.1.
+const PRESETS: [Preset; 4] = [
+    Preset {
+        name: ADMIN,
+        description: "Manages members, projects and access control",
+        permissions: ROUTE_PERMISSIONS,
+        components: &[
+            "comp_member_list",
+            "comp_member_edit",
+            "comp_board_view",
+            "comp_task_archive",
+            "comp_project_edit",
+        ],
+    },
+    Preset {
+        name: MANAGER,
+        description: "Runs projects and archives their tasks",
+        permissions: &[
+            ("member", "read"),
+            ("project", "read"),
+            ("project/tasks", "archive"),
+        ],
+        components: &["comp_member_list", "comp_board_view", "comp_task_archive"],
+    },
+    Preset {
+        name: VIEWER,
+        description: "Reads members and projects",
+        permissions: &[("member", "read"), ("project", "read")],
+        components: &["comp_member_list", "comp_board_view"],
+    },
+    Preset {
+        name: EXTERNAL_AUDITOR,
+        description: "Reads everything, including the access control configuration",
+        permissions: &[("member", "read"), ("project", "read"), ("rbac", "read")],
+        components: &["comp_member_list", "comp_board_view"],
+    },
+];
+
+/// Creates the preset roles missing from the organization, with the
+/// permissions and components they need, and marks them as system roles.
+/// Presets that exist already, or roles of the same name, are adopted as
+/// they are, so edits made to them survive reseeding. Nothing else of the
+/// organization or the catalog is looked at.
+#[instrument(skip(connection))]
+pub async fn seed_presets(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+) -> Result<Vec<Change>, AppError> {
+    let mut document = RbacDocument {
+        version: DOCUMENT_VERSION,
+        permissions: Vec::new(),
+        components: Vec::new(),
+        roles: Vec::new(),
+        bindings: Vec::new(),
+    };
+    for preset in &PRESETS {
+        let permissions = preset
+            .permissions
+            .iter()
+            .map(|(resource, action)| PermissionKey {
+                resource: resource.to_string(),
+                action: action.to_string(),
+                scope: "*".to_string(),
+                condition: None,
+            })
+            .collect::<Vec<_>>();
+        let components = preset
+            .components
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+
+        document.permissions.extend(permissions.iter().cloned());
+        document.components.extend(components.iter().cloned());
+        document.roles.push(RoleDocument {
+            name: preset.name.to_string(),
+            description: preset.description.to_string(),
+            permissions,
+            components,
+        });
+    }
+
+    let changes = add_document(connection, organization_id, document).await?;
+
+    let names = PRESETS.map(|preset| preset.name.to_string());
+    sqlx::query!(
+        "UPDATE roles SET is_system = TRUE WHERE organization_id = $1 AND name = ANY($2)",
+        organization_id,
+        &names as &[String]
+    )
+    .execute(connection)
+    .await
+    .context("Failed to mark system roles")
+    .map_err(AppError::E500)?;
+
+    Ok(changes)
+}
+
+/// The id of the organization's Admin preset, once seeded.
+pub async fn admin_role_id(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+) -> Result<uuid::Uuid, anyhow::Error> {
+    sqlx::query_scalar!(
+        "SELECT role_id FROM roles WHERE organization_id = $1 AND name = $2 AND is_system",
+        organization_id,
+        ADMIN
+    )
+    .fetch_one(connection)
+    .await
+    .context("Failed to find the Admin role")
+}
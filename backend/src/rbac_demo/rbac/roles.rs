@@ -1,4 +1,8 @@
+pub mod compare;
 pub mod get;
+pub mod history;
 pub mod models;
 pub mod post;
+pub mod update_approvers;
+pub mod update_components;
 pub mod update_permissions;
@@ -0,0 +1,84 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::permissions::models::Permission;
+use crate::rbac_demo::rbac::roles::models::{CompareRoles, Role, RoleComparison, SetDifference};
+use anyhow::Context;
+use axum::extract::{Json, Query, State};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// What each of two roles holds that the other does not, e.g. what `a` =
+/// Manager grants beyond `b` = Viewer. Permissions are compared by identity,
+/// not by what they cover: a wildcard does not absorb the permissions it
+/// matches.
+#[instrument(name = "Compare roles", skip(tenant, app_state))]
+pub async fn compare_roles(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    Query(query): Query<CompareRoles>,
+) -> Result<Json<RoleComparison>, AppError> {
+    let (a, a_permissions, a_components) =
+        load_contents(&app_state.pool, tenant.organization_id, query.a).await?;
+    let (b, b_permissions, b_components) =
+        load_contents(&app_state.pool, tenant.organization_id, query.b).await?;
+
+    Ok(Json(RoleComparison {
+        a,
+        b,
+        permissions: SetDifference::between(&a_permissions, &b_permissions, |p| p.permission_id),
+        components: SetDifference::between(&a_components, &b_components, Clone::clone),
+    }))
+}
+
+/// The role with its current permissions and component codes, `404` unless
+/// it belongs to the organization.
+async fn load_contents(
+    pool: &PgPool,
+    organization_id: uuid::Uuid,
+    role_id: uuid::Uuid,
+) -> Result<(Role, Vec<Permission>, Vec<String>), AppError> {
+    let role = sqlx::query_as!(
+        Role,
+        r#"SELECT role_id, name, description, version, is_system
+        FROM roles
+        WHERE role_id = $1 AND organization_id = $2"#,
+        role_id,
+        organization_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch role")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Role {role_id} not found")))?;
+
+    let permissions = sqlx::query_as!(
+        Permission,
+        r#"SELECT p.permission_id, p.resource, p.action, p.scope, p.deprecated, p.condition
+        FROM permissions as p
+        JOIN roles_permissions as rp ON p.permission_id = rp.permission_id
+        WHERE rp.role_id = $1
+        ORDER BY p.resource, p.action, p.scope, p.condition"#,
+        role_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch role permissions")
+    .map_err(AppError::E500)?;
+
+    let components = sqlx::query_scalar!(
+        r#"SELECT c.code
+        FROM components as c
+        JOIN roles_components as rc ON c.component_id = rc.component_id
+        WHERE rc.role_id = $1
+        ORDER BY c.code"#,
+        role_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch role components")
+    .map_err(AppError::E500)?;
+
+    Ok((role, permissions, components))
+}
@@ -1,8 +1,10 @@
 use crate::app_states::AppState;
+use crate::authorization::Tenant;
 use crate::errors::AppError;
-use crate::models::{ListRequest, ListResponse, Pagination};
+use crate::models::{ETag, IfMatch, ListRequest, ListResponse, Pagination};
+use crate::rbac_demo::rbac::components::models::Component;
 use crate::rbac_demo::rbac::permissions::models::Permission;
-use crate::rbac_demo::rbac::roles::models::Role;
+use crate::rbac_demo::rbac::roles::models::{Approver, Role};
 use crate::utils::db;
 use anyhow::Context;
 use axum::extract::{Json, Path, State};
@@ -13,10 +15,12 @@ use tracing::instrument;
 
 #[instrument(skip_all)]
 pub async fn list_roles(
+    tenant: Tenant,
     QsQuery(request): QsQuery<ListRequest<()>>,
     State(app_state): State<Arc<AppState>>,
 ) -> Result<Json<ListResponse<Role>>, AppError> {
-    let mut qb = QueryBuilder::new("SELECT * FROM roles");
+    let mut qb = QueryBuilder::new("SELECT * FROM roles WHERE organization_id = ");
+    qb.push_bind(tenant.organization_id);
     Pagination::to_query(request.current_page, request.page_size, &mut qb);
 
     let roles = qb
@@ -26,7 +30,7 @@ pub async fn list_roles(
         .context("Failed to fetch roles")
         .map_err(AppError::E500)?;
 
-    let total = db::count("roles", &(), &app_state.pool)
+    let total = db::count_in_tenant("roles", tenant.organization_id, &(), &app_state.pool)
         .await
         .context("Failed to fetch roles count")
         .map_err(AppError::E500)?;
@@ -38,18 +42,46 @@ pub async fn list_roles(
     }))
 }
 
+/// The role's `version` doubles as its `ETag`; send it back in `If-Match`
+/// when editing the role's permissions or components.
+#[instrument(skip_all)]
+pub async fn get_role(
+    tenant: Tenant,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<(ETag, Json<Role>), AppError> {
+    let role = sqlx::query_as!(
+        Role,
+        r#"SELECT role_id, name, description, version, is_system
+        FROM roles
+        WHERE role_id = $1 AND organization_id = $2"#,
+        role_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&app_state.pool)
+    .await
+    .context("Failed to fetch role")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Role {role_id} not found")))?;
+
+    Ok((IfMatch::etag(role.version), Json(role)))
+}
+
 #[instrument(skip_all)]
 pub async fn list_role_permissions(
+    tenant: Tenant,
     Path(role_id): Path<uuid::Uuid>,
     State(app_state): State<Arc<AppState>>,
 ) -> Result<Json<Vec<Permission>>, AppError> {
     let roles = sqlx::query_as!(
         Permission,
-        r#"SELECT p.*
+        r#"SELECT p.permission_id, p.resource, p.action, p.scope, p.deprecated, p.condition
         FROM permissions as p
-        LEFT JOIN roles_permissions as rp ON p.permission_id = rp.permission_id
-        WHERE rp.role_id = $1"#,
-        role_id
+        JOIN roles_permissions as rp ON p.permission_id = rp.permission_id
+        JOIN roles as r ON rp.role_id = r.role_id
+        WHERE rp.role_id = $1 AND r.organization_id = $2"#,
+        role_id,
+        tenant.organization_id
     )
     .fetch_all(&app_state.pool)
     .await
@@ -58,3 +90,53 @@ pub async fn list_role_permissions(
 
     Ok(Json(roles))
 }
+
+#[instrument(skip_all)]
+pub async fn list_role_components(
+    tenant: Tenant,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Component>>, AppError> {
+    let components = sqlx::query_as!(
+        Component,
+        r#"SELECT c.component_id, c.code
+        FROM components as c
+        JOIN roles_components as rc ON c.component_id = rc.component_id
+        JOIN roles as r ON rc.role_id = r.role_id
+        WHERE rc.role_id = $1 AND r.organization_id = $2
+        ORDER BY c.code"#,
+        role_id,
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch role components")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(components))
+}
+
+#[instrument(skip_all)]
+pub async fn list_role_approvers(
+    tenant: Tenant,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Approver>>, AppError> {
+    let approvers = sqlx::query_as!(
+        Approver,
+        r#"SELECT u.user_id, u.username
+        FROM users as u
+        JOIN roles_approvers as ra ON u.user_id = ra.user_id
+        JOIN roles as r ON ra.role_id = r.role_id
+        WHERE ra.role_id = $1 AND r.organization_id = $2
+        ORDER BY u.username"#,
+        role_id,
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch role approvers")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(approvers))
+}
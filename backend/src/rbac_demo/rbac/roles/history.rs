@@ -0,0 +1,118 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::permissions::models::Permission;
+use crate::rbac_demo::rbac::roles::models::{
+    DescriptionChange, RoleVersion, RoleVersionDiff, SetDifference, VersionRange,
+};
+use crate::rbac_demo::rbac::roles::update_permissions::check_role_exists;
+use anyhow::Context;
+use axum::extract::{Json, Path, Query, State};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Versions are recorded from the moment history was introduced, earlier
+/// ones are unknown.
+#[instrument(name = "List role history", skip(tenant, app_state))]
+pub async fn list_role_history(
+    tenant: Tenant,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<RoleVersion>>, AppError> {
+    if !check_role_exists(&app_state.pool, tenant.organization_id, role_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
+    }
+
+    let versions = sqlx::query_as!(
+        RoleVersion,
+        r#"SELECT version, description, recorded_at
+        FROM roles_history
+        WHERE role_id = $1
+        ORDER BY version"#,
+        role_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch role history")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(versions))
+}
+
+/// Permissions are shown as they were at each version. `from` may be the
+/// later version, which shows the change needed to go back.
+#[instrument(name = "Diff role versions", skip(tenant, app_state))]
+pub async fn diff_role_versions(
+    tenant: Tenant,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Query(range): Query<VersionRange>,
+) -> Result<Json<RoleVersionDiff>, AppError> {
+    if !check_role_exists(&app_state.pool, tenant.organization_id, role_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
+    }
+
+    let from = load_version(&app_state.pool, role_id, range.from).await?;
+    let to = load_version(&app_state.pool, role_id, range.to).await?;
+
+    let description = (from.description != to.description).then_some(DescriptionChange {
+        from: from.description,
+        to: to.description,
+    });
+    Ok(Json(RoleVersionDiff {
+        role_id,
+        from: range.from,
+        to: range.to,
+        description,
+        permissions: SetDifference::between(&from.permissions, &to.permissions, |p| {
+            p.permission_id
+        })
+        .into(),
+        components: SetDifference::between(&from.components, &to.components, Clone::clone).into(),
+    }))
+}
+
+struct Snapshot {
+    description: String,
+    permissions: Vec<Permission>,
+    components: Vec<String>,
+}
+
+async fn load_version(
+    pool: &PgPool,
+    role_id: uuid::Uuid,
+    version: i32,
+) -> Result<Snapshot, AppError> {
+    let row = sqlx::query!(
+        r#"SELECT description, permissions, components
+        FROM roles_history
+        WHERE role_id = $1 AND version = $2"#,
+        role_id,
+        version
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch role version")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| {
+        AppError::E404(anyhow::anyhow!(
+            "Version {version} of role {role_id} not found"
+        ))
+    })?;
+
+    let permissions = serde_json::from_value(row.permissions)
+        .context("Failed to read recorded permissions")
+        .map_err(AppError::E500)?;
+    Ok(Snapshot {
+        description: row.description,
+        permissions,
+        components: row.components,
+    })
+}
@@ -1,12 +1,20 @@
+use crate::rbac_demo::rbac::permissions::models::Permission;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde::Serialize;
 use sqlx::prelude::FromRow;
+use std::collections::BTreeSet;
 
 #[derive(Deserialize, Serialize, Debug, FromRow, Clone)]
 pub struct Role {
     pub role_id: uuid::Uuid,
     pub name: String,
     pub description: String,
+    /// Bumped on every change to the role's permissions or components and
+    /// exposed as its `ETag`.
+    pub version: i32,
+    /// Seeded presets, which cannot be deleted.
+    pub is_system: bool,
 }
 
 impl Role {
@@ -15,9 +23,34 @@ impl Role {
             role_id,
             name,
             description,
+            version: 1,
+            is_system: false,
         }
     }
 
+    /// Moves the role to its next version, provided it is still at `expected`.
+    /// Returns `None` if the role was changed by someone else in the meantime.
+    /// The row stays locked until the transaction ends, so concurrent edits of
+    /// the same role are serialized.
+    pub async fn bump_version(
+        transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        role_id: uuid::Uuid,
+        expected: i32,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            UPDATE roles
+            SET version = version + 1
+            WHERE role_id = $1 AND version = $2
+            RETURNING version
+            "#,
+            role_id,
+            expected
+        )
+        .fetch_optional(&mut **transaction)
+        .await
+    }
+
     pub async fn add_permissions(
         &self,
         pgpool: &sqlx::PgPool,
@@ -35,8 +68,122 @@ impl Role {
     }
 }
 
+/// `404` body of `/roles/{id}/permissions/add` naming the permissions that
+/// cannot be granted.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnknownPermissions {
+    pub message: String,
+    pub permission_ids: Vec<uuid::Uuid>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct CreateRole {
     pub name: String,
     pub description: String,
 }
+
+/// A user allowed to decide access requests for a role.
+#[derive(Deserialize, Serialize, Debug, FromRow, Clone)]
+pub struct Approver {
+    pub user_id: uuid::Uuid,
+    pub username: String,
+}
+
+/// Query of `/roles/compare`.
+#[derive(Deserialize, Debug)]
+pub struct CompareRoles {
+    pub a: uuid::Uuid,
+    pub b: uuid::Uuid,
+}
+
+/// Query of `/roles/{id}/history/diff`, both ends being role versions.
+#[derive(Deserialize, Debug)]
+pub struct VersionRange {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// Items held by only one of two sides, and by both.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetDifference<T> {
+    pub only_in_a: Vec<T>,
+    pub only_in_b: Vec<T>,
+    pub common: Vec<T>,
+}
+
+impl<T: Clone> SetDifference<T> {
+    /// Items are told apart by `key`, the result keeps the order of the
+    /// inputs.
+    pub fn between<K: Ord>(a: &[T], b: &[T], key: impl Fn(&T) -> K) -> Self {
+        let keys_a = a.iter().map(&key).collect::<BTreeSet<_>>();
+        let keys_b = b.iter().map(&key).collect::<BTreeSet<_>>();
+        Self {
+            only_in_a: a
+                .iter()
+                .filter(|i| !keys_b.contains(&key(i)))
+                .cloned()
+                .collect(),
+            only_in_b: b
+                .iter()
+                .filter(|i| !keys_a.contains(&key(i)))
+                .cloned()
+                .collect(),
+            common: a
+                .iter()
+                .filter(|i| keys_b.contains(&key(i)))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Response of `/roles/compare`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoleComparison {
+    pub a: Role,
+    pub b: Role,
+    pub permissions: SetDifference<Permission>,
+    pub components: SetDifference<String>,
+}
+
+/// A recorded version of a role, see `/roles/{id}/history`.
+#[derive(Serialize, Deserialize, Debug, FromRow)]
+pub struct RoleVersion {
+    pub version: i32,
+    pub description: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// What changed between two versions of a role, `added` being held at `to`
+/// but not at `from`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RoleVersionDiff {
+    pub role_id: uuid::Uuid,
+    pub from: i32,
+    pub to: i32,
+    pub description: Option<DescriptionChange>,
+    pub permissions: Delta<Permission>,
+    pub components: Delta<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DescriptionChange {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Delta<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+
+impl<T> From<SetDifference<T>> for Delta<T> {
+    /// From the difference between the `from` side `a` and the `to` side `b`.
+    fn from(difference: SetDifference<T>) -> Self {
+        Self {
+            added: difference.only_in_b,
+            removed: difference.only_in_a,
+        }
+    }
+}
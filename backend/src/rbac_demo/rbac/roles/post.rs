@@ -1,5 +1,7 @@
 use super::models::Role;
 use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
 use crate::{errors::AppError, rbac_demo::rbac::roles::models::CreateRole};
 use anyhow::Context;
 use axum::extract::Json;
@@ -7,23 +9,47 @@ use axum::extract::State;
 use std::sync::Arc;
 
 pub async fn create_new_role(
+    tenant: Tenant,
+    audit: Audit,
     State(app_state): State<Arc<AppState>>,
     Json(role): Json<CreateRole>,
 ) -> Result<Json<Role>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
     let role = sqlx::query_as!(
         Role,
         r#"
-        INSERT INTO roles (role_id, name, description)
-        VALUES (gen_random_uuid(), $1, $2)
-        RETURNING role_id, name, description
+        INSERT INTO roles (role_id, name, description, organization_id)
+        VALUES (gen_random_uuid(), $1, $2, $3)
+        RETURNING role_id, name, description, version, is_system
         "#,
         role.name,
-        role.description
+        role.description,
+        tenant.organization_id
     )
-    .fetch_one(&app_state.pool)
+    .fetch_one(&mut *transaction)
     .await
     .context("Failed to insert new role into db")
     .map_err(AppError::E500)?;
 
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("role.create", "role", role.role_id).after(&role),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
     Ok(Json(role))
 }
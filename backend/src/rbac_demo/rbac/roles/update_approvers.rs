@@ -0,0 +1,167 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::AssociationResult;
+use crate::rbac_demo::rbac::roles::update_permissions::check_role_exists;
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(
+    name = "Add approvers to role",
+    skip(tenant, audit, app_state),
+    fields(role_id = %role_id, users = ?users),
+)]
+pub async fn add_role_approvers(
+    tenant: Tenant,
+    audit: Audit,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(users): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    let exists = check_role_exists(&app_state.pool, tenant.organization_id, role_id)
+        .await
+        .map_err(AppError::E500)?;
+    if !exists {
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
+    }
+
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT requested.user_id as "user_id!"
+        FROM unnest($1::uuid[]) as requested (user_id)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM organizations_users as ou
+            WHERE ou.user_id = requested.user_id AND ou.organization_id = $2
+        )
+        "#,
+        &users as &[uuid::Uuid],
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to check if users exist")
+    .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        return Err(AppError::E404(anyhow::anyhow!(
+            "Users {unknown:?} not found in the organization"
+        )));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let added = sqlx::query_scalar!(
+        r#"
+        INSERT INTO roles_approvers (role_id, user_id)
+        SELECT $1, unnest($2::uuid[])
+        ON CONFLICT (role_id, user_id) DO NOTHING
+        RETURNING user_id
+        "#,
+        role_id,
+        &users as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to add approvers to role")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_added(&users, added);
+    finish_approvers_edit(
+        transaction,
+        &audit,
+        tenant,
+        "role.approvers.add",
+        role_id,
+        &result,
+    )
+    .await?;
+    Ok((result.status_code(), Json(result)).into_response())
+}
+
+#[instrument(
+    name = "Remove approvers from role",
+    skip(tenant, audit, app_state),
+    fields(role_id = %role_id, users = ?users),
+)]
+pub async fn remove_role_approvers(
+    tenant: Tenant,
+    audit: Audit,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Json(users): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    let exists = check_role_exists(&app_state.pool, tenant.organization_id, role_id)
+        .await
+        .map_err(AppError::E500)?;
+    if !exists {
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let removed = sqlx::query_scalar!(
+        r#"
+        DELETE FROM roles_approvers
+        WHERE role_id = $1 AND user_id = ANY($2)
+        RETURNING user_id
+        "#,
+        role_id,
+        &users as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to remove approvers from role")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_removed(&users, removed);
+    finish_approvers_edit(
+        transaction,
+        &audit,
+        tenant,
+        "role.approvers.remove",
+        role_id,
+        &result,
+    )
+    .await?;
+    Ok((result.status_code(), Json(result)).into_response())
+}
+
+/// Records the edit and commits it, unless nothing changed.
+async fn finish_approvers_edit(
+    mut transaction: sqlx::Transaction<'_, sqlx::Postgres>,
+    audit: &Audit,
+    tenant: Tenant,
+    action: &'static str,
+    role_id: uuid::Uuid,
+    result: &AssociationResult<uuid::Uuid>,
+) -> Result<(), AppError> {
+    if result.is_noop() {
+        return Ok(());
+    }
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new(action, "role", role_id).after(result),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)
+}
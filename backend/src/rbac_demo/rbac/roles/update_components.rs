@@ -0,0 +1,153 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::{AssociationResult, IfMatch};
+use crate::rbac_demo::rbac::roles::update_permissions::{
+    check_role_exists, claim_role_version, finish_role_edit,
+};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use axum::response::Response;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Components are addressed by their registry code. Codes seen for the first
+/// time are registered on the fly, as the frontend registry owns them.
+#[instrument(
+    name = "Add components to role",
+    skip(tenant, audit, app_state),
+    fields(role_id = %role_id, components = ?components),
+)]
+pub async fn add_role_components(
+    tenant: Tenant,
+    audit: Audit,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    IfMatch(version): IfMatch,
+    Json(components): Json<Vec<String>>,
+) -> Result<Response, AppError> {
+    let exists = check_role_exists(&app_state.pool, tenant.organization_id, role_id)
+        .await
+        .map_err(AppError::E500)?;
+    if !exists {
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let new_version = claim_role_version(&mut transaction, role_id, version).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO components (code)
+        SELECT unnest($1::text[])
+        ON CONFLICT (code) DO NOTHING
+        "#,
+        &components as &[String]
+    )
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to register components")
+    .map_err(AppError::E500)?;
+
+    let added = sqlx::query_scalar!(
+        r#"
+        WITH inserted AS (
+            INSERT INTO roles_components (role_id, component_id)
+            SELECT $1, component_id FROM components WHERE code = ANY($2)
+            ON CONFLICT (role_id, component_id) DO NOTHING
+            RETURNING component_id
+        )
+        SELECT c.code
+        FROM inserted
+        JOIN components as c ON c.component_id = inserted.component_id
+        "#,
+        role_id,
+        &components as &[String]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to add components to role")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_added(&components, added);
+    let event = AuditEvent::new("role.components.add", "role", role_id);
+    finish_role_edit(
+        transaction,
+        &audit,
+        tenant,
+        event,
+        version,
+        new_version,
+        result,
+    )
+    .await
+}
+
+#[instrument(
+    name = "Remove components from role",
+    skip(tenant, audit, app_state),
+    fields(role_id = %role_id, components = ?components),
+)]
+pub async fn remove_role_components(
+    tenant: Tenant,
+    audit: Audit,
+    Path(role_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    IfMatch(version): IfMatch,
+    Json(components): Json<Vec<String>>,
+) -> Result<Response, AppError> {
+    let exists = check_role_exists(&app_state.pool, tenant.organization_id, role_id)
+        .await
+        .map_err(AppError::E500)?;
+    if !exists {
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let new_version = claim_role_version(&mut transaction, role_id, version).await?;
+
+    let removed = sqlx::query_scalar!(
+        r#"
+        DELETE FROM roles_components as rc
+        USING components as c
+        WHERE rc.component_id = c.component_id
+            AND rc.role_id = $1
+            AND c.code = ANY($2)
+        RETURNING c.code
+        "#,
+        role_id,
+        &components as &[String]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to delete components from role")
+    .map_err(AppError::E500)?;
+
+    tracing::info!("Deleted {} components from role", removed.len());
+
+    let result = AssociationResult::from_removed(&components, removed);
+    let event = AuditEvent::new("role.components.remove", "role", role_id);
+    finish_role_edit(
+        transaction,
+        &audit,
+        tenant,
+        event,
+        version,
+        new_version,
+        result,
+    )
+    .await
+}
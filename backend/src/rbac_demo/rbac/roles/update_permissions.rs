@@ -1,117 +1,249 @@
 use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
 use crate::errors::AppError;
+use crate::models::{AssociationResult, IfMatch};
+use crate::rbac_demo::rbac::roles::models::{Role, UnknownPermissions};
 use anyhow::Context;
 use axum::extract::{Json, Path, State};
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use sqlx::PgPool;
 use std::sync::Arc;
 use tracing::instrument;
 
 #[instrument(
     name = "Add permissions to role",
-    skip(app_state),
+    skip(tenant, audit, app_state),
     fields(role_id = %role_id, permissions = ?permissions),
 )]
 pub async fn add_role_permissions(
+    tenant: Tenant,
+    audit: Audit,
     Path(role_id): Path<uuid::Uuid>,
     State(app_state): State<Arc<AppState>>,
+    IfMatch(version): IfMatch,
     Json(permissions): Json<Vec<uuid::Uuid>>,
-) -> Result<StatusCode, AppError> {
-    let exists = check_role_exists(&app_state.pool, role_id)
+) -> Result<Response, AppError> {
+    let exists = check_role_exists(&app_state.pool, tenant.organization_id, role_id)
         .await
         .map_err(AppError::E500)?;
     if !exists {
-        return Ok(StatusCode::NOT_FOUND);
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
     }
 
-    if !validate_permissions(&app_state.pool, &permissions)
+    let unknown = find_unknown_permissions(&app_state.pool, &permissions)
         .await
-        .map_err(AppError::E500)?
-    {
-        return Ok(StatusCode::NOT_FOUND);
+        .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        let body = UnknownPermissions {
+            message: "Some permissions do not exist or are deprecated".to_string(),
+            permission_ids: unknown,
+        };
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
     }
 
-    let mut qb = sqlx::QueryBuilder::new("INSERT INTO roles_permissions (role_id, permission_id) ");
-    qb.push_values(permissions, |mut query, permission| {
-        query.push_bind(role_id);
-        query.push_bind(permission);
-    });
-    qb.push(" ON CONFLICT (role_id, permission_id) DO NOTHING");
-    qb.build()
-        .execute(&app_state.pool)
+    let mut transaction = app_state
+        .pool
+        .begin()
         .await
-        .context("Failed to insert new role into db")
+        .context("Failed to start transaction")
         .map_err(AppError::E500)?;
 
-    Ok(StatusCode::OK)
+    let new_version = claim_role_version(&mut transaction, role_id, version).await?;
+
+    let added = sqlx::query_scalar!(
+        r#"
+        INSERT INTO roles_permissions (role_id, permission_id)
+        SELECT $1, unnest($2::uuid[])
+        ON CONFLICT (role_id, permission_id) DO NOTHING
+        RETURNING permission_id
+        "#,
+        role_id,
+        &permissions as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to add permissions to role")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_added(&permissions, added);
+    let event = AuditEvent::new("role.permissions.add", "role", role_id);
+    finish_role_edit(
+        transaction,
+        &audit,
+        tenant,
+        event,
+        version,
+        new_version,
+        result,
+    )
+    .await
 }
 
+/// Returns the requested permissions that cannot be granted, either because
+/// they do not exist or because they are deprecated.
 #[instrument(name = "Validate permissions", skip_all)]
-async fn validate_permissions(
+async fn find_unknown_permissions(
     pool: &PgPool,
     permissions: &[uuid::Uuid],
-) -> Result<bool, anyhow::Error> {
-    let all_exists = sqlx::query_scalar!(
+) -> Result<Vec<uuid::Uuid>, anyhow::Error> {
+    let unknown = sqlx::query_scalar!(
         r#"
-        SELECT NOT EXISTS (
-            SELECT unnest($1::uuid[])
-            EXCEPT
-            SELECT permission_id FROM permissions
-        ) as "all_exists!"
+        SELECT DISTINCT requested.permission_id as "permission_id!"
+        FROM unnest($1::uuid[]) as requested (permission_id)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM permissions as p
+            WHERE p.permission_id = requested.permission_id AND NOT p.deprecated
+        )
         "#,
         permissions as &[uuid::Uuid]
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await
     .context("Failed to check if permissions exist")?;
 
-    Ok(all_exists)
+    Ok(unknown)
 }
 
 #[instrument(
     name = "Remove permissions from role",
-    skip(app_state),
+    skip(tenant, audit, app_state),
     fields(role_id = %role_id, permissions = ?permissions),
 )]
 pub async fn remove_role_permissions(
+    tenant: Tenant,
+    audit: Audit,
     Path(role_id): Path<uuid::Uuid>,
     State(app_state): State<Arc<AppState>>,
+    IfMatch(version): IfMatch,
     Json(permissions): Json<Vec<uuid::Uuid>>,
-) -> Result<StatusCode, AppError> {
-    let exists = check_role_exists(&app_state.pool, role_id)
+) -> Result<Response, AppError> {
+    let exists = check_role_exists(&app_state.pool, tenant.organization_id, role_id)
         .await
         .map_err(AppError::E500)?;
     if !exists {
-        return Ok(StatusCode::NOT_FOUND);
+        return Err(AppError::E404(anyhow::anyhow!("Role {role_id} not found")));
     }
 
-    // No need to validate permissions since we are deleting them.
-    // If a permission does not exist, it will simply be skipped.
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let new_version = claim_role_version(&mut transaction, role_id, version).await?;
 
-    let result = sqlx::query!(
+    let removed = sqlx::query_scalar!(
         r#"
-        DELETE FROM roles_permissions 
+        DELETE FROM roles_permissions
         WHERE role_id = $1 AND permission_id = ANY($2)
+        RETURNING permission_id
         "#,
         role_id,
         &permissions as &[uuid::Uuid]
     )
-    .execute(&app_state.pool)
+    .fetch_all(&mut *transaction)
     .await
     .context("Failed to delete permissions from role")
     .map_err(AppError::E500)?;
 
-    tracing::info!("Deleted {} permissions from role", result.rows_affected());
+    tracing::info!("Deleted {} permissions from role", removed.len());
 
-    Ok(StatusCode::OK)
+    let result = AssociationResult::from_removed(&permissions, removed);
+    let event = AuditEvent::new("role.permissions.remove", "role", role_id);
+    finish_role_edit(
+        transaction,
+        &audit,
+        tenant,
+        event,
+        version,
+        new_version,
+        result,
+    )
+    .await
 }
 
 #[instrument(skip_all)]
-async fn check_role_exists(pool: &PgPool, role_id: uuid::Uuid) -> Result<bool, anyhow::Error> {
-    let exists = sqlx::query_scalar!("SELECT 1 FROM roles WHERE role_id = $1", role_id)
-        .fetch_optional(pool)
-        .await
-        .context("Failed to check if role exists")?;
+pub(crate) async fn check_role_exists(
+    pool: &PgPool,
+    organization_id: uuid::Uuid,
+    role_id: uuid::Uuid,
+) -> Result<bool, anyhow::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT 1 FROM roles WHERE role_id = $1 AND organization_id = $2",
+        role_id,
+        organization_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to check if role exists")?;
 
     Ok(exists.is_some())
 }
+
+/// Bumps the role version inside `transaction`, answering `412` if the client
+/// edited a stale version of the role.
+#[instrument(skip(transaction))]
+pub(super) async fn claim_role_version(
+    transaction: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    role_id: uuid::Uuid,
+    expected: i32,
+) -> Result<i32, AppError> {
+    Role::bump_version(transaction, role_id, expected)
+        .await
+        .context("Failed to bump role version")
+        .map_err(AppError::E500)?
+        .ok_or_else(|| {
+            AppError::E412(anyhow::anyhow!(
+                "Role {role_id} has been modified since version {expected}"
+            ))
+        })
+}
+
+/// Records the edit and commits it, or rolls it back when nothing changed so
+/// the role keeps its current version. The response carries the per-item
+/// result and the role's resulting `ETag`. An edit applied in part, some
+/// items being skipped or missing, is committed and answers `207
+/// Multi-Status` rather than the `409` or `412` of an edit applying nothing,
+/// so that clients take the new `ETag`.
+pub(super) async fn finish_role_edit<T>(
+    mut transaction: sqlx::Transaction<'_, sqlx::Postgres>,
+    audit: &Audit,
+    tenant: Tenant,
+    event: AuditEvent,
+    old_version: i32,
+    new_version: i32,
+    result: AssociationResult<T>,
+) -> Result<Response, AppError>
+where
+    T: PartialEq + Clone + serde::Serialize,
+{
+    let version = if result.is_noop() {
+        transaction
+            .rollback()
+            .await
+            .context("Failed to roll back transaction")
+            .map_err(AppError::E500)?;
+        old_version
+    } else {
+        audit
+            .record(&mut transaction, tenant, event.after(&result))
+            .await
+            .map_err(AppError::E500)?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction")
+            .map_err(AppError::E500)?;
+        new_version
+    };
+
+    let status = match result.status_code() {
+        StatusCode::OK => StatusCode::OK,
+        _ if !result.is_noop() => StatusCode::MULTI_STATUS,
+        status => status,
+    };
+    Ok((status, IfMatch::etag(version), Json(result)).into_response())
+}
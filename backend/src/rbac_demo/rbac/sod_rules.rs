@@ -0,0 +1,5 @@
+pub mod check;
+pub mod delete;
+pub mod get;
+pub mod models;
+pub mod post;
@@ -0,0 +1,74 @@
+use crate::rbac_demo::rbac::sod_rules::models::{SodRuleKind, SodViolation, ViolationRow};
+use anyhow::Context;
+use sqlx::PgConnection;
+use tracing::instrument;
+
+/// Rules that granting `roles` to the user would break. Only the roles the
+/// user does not hold yet are considered, so existing violations do not block
+/// unrelated grants. The rules involved stay locked until the transaction
+/// ends, serializing concurrent grants they constrain.
+#[instrument(skip(connection))]
+pub(crate) async fn violations_for_grant(
+    connection: &mut PgConnection,
+    user_id: uuid::Uuid,
+    roles: &[uuid::Uuid],
+) -> Result<Vec<SodViolation>, anyhow::Error> {
+    sqlx::query!(
+        r#"
+        SELECT 1 as locked FROM sod_rules
+        WHERE role_id = ANY($1) OR other_role_id = ANY($1)
+        FOR UPDATE
+        "#,
+        roles as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to lock separation-of-duties rules")?;
+
+    let rows = sqlx::query_as!(
+        ViolationRow,
+        r#"
+        WITH new AS (
+            SELECT unnest($2::uuid[]) as role_id
+            EXCEPT
+            SELECT role_id FROM users_roles WHERE user_id = $1
+        ), held AS (
+            SELECT role_id FROM users_roles
+            WHERE user_id = $1 AND (valid_until IS NULL OR valid_until > now())
+            UNION
+            SELECT role_id FROM new
+        ), holders AS (
+            SELECT role_id, user_id FROM users_roles
+            WHERE valid_until IS NULL OR valid_until > now()
+            UNION
+            SELECT role_id, $1 FROM new
+        )
+        SELECT s.sod_rule_id, s.kind as "kind: SodRuleKind", s.role_id, r.name as role_name,
+            s.other_role_id, o.name as "other_role_name?", s.max_holders, s.description,
+            CASE WHEN s.kind = 'mutually_exclusive' THEN ARRAY[$1::uuid]
+            ELSE ARRAY(SELECT h.user_id FROM holders as h WHERE h.role_id = s.role_id ORDER BY h.user_id)
+            END as "user_ids!"
+        FROM sod_rules as s
+        JOIN roles as r ON r.role_id = s.role_id
+        LEFT JOIN roles as o ON o.role_id = s.other_role_id
+        WHERE (
+            s.kind = 'mutually_exclusive'
+            AND s.role_id IN (SELECT role_id FROM held)
+            AND s.other_role_id IN (SELECT role_id FROM held)
+            AND (s.role_id IN (SELECT role_id FROM new) OR s.other_role_id IN (SELECT role_id FROM new))
+        ) OR (
+            s.kind = 'max_holders'
+            AND s.role_id IN (SELECT role_id FROM new)
+            AND (SELECT count(*) FROM holders as h WHERE h.role_id = s.role_id) > s.max_holders
+        )
+        ORDER BY s.sod_rule_id
+        "#,
+        user_id,
+        roles as &[uuid::Uuid]
+    )
+    .fetch_all(&mut *connection)
+    .await
+    .context("Failed to check separation-of-duties rules")?;
+
+    Ok(rows.into_iter().map(SodViolation::from).collect())
+}
@@ -0,0 +1,62 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::sod_rules::models::{SodRule, SodRuleKind};
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(
+    name = "Delete a separation-of-duties rule",
+    skip(tenant, audit, app_state)
+)]
+pub async fn delete_sod_rule(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(sod_rule_id): Path<uuid::Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let deleted = sqlx::query_as!(
+        SodRule,
+        r#"
+        DELETE FROM sod_rules as s
+        USING roles as r
+        WHERE s.role_id = r.role_id AND s.sod_rule_id = $1 AND r.organization_id = $2
+        RETURNING s.sod_rule_id, s.kind as "kind: SodRuleKind", s.role_id, s.other_role_id,
+            s.max_holders, s.description
+        "#,
+        sod_rule_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .context("Failed to delete rule")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Rule {sod_rule_id} not found")))?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("sod_rule.delete", "sod_rule", sod_rule_id).before(&deleted),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,85 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::sod_rules::models::{SodRule, SodRuleKind, SodViolation, ViolationRow};
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(skip_all)]
+pub async fn list_sod_rules(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SodRule>>, AppError> {
+    let rules = sqlx::query_as!(
+        SodRule,
+        r#"
+        SELECT s.sod_rule_id, s.kind as "kind: SodRuleKind", s.role_id, s.other_role_id,
+            s.max_holders, s.description
+        FROM sod_rules as s
+        JOIN roles as r ON s.role_id = r.role_id
+        WHERE r.organization_id = $1
+        ORDER BY s.sod_rule_id
+        "#,
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch separation-of-duties rules")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(rules))
+}
+
+/// Rules broken by the current grants, e.g. because they predate the rule.
+/// Expired grants waiting to be purged are ignored.
+#[instrument(skip_all)]
+pub async fn list_sod_violations(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SodViolation>>, AppError> {
+    let rows = sqlx::query_as!(
+        ViolationRow,
+        r#"
+        WITH holders AS (
+            SELECT role_id, user_id FROM users_roles
+            WHERE valid_until IS NULL OR valid_until > now()
+        ), evaluated AS (
+            SELECT s.sod_rule_id, s.kind, s.role_id, r.name as role_name,
+                s.other_role_id, o.name as other_role_name, s.max_holders, s.description,
+                CASE WHEN s.kind = 'mutually_exclusive' THEN ARRAY(
+                    SELECT a.user_id
+                    FROM holders as a
+                    JOIN holders as b ON a.user_id = b.user_id
+                    WHERE a.role_id = s.role_id AND b.role_id = s.other_role_id
+                    ORDER BY a.user_id
+                )
+                ELSE ARRAY(
+                    SELECT h.user_id FROM holders as h WHERE h.role_id = s.role_id
+                    ORDER BY h.user_id
+                )
+                END as user_ids
+            FROM sod_rules as s
+            JOIN roles as r ON r.role_id = s.role_id
+            LEFT JOIN roles as o ON o.role_id = s.other_role_id
+            WHERE r.organization_id = $1
+        )
+        SELECT sod_rule_id as "sod_rule_id!", kind as "kind!: SodRuleKind",
+            role_id as "role_id!", role_name as "role_name!", other_role_id,
+            other_role_name, max_holders, description as "description!",
+            user_ids as "user_ids!"
+        FROM evaluated
+        WHERE (kind = 'mutually_exclusive' AND cardinality(user_ids) > 0)
+            OR (kind = 'max_holders' AND cardinality(user_ids) > max_holders)
+        ORDER BY sod_rule_id
+        "#,
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to evaluate separation-of-duties rules")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(rows.into_iter().map(SodViolation::from).collect()))
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum SodRuleKind {
+    /// No user may hold both `role_id` and `other_role_id`.
+    MutuallyExclusive,
+    /// At most `max_holders` users may hold `role_id`.
+    MaxHolders,
+}
+
+/// A static separation-of-duties constraint, enforced whenever a role is
+/// granted.
+#[derive(Serialize, Deserialize, Debug, Clone, FromRow)]
+pub struct SodRule {
+    pub sod_rule_id: uuid::Uuid,
+    pub kind: SodRuleKind,
+    pub role_id: uuid::Uuid,
+    pub other_role_id: Option<uuid::Uuid>,
+    pub max_holders: Option<i32>,
+    pub description: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateSodRule {
+    pub kind: SodRuleKind,
+    pub role_id: uuid::Uuid,
+    pub other_role_id: Option<uuid::Uuid>,
+    pub max_holders: Option<i32>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A rule broken by existing grants, or by a grant about to be made.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SodViolation {
+    pub sod_rule_id: uuid::Uuid,
+    pub kind: SodRuleKind,
+    pub message: String,
+    pub role_ids: Vec<uuid::Uuid>,
+    /// Users holding the conflicting roles, or all holders of a role with
+    /// too many of them.
+    pub user_ids: Vec<uuid::Uuid>,
+}
+
+/// `409` body of a grant refused for breaking separation-of-duties rules.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SodConflict {
+    pub message: String,
+    pub violations: Vec<SodViolation>,
+}
+
+impl SodConflict {
+    pub fn new(violations: Vec<SodViolation>) -> Self {
+        Self {
+            message: "The grant breaks separation-of-duties rules".to_string(),
+            violations,
+        }
+    }
+}
+
+/// Broken rule as read from the database, before it is described.
+#[derive(Debug, FromRow)]
+pub(crate) struct ViolationRow {
+    pub sod_rule_id: uuid::Uuid,
+    pub kind: SodRuleKind,
+    pub role_id: uuid::Uuid,
+    pub role_name: String,
+    pub other_role_id: Option<uuid::Uuid>,
+    pub other_role_name: Option<String>,
+    pub max_holders: Option<i32>,
+    pub description: String,
+    pub user_ids: Vec<uuid::Uuid>,
+}
+
+impl From<ViolationRow> for SodViolation {
+    fn from(row: ViolationRow) -> Self {
+        let mut message = match row.kind {
+            SodRuleKind::MutuallyExclusive => format!(
+                "Roles '{}' and '{}' are mutually exclusive",
+                row.role_name,
+                row.other_role_name.unwrap_or_default()
+            ),
+            SodRuleKind::MaxHolders => format!(
+                "Role '{}' may be held by at most {} users, found {}",
+                row.role_name,
+                row.max_holders.unwrap_or_default(),
+                row.user_ids.len()
+            ),
+        };
+        if !row.description.is_empty() {
+            message = format!("{message}: {}", row.description);
+        }
+
+        Self {
+            sod_rule_id: row.sod_rule_id,
+            kind: row.kind,
+            message,
+            role_ids: std::iter::once(row.role_id)
+                .chain(row.other_role_id)
+                .collect(),
+            user_ids: row.user_ids,
+        }
+    }
+}
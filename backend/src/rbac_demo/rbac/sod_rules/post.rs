@@ -0,0 +1,111 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::sod_rules::models::{CreateSodRule, SodRule, SodRuleKind};
+use crate::utils::db;
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Rules already broken by existing grants are accepted, they show up in
+/// `/sod-rules/violations`.
+#[instrument(
+    name = "Create separation-of-duties rule",
+    skip(tenant, audit, app_state)
+)]
+pub async fn create_sod_rule(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(rule): Json<CreateSodRule>,
+) -> Result<Json<SodRule>, AppError> {
+    match (rule.kind, rule.other_role_id, rule.max_holders) {
+        (SodRuleKind::MutuallyExclusive, Some(other), None) if other != rule.role_id => {}
+        (SodRuleKind::MaxHolders, None, Some(max)) if max >= 1 => {}
+        (SodRuleKind::MutuallyExclusive, _, _) => {
+            return Err(AppError::E400(anyhow::anyhow!(
+                "mutually_exclusive rules need a distinct other_role_id and no max_holders"
+            )));
+        }
+        (SodRuleKind::MaxHolders, _, _) => {
+            return Err(AppError::E400(anyhow::anyhow!(
+                "max_holders rules need a positive max_holders and no other_role_id"
+            )));
+        }
+    }
+
+    let roles = [Some(rule.role_id), rule.other_role_id]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT requested.role_id as "role_id!"
+        FROM unnest($1::uuid[]) as requested (role_id)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM roles as r
+            WHERE r.role_id = requested.role_id AND r.organization_id = $2
+        )
+        "#,
+        &roles as &[uuid::Uuid],
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to check if roles exist")
+    .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        return Err(AppError::E404(anyhow::anyhow!(
+            "Roles {unknown:?} not found"
+        )));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let created = sqlx::query_as!(
+        SodRule,
+        r#"
+        INSERT INTO sod_rules (kind, role_id, other_role_id, max_holders, description)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING sod_rule_id, kind as "kind: SodRuleKind", role_id, other_role_id,
+            max_holders, description
+        "#,
+        rule.kind as SodRuleKind,
+        rule.role_id,
+        rule.other_role_id,
+        rule.max_holders,
+        rule.description
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(|e| {
+        if db::is_unique_violation(&e) {
+            AppError::E409(anyhow::anyhow!(e).context("An equivalent rule already exists"))
+        } else {
+            AppError::E500(anyhow::anyhow!(e).context("Failed to create rule"))
+        }
+    })?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("sod_rule.create", "sod_rule", created.sod_rule_id).after(&created),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(created))
+}
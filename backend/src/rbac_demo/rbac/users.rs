@@ -0,0 +1,3 @@
+pub mod get;
+pub mod models;
+pub mod update_roles;
@@ -0,0 +1,65 @@
+use crate::app_states::AppState;
+use crate::authorization::{EffectivePermissions, Tenant};
+use crate::errors::AppError;
+use crate::rbac_demo::rbac::users::models::{GrantScope, RoleGrant};
+use crate::rbac_demo::rbac::users::update_roles::check_user_exists;
+use anyhow::Context;
+use axum::extract::{Json, Path, Query, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// All grants of the user, including those not effective yet or expired but
+/// not purged yet.
+#[instrument(skip_all)]
+pub async fn list_user_roles(
+    tenant: Tenant,
+    Path(user_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<Vec<RoleGrant>>, AppError> {
+    let roles = sqlx::query_as!(
+        RoleGrant,
+        r#"SELECT r.role_id, r.name, r.description, r.version, ur.project_id,
+            ur.valid_from, ur.valid_until
+        FROM roles as r
+        JOIN users_roles as ur ON r.role_id = ur.role_id
+        WHERE ur.user_id = $1 AND r.organization_id = $2
+        ORDER BY r.name, ur.project_id NULLS FIRST"#,
+        user_id,
+        tenant.organization_id
+    )
+    .fetch_all(&app_state.pool)
+    .await
+    .context("Failed to fetch roles of user")
+    .map_err(AppError::E500)?;
+
+    Ok(Json(roles))
+}
+
+/// Union of the permissions and components of all the user's roles in the
+/// organization. With `?project_id=` the permissions are those effective on
+/// that project, organization-wide and project-bound roles merged.
+#[instrument(skip_all)]
+pub async fn get_user_permissions(
+    tenant: Tenant,
+    Path(user_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Query(scope): Query<GrantScope>,
+) -> Result<Json<EffectivePermissions>, AppError> {
+    if !check_user_exists(&app_state.pool, tenant.organization_id, user_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!("User {user_id} not found")));
+    }
+
+    let permissions = app_state
+        .permission_cache
+        .effective_permissions(&app_state.pool, user_id, tenant.organization_id)
+        .await
+        .map_err(AppError::E500)?;
+
+    match scope.project_id {
+        Some(project_id) => Ok(Json(permissions.for_project(project_id))),
+        None => Ok(Json(permissions)),
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// `404` body of `/users/{id}/roles/add` naming the roles that do not exist.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnknownRoles {
+    pub message: String,
+    pub role_ids: Vec<uuid::Uuid>,
+}
+
+/// Validity window of the grants made by `/users/{id}/roles/add`, given in
+/// the query string. Grants start now and never end unless told otherwise.
+#[derive(Deserialize, Debug, Default)]
+pub struct GrantWindow {
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+/// Project the grants of `/users/{id}/roles/add` and `/remove` are bound
+/// to, given in the query string. Without one they apply organization-wide.
+#[derive(Deserialize, Debug, Default)]
+pub struct GrantScope {
+    pub project_id: Option<uuid::Uuid>,
+}
+
+/// A role held by a user, with the window in which it is effective and the
+/// project it is bound to, if any.
+#[derive(Serialize, Deserialize, Debug, FromRow)]
+pub struct RoleGrant {
+    pub role_id: uuid::Uuid,
+    pub name: String,
+    pub description: String,
+    pub version: i32,
+    pub project_id: Option<uuid::Uuid>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,299 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::AssociationResult;
+use crate::rbac_demo::rbac::sod_rules::check::violations_for_grant;
+use crate::rbac_demo::rbac::sod_rules::models::SodConflict;
+use crate::rbac_demo::rbac::users::models::{GrantScope, GrantWindow, UnknownRoles};
+use anyhow::Context;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool};
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(
+    name = "Assign roles to user",
+    skip(tenant, audit, app_state),
+    fields(user_id = %user_id, roles = ?roles),
+)]
+pub async fn add_user_roles(
+    tenant: Tenant,
+    audit: Audit,
+    Path(user_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Query(window): Query<GrantWindow>,
+    Query(scope): Query<GrantScope>,
+    Json(roles): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    let valid_from = window.valid_from.unwrap_or_else(Utc::now);
+    if let Some(valid_until) = window.valid_until
+        && (valid_until <= valid_from || valid_until <= Utc::now())
+    {
+        return Err(AppError::E400(anyhow::anyhow!(
+            "valid_until must be after valid_from and in the future"
+        )));
+    }
+
+    if !check_user_exists(&app_state.pool, tenant.organization_id, user_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!("User {user_id} not found")));
+    }
+    if let Some(project_id) = scope.project_id {
+        check_project_exists(&app_state.pool, tenant.organization_id, project_id).await?;
+    }
+
+    let unknown = find_unknown_roles(&app_state.pool, tenant.organization_id, &roles)
+        .await
+        .map_err(AppError::E500)?;
+    if !unknown.is_empty() {
+        let body = UnknownRoles {
+            message: "Some roles do not exist".to_string(),
+            role_ids: unknown,
+        };
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let violations = violations_for_grant(&mut transaction, user_id, &roles)
+        .await
+        .map_err(AppError::E500)?;
+    if !violations.is_empty() {
+        let body = SodConflict::new(violations);
+        return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+    }
+
+    let added = grant_roles(
+        &mut *transaction,
+        user_id,
+        &roles,
+        scope.project_id,
+        valid_from,
+        window.valid_until,
+    )
+    .await
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_added(&roles, added);
+    if !result.is_noop() {
+        let grant = json!({
+            "roles": &result,
+            "project_id": scope.project_id,
+            "valid_from": valid_from,
+            "valid_until": window.valid_until,
+        });
+        audit
+            .record(
+                &mut transaction,
+                tenant,
+                AuditEvent::new("user.roles.grant", "user", user_id).after(grant),
+            )
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok((result.status_code(), Json(result)).into_response())
+}
+
+#[instrument(
+    name = "Unassign roles from user",
+    skip(tenant, audit, app_state),
+    fields(user_id = %user_id, roles = ?roles),
+)]
+pub async fn remove_user_roles(
+    tenant: Tenant,
+    audit: Audit,
+    Path(user_id): Path<uuid::Uuid>,
+    State(app_state): State<Arc<AppState>>,
+    Query(scope): Query<GrantScope>,
+    Json(roles): Json<Vec<uuid::Uuid>>,
+) -> Result<Response, AppError> {
+    if !check_user_exists(&app_state.pool, tenant.organization_id, user_id)
+        .await
+        .map_err(AppError::E500)?
+    {
+        return Err(AppError::E404(anyhow::anyhow!("User {user_id} not found")));
+    }
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let removed = sqlx::query_scalar!(
+        r#"
+        WITH revoked AS (
+            DELETE FROM users_roles
+            WHERE user_id = $1
+                AND role_id = ANY($2)
+                AND role_id IN (SELECT role_id FROM roles WHERE organization_id = $3)
+                AND project_id IS NOT DISTINCT FROM $4
+            RETURNING user_id, role_id, project_id, valid_from, valid_until
+        ), audited AS (
+            INSERT INTO users_roles_audit
+                (user_id, role_id, project_id, valid_from, valid_until, event)
+            SELECT user_id, role_id, project_id, valid_from, valid_until, 'revoked'
+            FROM revoked
+        )
+        SELECT role_id as "role_id!" FROM revoked
+        "#,
+        user_id,
+        &roles as &[uuid::Uuid],
+        tenant.organization_id,
+        scope.project_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to unassign roles from user")
+    .map_err(AppError::E500)?;
+
+    let result = AssociationResult::from_removed(&roles, removed);
+    if !result.is_noop() {
+        let revocation = json!({ "roles": &result, "project_id": scope.project_id });
+        audit
+            .record(
+                &mut transaction,
+                tenant,
+                AuditEvent::new("user.roles.revoke", "user", user_id).after(revocation),
+            )
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok((result.status_code(), Json(result)).into_response())
+}
+
+/// Grants the roles not held yet in the scope, organization-wide without a
+/// `project_id`, recording each in `users_roles_audit`, and returns the ones
+/// granted. Callers check separation-of-duties rules first, in the same
+/// transaction.
+#[instrument(skip(executor))]
+pub(crate) async fn grant_roles(
+    executor: impl PgExecutor<'_>,
+    user_id: uuid::Uuid,
+    roles: &[uuid::Uuid],
+    project_id: Option<uuid::Uuid>,
+    valid_from: DateTime<Utc>,
+    valid_until: Option<DateTime<Utc>>,
+) -> Result<Vec<uuid::Uuid>, anyhow::Error> {
+    let granted = sqlx::query_scalar!(
+        r#"
+        WITH granted AS (
+            INSERT INTO users_roles (user_id, role_id, project_id, valid_from, valid_until)
+            SELECT $1, unnest($2::uuid[]), $3, $4, $5
+            ON CONFLICT ON CONSTRAINT uq_users_roles DO NOTHING
+            RETURNING user_id, role_id, project_id, valid_from, valid_until
+        ), audited AS (
+            INSERT INTO users_roles_audit
+                (user_id, role_id, project_id, valid_from, valid_until, event)
+            SELECT user_id, role_id, project_id, valid_from, valid_until, 'granted'
+            FROM granted
+        )
+        SELECT role_id as "role_id!" FROM granted
+        "#,
+        user_id,
+        roles as &[uuid::Uuid],
+        project_id,
+        valid_from,
+        valid_until
+    )
+    .fetch_all(executor)
+    .await
+    .context("Failed to assign roles to user")?;
+
+    Ok(granted)
+}
+
+/// Roles that do not exist in the organization.
+#[instrument(name = "Validate roles", skip_all)]
+pub(crate) async fn find_unknown_roles(
+    pool: &PgPool,
+    organization_id: uuid::Uuid,
+    roles: &[uuid::Uuid],
+) -> Result<Vec<uuid::Uuid>, anyhow::Error> {
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT requested.role_id as "role_id!"
+        FROM unnest($1::uuid[]) as requested (role_id)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM roles as r
+            WHERE r.role_id = requested.role_id AND r.organization_id = $2
+        )
+        "#,
+        roles as &[uuid::Uuid],
+        organization_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to check if roles exist")?;
+
+    Ok(unknown)
+}
+
+/// `404` unless the project belongs to the organization.
+#[instrument(skip(pool))]
+async fn check_project_exists(
+    pool: &PgPool,
+    organization_id: uuid::Uuid,
+    project_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    let exists = sqlx::query_scalar!(
+        "SELECT 1 FROM projects WHERE project_id = $1 AND organization_id = $2",
+        project_id,
+        organization_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to check if project exists")
+    .map_err(AppError::E500)?;
+
+    match exists {
+        Some(_) => Ok(()),
+        None => Err(AppError::E404(anyhow::anyhow!(
+            "Project {project_id} not found"
+        ))),
+    }
+}
+
+/// Users outside the organization are reported as missing.
+#[instrument(skip_all)]
+pub(crate) async fn check_user_exists(
+    pool: &PgPool,
+    organization_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Result<bool, anyhow::Error> {
+    let exists = sqlx::query_scalar!(
+        "SELECT 1 FROM organizations_users WHERE organization_id = $1 AND user_id = $2",
+        organization_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to check if user exists")?;
+
+    Ok(exists.is_some())
+}
@@ -0,0 +1,8 @@
+pub mod archive;
+mod board;
+pub mod delete;
+pub mod get;
+pub mod models;
+pub mod patch;
+pub mod post;
+pub mod reorder;
@@ -0,0 +1,59 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::tasks::board;
+use crate::rbac_demo::tasks::models::{ArchiveTasks, ArchivedTasks, Task, TaskStatus};
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Archives every `done` task of the project, emptying its `done` column.
+#[instrument(name = "Archive completed tasks", skip(tenant, audit, app_state))]
+pub async fn archive_done_tasks(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ArchiveTasks>,
+) -> Result<Json<ArchivedTasks>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    board::lock_board(&mut transaction, tenant.organization_id, request.project_id).await?;
+    let archived = sqlx::query_as!(
+        Task,
+        r#"
+        UPDATE tasks SET archived = TRUE, updated_at = now()
+        WHERE project_id = $1 AND status = 'done' AND NOT archived
+        RETURNING task_id, project_id, title, status as "status: TaskStatus", assignee_id,
+            position, archived, created_at, updated_at
+        "#,
+        request.project_id
+    )
+    .fetch_all(&mut *transaction)
+    .await
+    .context("Failed to archive tasks")
+    .map_err(AppError::E500)?;
+
+    for task in &archived {
+        let event = AuditEvent::new("task.archive", "task", task.task_id).after(task);
+        audit
+            .record(&mut transaction, tenant, event)
+            .await
+            .map_err(AppError::E500)?;
+    }
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(ArchivedTasks {
+        archived: archived.into_iter().map(|task| task.task_id).collect(),
+    }))
+}
@@ -0,0 +1,124 @@
+use crate::errors::AppError;
+use crate::rbac_demo::projects::patch::lock_writable_project;
+use crate::rbac_demo::tasks::models::{Task, TaskStatus};
+use anyhow::Context;
+use sqlx::PgConnection;
+
+/// Locks the project of a board, serializing the changes to its positions.
+/// Tasks of archived projects are read-only.
+pub(super) async fn lock_board(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    project_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    lock_writable_project(connection, organization_id, project_id).await?;
+    Ok(())
+}
+
+/// The task, after locking its board.
+pub(super) async fn lock_task(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    task_id: uuid::Uuid,
+) -> Result<Task, AppError> {
+    let project_id = sqlx::query_scalar!(
+        "SELECT project_id FROM tasks WHERE task_id = $1 AND organization_id = $2",
+        task_id,
+        organization_id
+    )
+    .fetch_optional(&mut *connection)
+    .await
+    .context("Failed to fetch task")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Task {task_id} not found")))?;
+    lock_board(&mut *connection, organization_id, project_id).await?;
+
+    sqlx::query_as!(
+        Task,
+        r#"
+        SELECT task_id, project_id, title, status as "status: TaskStatus", assignee_id,
+            position, archived, created_at, updated_at
+        FROM tasks
+        WHERE task_id = $1
+        "#,
+        task_id
+    )
+    .fetch_one(connection)
+    .await
+    .context("Failed to fetch task")
+    .map_err(AppError::E500)
+}
+
+/// Assignees are members of the organization that are not deleted.
+pub(super) async fn check_assignee(
+    connection: &mut PgConnection,
+    organization_id: uuid::Uuid,
+    member_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT 1 FROM members
+        WHERE member_id = $1 AND organization_id = $2 AND deleted_at IS NULL
+        "#,
+        member_id,
+        organization_id
+    )
+    .fetch_optional(connection)
+    .await
+    .context("Failed to check the assignee")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Member {member_id} not found")))?;
+    Ok(())
+}
+
+/// Number of unarchived tasks in a column, leaving `except` out.
+pub(super) async fn column_len(
+    connection: &mut PgConnection,
+    project_id: uuid::Uuid,
+    status: TaskStatus,
+    except: Option<uuid::Uuid>,
+) -> Result<i32, AppError> {
+    let len = sqlx::query_scalar!(
+        r#"
+        SELECT count(*) as "len!" FROM tasks
+        WHERE project_id = $1 AND status = $2 AND NOT archived
+            AND task_id IS DISTINCT FROM $3
+        "#,
+        project_id,
+        status as TaskStatus,
+        except
+    )
+    .fetch_one(connection)
+    .await
+    .context("Failed to count the tasks of the column")
+    .map_err(AppError::E500)?;
+    Ok(len as i32)
+}
+
+/// Moves the unarchived tasks of a column at or after `position` by `offset`,
+/// leaving `task_id` out. Opens a gap with `1`, closes one with `-1`.
+pub(super) async fn shift(
+    connection: &mut PgConnection,
+    task: &Task,
+    status: TaskStatus,
+    position: i32,
+    offset: i32,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE tasks SET position = position + $5
+        WHERE project_id = $1 AND status = $2 AND NOT archived
+            AND position >= $3 AND task_id <> $4
+        "#,
+        task.project_id,
+        status as TaskStatus,
+        position,
+        task.task_id,
+        offset
+    )
+    .execute(connection)
+    .await
+    .context("Failed to shift tasks")
+    .map_err(AppError::E500)?;
+    Ok(())
+}
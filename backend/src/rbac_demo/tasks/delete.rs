@@ -0,0 +1,51 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::tasks::board;
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use std::sync::Arc;
+use tracing::instrument;
+
+#[instrument(name = "Delete a task", skip(tenant, audit, app_state))]
+pub async fn delete_task(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(task_id): Path<uuid::Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let task = board::lock_task(&mut transaction, tenant.organization_id, task_id).await?;
+    sqlx::query!("DELETE FROM tasks WHERE task_id = $1", task_id)
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to delete task")
+        .map_err(AppError::E500)?;
+    if !task.archived {
+        board::shift(&mut transaction, &task, task.status, task.position + 1, -1).await?;
+    }
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("task.delete", "task", task_id).before(&task),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
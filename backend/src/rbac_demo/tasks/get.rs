@@ -0,0 +1,105 @@
+use crate::app_states::AppState;
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::models::{ListRequest, ListResponse, Pagination};
+use crate::rbac_demo::tasks::models::{Task, TaskFilter, TaskStatus};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use serde_qs::axum::QsQuery;
+use sqlx::{Postgres, QueryBuilder};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Tasks of the organization in board order: by project, column, then
+/// position. Archived tasks are skipped unless asked for.
+#[instrument(name = "List all tasks", skip_all)]
+pub async fn list_tasks(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    QsQuery(request): QsQuery<ListRequest<TaskFilter>>,
+) -> Result<Json<ListResponse<Task>>, AppError> {
+    let filter = request.filter.unwrap_or_default();
+
+    let mut qb = scoped_query(
+        r#"
+        SELECT task_id, project_id, title, status, assignee_id, position, archived,
+            created_at, updated_at
+        "#,
+        tenant.organization_id,
+        &filter,
+    );
+    qb.push(
+        " ORDER BY project_id, array_position(ARRAY['todo', 'in_progress', 'done'], status), \
+        archived, position, created_at",
+    );
+    Pagination::to_query(request.current_page, request.page_size, &mut qb);
+
+    let tasks = qb
+        .build_query_as::<Task>()
+        .fetch_all(&app_state.pool)
+        .await
+        .context("Failed to fetch tasks")
+        .map_err(AppError::E500)?;
+
+    let total: i64 = scoped_query("SELECT count(*)", tenant.organization_id, &filter)
+        .build_query_scalar()
+        .fetch_one(&app_state.pool)
+        .await
+        .context("Failed to fetch tasks count")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(ListResponse {
+        results: tasks,
+        total: total as u64,
+        page: request.current_page,
+    }))
+}
+
+#[instrument(name = "Get a task", skip(tenant, app_state))]
+pub async fn get_task(
+    tenant: Tenant,
+    State(app_state): State<Arc<AppState>>,
+    Path(task_id): Path<uuid::Uuid>,
+) -> Result<Json<Task>, AppError> {
+    let task = sqlx::query_as!(
+        Task,
+        r#"
+        SELECT task_id, project_id, title, status as "status: TaskStatus", assignee_id,
+            position, archived, created_at, updated_at
+        FROM tasks
+        WHERE task_id = $1 AND organization_id = $2
+        "#,
+        task_id,
+        tenant.organization_id
+    )
+    .fetch_optional(&app_state.pool)
+    .await
+    .context("Failed to fetch task")
+    .map_err(AppError::E500)?
+    .ok_or_else(|| AppError::E404(anyhow::anyhow!("Task {task_id} not found")))?;
+
+    Ok(Json(task))
+}
+
+fn scoped_query<'a>(
+    select: &str,
+    organization_id: uuid::Uuid,
+    filter: &'a TaskFilter,
+) -> QueryBuilder<'a, Postgres> {
+    let mut qb = QueryBuilder::new(select);
+    qb.push(" FROM tasks WHERE organization_id = ")
+        .push_bind(organization_id);
+    if let Some(project_id) = filter.project_id {
+        qb.push(" AND project_id = ").push_bind(project_id);
+    }
+    if let Some(status) = filter.status {
+        qb.push(" AND status = ").push_bind(status);
+    }
+    if let Some(assignee_id) = filter.assignee_id {
+        qb.push(" AND assignee_id = ").push_bind(assignee_id);
+    }
+    if !filter.include_archived {
+        qb.push(" AND NOT archived");
+    }
+    qb
+}
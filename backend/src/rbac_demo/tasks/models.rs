@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// Column of the project board a task is in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum TaskStatus {
+    Todo,
+    InProgress,
+    Done,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Task {
+    pub task_id: uuid::Uuid,
+    pub project_id: uuid::Uuid,
+    pub title: String,
+    pub status: TaskStatus,
+    pub assignee_id: Option<uuid::Uuid>,
+    /// Index of the task in its status column, from 0.
+    pub position: i32,
+    pub archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// New tasks go to the end of their column, `todo` by default.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateTask {
+    pub project_id: uuid::Uuid,
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    pub status: Option<TaskStatus>,
+    pub assignee_id: Option<uuid::Uuid>,
+}
+
+/// Body of `PATCH /tasks/{id}`, absent fields are kept and an `assignee_id`
+/// of `null` unassigns the task. Tasks change column through
+/// `POST /tasks/{id}/move`.
+#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+pub struct PatchTask {
+    #[validate(length(min = 1, max = 255))]
+    pub title: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub assignee_id: Option<Option<uuid::Uuid>>,
+}
+
+/// Tells a `null` field apart from an absent one.
+fn present<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Target of `POST /tasks/{id}/move`. A `position` past the end of the
+/// column puts the task last.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MoveTask {
+    pub status: TaskStatus,
+    pub position: u32,
+}
+
+/// Body of `PUT /tasks/archive`, archives the `done` tasks of the project.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ArchiveTasks {
+    pub project_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ArchivedTasks {
+    pub archived: Vec<uuid::Uuid>,
+}
+
+/// Filter of `GET /tasks`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TaskFilter {
+    pub project_id: Option<uuid::Uuid>,
+    pub status: Option<TaskStatus>,
+    pub assignee_id: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub include_archived: bool,
+}
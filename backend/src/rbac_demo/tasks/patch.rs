@@ -0,0 +1,77 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::tasks::board;
+use crate::rbac_demo::tasks::models::{PatchTask, Task, TaskStatus};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+/// Retitles or reassigns a task.
+#[instrument(name = "Patch a task", skip(tenant, audit, app_state))]
+pub async fn patch_task(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(task_id): Path<uuid::Uuid>,
+    Json(request): Json<PatchTask>,
+) -> Result<Json<Task>, AppError> {
+    request
+        .validate()
+        .context("Invalid task")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before = board::lock_task(&mut transaction, tenant.organization_id, task_id).await?;
+    if let Some(Some(assignee_id)) = request.assignee_id {
+        board::check_assignee(&mut transaction, tenant.organization_id, assignee_id).await?;
+    }
+
+    let task = sqlx::query_as!(
+        Task,
+        r#"
+        UPDATE tasks
+        SET title = coalesce($2, title),
+            assignee_id = CASE WHEN $3 THEN $4 ELSE assignee_id END,
+            updated_at = now()
+        WHERE task_id = $1
+        RETURNING task_id, project_id, title, status as "status: TaskStatus", assignee_id,
+            position, archived, created_at, updated_at
+        "#,
+        task_id,
+        request.title,
+        request.assignee_id.is_some(),
+        request.assignee_id.flatten()
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to update task")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("task.update", "task", task_id)
+                .before(&before)
+                .after(&task),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(task))
+}
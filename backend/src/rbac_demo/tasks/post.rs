@@ -0,0 +1,78 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::tasks::board;
+use crate::rbac_demo::tasks::models::{CreateTask, Task, TaskStatus};
+use anyhow::Context;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::instrument;
+use validator::Validate;
+
+#[instrument(
+    name = "Create a new task",
+    skip(tenant, audit, app_state, request),
+    fields(project_id = %request.project_id)
+)]
+pub async fn create_task(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<CreateTask>,
+) -> Result<Json<Task>, AppError> {
+    request
+        .validate()
+        .context("Invalid task")
+        .map_err(AppError::E400)?;
+
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    board::lock_board(&mut transaction, tenant.organization_id, request.project_id).await?;
+    if let Some(assignee_id) = request.assignee_id {
+        board::check_assignee(&mut transaction, tenant.organization_id, assignee_id).await?;
+    }
+    let status = request.status.unwrap_or(TaskStatus::Todo);
+    let position = board::column_len(&mut transaction, request.project_id, status, None).await?;
+
+    let task = sqlx::query_as!(
+        Task,
+        r#"
+        INSERT INTO tasks (organization_id, project_id, title, status, assignee_id, position)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING task_id, project_id, title, status as "status: TaskStatus", assignee_id,
+            position, archived, created_at, updated_at
+        "#,
+        tenant.organization_id,
+        request.project_id,
+        request.title,
+        status as TaskStatus,
+        request.assignee_id,
+        position
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to create new task")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("task.create", "task", task.task_id).after(&task),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(task))
+}
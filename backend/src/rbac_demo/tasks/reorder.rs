@@ -0,0 +1,89 @@
+use crate::app_states::AppState;
+use crate::audit::{Audit, AuditEvent};
+use crate::authorization::Tenant;
+use crate::errors::AppError;
+use crate::rbac_demo::tasks::board;
+use crate::rbac_demo::tasks::models::{MoveTask, Task, TaskStatus};
+use anyhow::Context;
+use axum::extract::{Json, Path, State};
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Moves a task to `position` of the `status` column, within its column or
+/// to another one. The tasks after it close the gap it leaves, those at and
+/// after its new position make room.
+#[instrument(name = "Move a task", skip(tenant, audit, app_state))]
+pub async fn move_task(
+    tenant: Tenant,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Path(task_id): Path<uuid::Uuid>,
+    Json(request): Json<MoveTask>,
+) -> Result<Json<Task>, AppError> {
+    let mut transaction = app_state
+        .pool
+        .begin()
+        .await
+        .context("Failed to start transaction")
+        .map_err(AppError::E500)?;
+
+    let before = board::lock_task(&mut transaction, tenant.organization_id, task_id).await?;
+    if before.archived {
+        return Err(AppError::E409(anyhow::anyhow!(
+            "Task {task_id} is archived"
+        )));
+    }
+
+    board::shift(
+        &mut transaction,
+        &before,
+        before.status,
+        before.position + 1,
+        -1,
+    )
+    .await?;
+    let len = board::column_len(
+        &mut transaction,
+        before.project_id,
+        request.status,
+        Some(task_id),
+    )
+    .await?;
+    let position = i32::try_from(request.position).unwrap_or(i32::MAX).min(len);
+    board::shift(&mut transaction, &before, request.status, position, 1).await?;
+
+    let task = sqlx::query_as!(
+        Task,
+        r#"
+        UPDATE tasks SET status = $2, position = $3, updated_at = now()
+        WHERE task_id = $1
+        RETURNING task_id, project_id, title, status as "status: TaskStatus", assignee_id,
+            position, archived, created_at, updated_at
+        "#,
+        task_id,
+        request.status as TaskStatus,
+        position
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .context("Failed to move task")
+    .map_err(AppError::E500)?;
+
+    audit
+        .record(
+            &mut transaction,
+            tenant,
+            AuditEvent::new("task.move", "task", task_id)
+                .before(&before)
+                .after(&task),
+        )
+        .await
+        .map_err(AppError::E500)?;
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit transaction")
+        .map_err(AppError::E500)?;
+
+    Ok(Json(task))
+}
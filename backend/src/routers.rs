@@ -1,5 +1,6 @@
 mod admin;
 mod health_check;
+mod metrics;
 pub mod session_state;
 mod user;
 
@@ -13,9 +14,14 @@ use axum_session::{SessionLayer, SessionStore};
 use axum_session_redispool::SessionRedisPool;
 use sqlx::{Pool, Postgres};
 use tower_http::cors::{Any, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 
 use crate::app_states::AppState;
+use crate::audit::CheckpointSigner;
+use crate::authentication::{InvitationSigner, reject_anonymous_users};
+use crate::authorization::PermissionCache;
+use crate::email_client::EmailClient;
 use crate::rbac_demo;
 
 pub fn error_chain_fmt(
@@ -35,8 +41,19 @@ pub fn get_router(
     pool: Pool<Postgres>,
     base_url: String,
     session_store: SessionStore<SessionRedisPool>,
+    permission_cache: PermissionCache,
+    checkpoint_signer: Option<CheckpointSigner>,
+    email_client: EmailClient,
+    invitation_signer: Option<InvitationSigner>,
 ) -> axum::Router {
-    let app_state = Arc::new(AppState { pool, base_url });
+    let app_state = Arc::new(AppState {
+        pool,
+        base_url,
+        permission_cache,
+        checkpoint_signer,
+        email_client,
+        invitation_signer,
+    });
 
     // TODO: Restrict the origin to the frontend URL
     let cors = CorsLayer::new()
@@ -46,12 +63,19 @@ pub fn get_router(
 
     axum::Router::new()
         .route("/health", get(health_check::health_check))
+        .route("/metrics", get(metrics::metrics))
         .route("/login", post(user::login))
+        .route(
+            "/admin/password",
+            post(admin::update_password).layer(from_fn(reject_anonymous_users)),
+        )
         .nest("/rbac-demo", rbac_demo::router())
         .layer(TraceLayer::new_for_http())
+        // Audit events record the request id, see `Audit`.
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(cors)
         .layer(SessionLayer::new(session_store))
-        // .layer(from_fn(reject_anonymous_users))
         .layer(from_fn(log_app_errors))
         .with_state(app_state)
 }
@@ -0,0 +1,2 @@
+mod password_post;
+pub use password_post::*;
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::{
+    extract::{Extension, Form, State},
+    http::StatusCode,
+};
+use secrecy::{ExposeSecret, SecretString};
+use tracing::instrument;
+
+use crate::{
+    app_states::AppState,
+    audit::Audit,
+    authentication::{
+        AuthError, Credentials, PASSWORD_LENGTH, UserId, change_password, validate_credentials,
+    },
+    errors::AppError,
+};
+
+#[derive(serde::Deserialize)]
+pub struct PasswordForm {
+    pub current_password: SecretString,
+    pub new_password: SecretString,
+}
+
+/// Changes the password of the logged-in user, who has to give the current
+/// one again.
+#[instrument(name = "Change own password", skip_all, fields(user_id = %user_id))]
+pub async fn update_password(
+    Extension(user_id): Extension<UserId>,
+    audit: Audit,
+    State(app_state): State<Arc<AppState>>,
+    Form(form): Form<PasswordForm>,
+) -> Result<StatusCode, AppError> {
+    let username = sqlx::query_scalar!("SELECT username FROM users WHERE user_id = $1", *user_id)
+        .fetch_one(&app_state.pool)
+        .await
+        .context("Failed to load user")
+        .map_err(AppError::E500)?;
+    let credentials = Credentials {
+        username,
+        password: form.current_password,
+    };
+    validate_credentials(&app_state.pool, credentials)
+        .await
+        .map_err(|e| match e {
+            AuthError::InvalidCredentials(e) => AppError::E401(e),
+            AuthError::UnexpectedError(e) => AppError::E500(e),
+        })?;
+    if !PASSWORD_LENGTH.contains(&form.new_password.expose_secret().chars().count()) {
+        return Err(AppError::E400(anyhow::anyhow!(
+            "The password must have between {} and {} characters",
+            PASSWORD_LENGTH.start(),
+            PASSWORD_LENGTH.end()
+        )));
+    }
+
+    change_password(
+        user_id.into_inner(),
+        form.new_password,
+        &app_state.pool,
+        &audit,
+    )
+    .await
+    .map_err(AppError::E500)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
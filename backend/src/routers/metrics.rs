@@ -0,0 +1,39 @@
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use axum::extract::State;
+use tracing::instrument;
+
+use crate::app_states::AppState;
+
+/// Exposes counters in the Prometheus text format.
+#[instrument(name = "metrics", skip_all)]
+pub(crate) async fn metrics(State(app_state): State<Arc<AppState>>) -> String {
+    let cache = app_state.permission_cache.metrics();
+    let counters = [
+        (
+            "permission_cache_hits_total",
+            "Effective permissions served from the cache.",
+            cache.hits.load(Ordering::Relaxed),
+        ),
+        (
+            "permission_cache_misses_total",
+            "Effective permissions resolved from the database.",
+            cache.misses.load(Ordering::Relaxed),
+        ),
+        (
+            "permission_cache_invalidations_total",
+            "Cached effective permissions evicted after a change.",
+            cache.invalidations.load(Ordering::Relaxed),
+        ),
+    ];
+
+    let mut body = String::new();
+    for (name, help, value) in counters {
+        let _ = writeln!(body, "# HELP {name} {help}");
+        let _ = writeln!(body, "# TYPE {name} counter");
+        let _ = writeln!(body, "{name} {value}");
+    }
+    body
+}
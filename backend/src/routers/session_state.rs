@@ -4,10 +4,13 @@ use axum_session::Session;
 use axum_session_redispool::SessionRedisPool;
 use uuid::Uuid;
 
+use crate::errors::AppError;
+
 pub struct TypeSession(Session<SessionRedisPool>);
 
 impl TypeSession {
     const USER_ID_KEY: &'static str = "user_id";
+    const ORGANIZATION_ID_KEY: &'static str = "organization_id";
 
     pub fn renew(&self) {
         self.0.renew();
@@ -21,6 +24,21 @@ impl TypeSession {
         self.0.get::<Uuid>(Self::USER_ID_KEY)
     }
 
+    /// Organization the user works in, every `rbac_demo` query is scoped to it.
+    pub fn insert_organization_id(&self, organization_id: Uuid) {
+        self.0.set(Self::ORGANIZATION_ID_KEY, organization_id)
+    }
+
+    pub fn get_organization_id(&self) -> Option<Uuid> {
+        self.0.get::<Uuid>(Self::ORGANIZATION_ID_KEY)
+    }
+
+    /// User id of the session, `401` for anonymous callers.
+    pub fn require_user_id(&self) -> Result<Uuid, AppError> {
+        self.get_user_id()
+            .ok_or_else(|| AppError::E401(anyhow::anyhow!("Not logged in")))
+    }
+
     pub fn logout(&self) {
         self.0.destroy();
     }
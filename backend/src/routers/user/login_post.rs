@@ -10,6 +10,7 @@ use tracing::instrument;
 
 use crate::{
     app_states::AppState,
+    audit::{Actor, Audit, AuditEvent},
     authentication::{AuthError, validate_credentials},
     routers::{error_chain_fmt, session_state::TypeSession},
 };
@@ -22,7 +23,7 @@ pub struct LoginForm {
 
 #[instrument(
     name = "User login",
-    skip(session, app_state, form),
+    skip(session, audit, app_state, form),
     fields(
         username=tracing::field::Empty,
         user_id=tracing::field::Empty
@@ -30,6 +31,7 @@ pub struct LoginForm {
 )]
 pub async fn login(
     session: TypeSession,
+    audit: Audit,
     State(app_state): State<Arc<AppState>>,
     axum::extract::Form(form): axum::extract::Form<LoginForm>,
 ) -> Result<response::Response, LoginError> {
@@ -39,10 +41,34 @@ pub async fn login(
     };
 
     tracing::Span::current().record("username", tracing::field::display(&_credentials.username));
+    let username = _credentials.username.clone();
 
     match validate_credentials(&app_state.pool, _credentials).await {
         Ok(user_id) => {
+            let mut connection = app_state
+                .pool
+                .acquire()
+                .await
+                .map_err(|e| LoginError::UnexpectedError(e.into()))?;
+            audit
+                .record(
+                    &mut connection,
+                    Actor::user(user_id),
+                    AuditEvent::new("auth.login", "user", user_id),
+                )
+                .await?;
             session.insert_user_id(user_id);
+            // Users of a single organization need not pick one.
+            let organizations = sqlx::query_scalar!(
+                "SELECT organization_id FROM organizations_users WHERE user_id = $1",
+                user_id
+            )
+            .fetch_all(&app_state.pool)
+            .await
+            .map_err(|e| LoginError::UnexpectedError(e.into()))?;
+            if let [organization_id] = organizations[..] {
+                session.insert_organization_id(organization_id);
+            }
             // prevent session fixation attacks
             session.renew();
 
@@ -52,6 +78,9 @@ pub async fn login(
             Ok(response::Redirect::to("/admin/dashboard").into_response())
         }
         Err(e) => {
+            if let AuthError::InvalidCredentials(_) = e {
+                record_failed_login(&app_state, &audit, &username).await?;
+            }
             let e = match e {
                 AuthError::InvalidCredentials(_) => LoginError::AuthError(e.into()),
                 AuthError::UnexpectedError(_) => LoginError::UnexpectedError(e.into()),
@@ -62,6 +91,37 @@ pub async fn login(
     }
 }
 
+/// Attributed to the user when the username exists, the password being the
+/// wrong one. Pending invitees have no password yet and stay anonymous, an
+/// event naming them would keep their invitation from being revoked.
+async fn record_failed_login(
+    app_state: &AppState,
+    audit: &Audit,
+    username: &str,
+) -> Result<(), LoginError> {
+    let mut connection = app_state
+        .pool
+        .acquire()
+        .await
+        .map_err(|e| LoginError::UnexpectedError(e.into()))?;
+    let user_id = sqlx::query_scalar!(
+        "SELECT user_id FROM users WHERE username = $1 AND password_hash IS NOT NULL",
+        username
+    )
+    .fetch_optional(&mut *connection)
+    .await
+    .map_err(|e| LoginError::UnexpectedError(e.into()))?;
+    let actor = user_id.map(Actor::user).unwrap_or_else(Actor::anonymous);
+    audit
+        .record(
+            &mut connection,
+            actor,
+            AuditEvent::new("auth.login_failed", "user", username),
+        )
+        .await?;
+    Ok(())
+}
+
 #[derive(thiserror::Error)]
 pub enum LoginError {
     #[error("Authentication failed")]
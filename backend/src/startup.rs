@@ -1,16 +1,30 @@
-use axum::Router;
-use axum::routing::IntoMakeService;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum::middleware::AddExtension;
 use axum::serve::Serve;
+use axum::{Extension, Router};
 use axum_session::{SessionConfig, SessionStore};
 use axum_session_redispool::SessionRedisPool;
-use redis_pool::RedisPool;
+use redis_pool::{RedisPool, SingleRedisPool};
 use secrecy::ExposeSecret;
 use sqlx::PgPool;
 
+use crate::audit::{self, CheckpointSigner, TrustedProxies};
+use crate::authentication::InvitationSigner;
+use crate::authorization::{self, PermissionCache};
+use crate::bootstrap;
 use crate::configuration::Settings;
+use crate::email_client::EmailClient;
 use crate::routers;
 
-type Server = Serve<tokio::net::TcpListener, IntoMakeService<Router>, Router>;
+type Server = Serve<
+    tokio::net::TcpListener,
+    IntoMakeServiceWithConnectInfo<Router, SocketAddr>,
+    AddExtension<Router, ConnectInfo<SocketAddr>>,
+>;
 
 pub struct Application {
     port: u16,
@@ -19,18 +33,64 @@ pub struct Application {
 
 impl Application {
     pub async fn build(settings: Settings) -> Result<Self, std::io::Error> {
-        let addr =
-            std::net::SocketAddr::from((settings.app_settings.host, settings.app_settings.port));
+        let addr = SocketAddr::from((settings.app_settings.host, settings.app_settings.port));
         let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
 
         let db_url = settings.database.get_connection();
         let pool = PgPool::connect_lazy(&db_url).expect("Failed to connect to the database");
 
-        let session_store =
-            Self::get_redis_store(settings.app_settings.redis_url.expose_secret()).await;
+        bootstrap::run(&pool, &settings.bootstrap)
+            .await
+            .map_err(std::io::Error::other)?;
+
+        let redis_pool = Self::get_redis_pool(settings.app_settings.redis_url.expose_secret());
+        let session_store = Self::get_redis_store(redis_pool.clone()).await;
+
+        let permission_cache = PermissionCache::new(redis_pool, &settings.permission_cache);
+        if permission_cache.is_enabled() {
+            tokio::spawn(authorization::invalidate_on_changes(
+                pool.clone(),
+                permission_cache.clone(),
+            ));
+        }
+
+        tokio::spawn(authorization::run_expiry(
+            pool.clone(),
+            Duration::from_secs(settings.role_grants.purge_interval_seconds.get()),
+        ));
 
-        let app = routers::get_router(pool, settings.app_settings.base_url, session_store);
-        let server = axum::serve(listener, app.into_make_service());
+        let checkpoint_signer = settings.audit.signing_key.map(CheckpointSigner::new);
+        if let Some(signer) = &checkpoint_signer {
+            tokio::spawn(audit::run_checkpoints(
+                pool.clone(),
+                signer.clone(),
+                Duration::from_secs(settings.audit.checkpoint_interval_seconds.get()),
+            ));
+        }
+
+        let email_client = EmailClient::new(&settings.email_client);
+        let invitation_signer = settings
+            .invitations
+            .signing_key
+            .map(|key| InvitationSigner::new(key, settings.invitations.expiry_hours))
+            .transpose()
+            .map_err(std::io::Error::other)?;
+
+        let app = routers::get_router(
+            pool,
+            settings.app_settings.base_url,
+            session_store,
+            permission_cache,
+            checkpoint_signer,
+            email_client,
+            invitation_signer,
+        )
+        .layer(Extension(TrustedProxies(settings.audit.trusted_proxies)));
+        // The peer address is the ip recorded by audit events without a proxy.
+        let server = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        );
 
         Ok(Self {
             port: server.local_addr()?.port(),
@@ -38,13 +98,16 @@ impl Application {
         })
     }
 
-    async fn get_redis_store(redis_url: &str) -> SessionStore<SessionRedisPool> {
+    fn get_redis_pool(redis_url: &str) -> SingleRedisPool {
         let client = redis::Client::open(redis_url)
             .expect("Error while trying to open the redis connection");
-        let redis_pool = RedisPool::from(client);
+        RedisPool::from(client)
+    }
+
+    async fn get_redis_store(redis_pool: SingleRedisPool) -> SessionStore<SessionRedisPool> {
         let session_config = SessionConfig::default();
 
-        SessionStore::<SessionRedisPool>::new(Some(redis_pool.clone().into()), session_config)
+        SessionStore::<SessionRedisPool>::new(Some(redis_pool.into()), session_config)
             .await
             .expect("Failed to create redis session store.")
     }
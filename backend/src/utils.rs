@@ -1,9 +1,11 @@
 pub mod db {
     use crate::models::Filter;
     use anyhow::Context;
+    use axum::body::Bytes;
+    use futures::Stream;
     use serde::Serialize;
-    use sqlx::PgPool;
-    use sqlx::QueryBuilder;
+    use sqlx::postgres::PgRow;
+    use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
 
     pub async fn count<T>(table: &str, filter: T, pool: &PgPool) -> Result<i64, anyhow::Error>
     where
@@ -19,4 +21,142 @@ pub mod db {
             .await
             .context(format!("Failed to fetch count for table {}", table))
     }
+
+    /// Like [`count`], restricted to the rows of one organization.
+    pub async fn count_in_tenant<T>(
+        table: &str,
+        organization_id: uuid::Uuid,
+        filter: T,
+        pool: &PgPool,
+    ) -> Result<i64, anyhow::Error>
+    where
+        T: Serialize,
+    {
+        let mut qb = QueryBuilder::new("SELECT count(*) FROM (SELECT * FROM ");
+        qb.push(table)
+            .push(" WHERE organization_id = ")
+            .push_bind(organization_id)
+            .push(") as scoped");
+
+        Filter::to_query(&mut qb, &filter);
+
+        qb.build_query_scalar()
+            .fetch_one(pool)
+            .await
+            .context(format!("Failed to fetch count for table {}", table))
+    }
+
+    pub fn is_unique_violation(e: &sqlx::Error) -> bool {
+        matches!(e, sqlx::Error::Database(db_err) if db_err.is_unique_violation())
+    }
+
+    /// Streams the rows of `cursor`, declared by `declare`, `batch_size` at a
+    /// time. `encode` turns each batch into a chunk, told whether it is the
+    /// first. The cursor lives in a transaction that ends with the stream, so
+    /// only one batch is held in memory at a time.
+    pub async fn stream_cursor<T, F>(
+        pool: &PgPool,
+        cursor: &'static str,
+        mut declare: QueryBuilder<'_, Postgres>,
+        batch_size: usize,
+        encode: F,
+    ) -> Result<impl Stream<Item = Result<Bytes, anyhow::Error>> + Send + 'static, anyhow::Error>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+        F: FnMut(&[T], bool) -> Result<Vec<u8>, anyhow::Error> + Send + 'static,
+    {
+        let mut transaction = pool.begin().await.context("Failed to start transaction")?;
+        declare
+            .build()
+            .execute(&mut *transaction)
+            .await
+            .with_context(|| format!("Failed to declare cursor {cursor}"))?;
+
+        let fetch = format!("FETCH {batch_size} FROM {cursor}");
+        let state = Some((transaction, encode, true));
+        Ok(futures::stream::try_unfold(state, move |state| {
+            let fetch = fetch.clone();
+            async move {
+                let Some((mut transaction, mut encode, first)) = state else {
+                    return Ok(None);
+                };
+                let rows = sqlx::query_as::<_, T>(&fetch)
+                    .fetch_all(&mut *transaction)
+                    .await
+                    .with_context(|| format!("Failed to fetch from cursor {cursor}"))?;
+
+                let chunk = encode(&rows, first)?;
+                let next = if rows.len() < batch_size {
+                    transaction
+                        .commit()
+                        .await
+                        .with_context(|| format!("Failed to close cursor {cursor}"))?;
+                    None
+                } else {
+                    Some((transaction, encode, false))
+                };
+                Ok(Some((Bytes::from(chunk), next)))
+            }
+        }))
+    }
+}
+
+pub mod csv {
+    use anyhow::Context;
+    use axum::body::Body;
+    use axum::http::header;
+    use axum::response::{IntoResponse, Response};
+    use serde::Serialize;
+    use serde_json::Value;
+    use std::borrow::Cow;
+
+    /// `text` as a cell spreadsheets will not evaluate. A leading `=`, `+`,
+    /// `-`, `@`, tab or carriage return starts a formula, so such text is
+    /// prefixed with `'`.
+    pub fn cell(text: &str) -> Cow<'_, str> {
+        if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+            Cow::Owned(format!("'{text}"))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// A chunk of a CSV export, led by the header when `first`. `columns`
+    /// names the fields of `T` written, in order. Text goes through [`cell`],
+    /// nested values are written as JSON.
+    pub fn encode<T>(columns: &[&str], records: &[T], first: bool) -> Result<Vec<u8>, anyhow::Error>
+    where
+        T: Serialize,
+    {
+        let mut writer = ::csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::new());
+        if first {
+            writer.write_record(columns)?;
+        }
+        for record in records {
+            let record = serde_json::to_value(record)?;
+            writer.write_record(columns.iter().map(|column| match &record[column] {
+                Value::Null => String::new(),
+                Value::String(text) => cell(text).into_owned(),
+                value => value.to_string(),
+            }))?;
+        }
+        writer.into_inner().context("Failed to write CSV")
+    }
+
+    /// `body` as a CSV attachment.
+    pub fn attachment(filename: &str, body: Body) -> Response {
+        (
+            [
+                (header::CONTENT_TYPE, "text/csv".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{filename}\""),
+                ),
+            ],
+            body,
+        )
+            .into_response()
+    }
 }
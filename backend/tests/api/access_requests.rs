@@ -0,0 +1,291 @@
+use crate::helper::{TestApp, insert_roles, spawn_app};
+use axum::http::StatusCode;
+use backend::rbac_demo::rbac::access_requests::models::{AccessRequest, AccessRequestStatus};
+use backend::rbac_demo::rbac::roles::models::Role;
+use backend::rbac_demo::rbac::users::models::RoleGrant;
+use serde_json::json;
+
+/// A role with a freshly created approver, whose client is returned.
+async fn role_with_approver(app: &TestApp) -> (Role, reqwest::Client) {
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let (approver, client) = app.login_new_user().await;
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/approvers/add",
+            &app.address, role.role_id
+        ))
+        .json(&[approver.user_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    (role, client)
+}
+
+async fn request_role(app: &TestApp, role: &Role) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo/access-requests", &app.address))
+        .json(&json!({
+            "role_id": role.role_id,
+            "reason": "audit of Q3",
+        }))
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+async fn decide(
+    app: &TestApp,
+    client: &reqwest::Client,
+    request: &AccessRequest,
+    decision: &str,
+) -> reqwest::Response {
+    client
+        .post(format!(
+            "{}/rbac-demo/access-requests/{}/{}",
+            &app.address, request.access_request_id, decision
+        ))
+        .json(&json!({ "comment": "ok" }))
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+#[tokio::test]
+async fn approving_a_request_grants_the_role() {
+    let app = spawn_app().await;
+    let (role, approver) = role_with_approver(&app).await;
+
+    let response = request_role(&app, &role).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let request = response
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(request.status, AccessRequestStatus::Pending);
+    assert_eq!(request.requester_id, app.test_user.user_id);
+
+    let response = decide(&app, &approver, &request, "approve").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let decided = response
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(decided.status, AccessRequestStatus::Approved);
+    assert_eq!(decided.granted_role_id, Some(role.role_id));
+
+    let grants = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/users/{}/roles",
+            &app.address, app.test_user.user_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<Vec<RoleGrant>>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].role_id, role.role_id);
+}
+
+#[tokio::test]
+async fn anonymous_callers_cannot_request_access() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/rbac-demo/access-requests", &app.address))
+        .json(&json!({
+            "role_id": role.role_id,
+            "reason": "audit of Q3",
+        }))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn a_second_pending_request_returns_409() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    request_role(&app, &role).await;
+
+    let response = request_role(&app, &role).await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn only_approvers_of_the_role_can_decide() {
+    let app = spawn_app().await;
+    let (role, _) = role_with_approver(&app).await;
+    let (_, stranger) = app.login_new_user().await;
+    let request = request_role(&app, &role)
+        .await
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+
+    let response = decide(&app, &stranger, &request, "approve").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = decide(&app, &app.api_client, &request, "approve").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn decided_requests_cannot_change() {
+    let app = spawn_app().await;
+    let (role, approver) = role_with_approver(&app).await;
+    let request = request_role(&app, &role)
+        .await
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+
+    let response = decide(&app, &approver, &request, "reject").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = decide(&app, &approver, &request, "approve").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/access-requests/{}/cancel",
+            &app.address, request.access_request_id
+        ))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn requesters_can_cancel_pending_requests() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let request = request_role(&app, &role)
+        .await
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/access-requests/{}/cancel",
+            &app.address, request.access_request_id
+        ))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let cancelled = response
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(cancelled.status, AccessRequestStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn overdue_requests_expire() {
+    let app = spawn_app().await;
+    let (role, approver) = role_with_approver(&app).await;
+    let request = request_role(&app, &role)
+        .await
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+    sqlx::query!(
+        "UPDATE access_requests SET expires_at = now() - interval '1 minute' WHERE access_request_id = $1",
+        request.access_request_id
+    )
+    .execute(&app.pool)
+    .await
+    .expect("Failed to backdate access request");
+
+    let response = decide(&app, &approver, &request, "approve").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let expired = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/access-requests/{}",
+            &app.address, request.access_request_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(expired.status, AccessRequestStatus::Expired);
+}
+
+#[tokio::test]
+async fn component_requests_are_granted_through_a_bound_role() {
+    let app = spawn_app().await;
+    let (role, approver) = role_with_approver(&app).await;
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&["reports.export"])
+        .send()
+        .await
+        .expect("Failed to post request");
+    let component_id =
+        sqlx::query_scalar!("SELECT component_id FROM components WHERE code = 'reports.export'")
+            .fetch_one(&app.pool)
+            .await
+            .expect("Failed to fetch component");
+
+    let request = app
+        .api_client
+        .post(format!("{}/rbac-demo/access-requests", &app.address))
+        .json(&json!({
+            "component_id": component_id,
+            "reason": "monthly export",
+        }))
+        .send()
+        .await
+        .expect("Failed to post request")
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+
+    let response = decide(&app, &approver, &request, "approve").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = approver
+        .post(format!(
+            "{}/rbac-demo/access-requests/{}/approve",
+            &app.address, request.access_request_id
+        ))
+        .json(&json!({ "role_id": role.role_id }))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let decided = response
+        .json::<AccessRequest>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(decided.granted_role_id, Some(role.role_id));
+}
@@ -0,0 +1,476 @@
+use crate::helper::{TestApp, TestUser, assert_is_redirect_to, create_organization, spawn_app};
+use axum::http::StatusCode;
+use backend::audit::models::{
+    AuditCheckpoint, AuditColumnFilter, AuditEventFilter, AuditExportQuery, AuditLogEntry,
+    BreakReason, ChainReport, ExportFormat,
+};
+use backend::audit::{CheckpointSigner, create_checkpoints};
+use backend::models::{ListRequest, ListResponse};
+use backend::rbac_demo::rbac::roles::models::Role;
+use serde_json::json;
+
+async fn list_events(app: &TestApp, filter: AuditEventFilter) -> ListResponse<AuditLogEntry> {
+    let request = ListRequest {
+        filter: Some(filter),
+        current_page: 1,
+        page_size: 50,
+    };
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/audit-events?{}",
+            &app.address,
+            serde_qs::to_string(&request).unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to get audit events");
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+async fn verify_chain(app: &TestApp) -> ChainReport {
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/audit-events/verify", &app.address))
+        .send()
+        .await
+        .expect("Failed to verify audit events");
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+async fn create_roles(app: &TestApp, names: &[&str]) {
+    for name in names {
+        let response = app
+            .api_client
+            .post(format!("{}/rbac-demo/roles", &app.address))
+            .json(&json!({ "name": name, "description": "" }))
+            .send()
+            .await
+            .expect("Failed to post role");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn mutations_record_who_did_what_from_where() {
+    let app = spawn_app().await;
+
+    let role = app
+        .api_client
+        .post(format!("{}/rbac-demo/roles", &app.address))
+        .header("x-request-id", "req-42")
+        // No proxy is trusted, the header is ignored.
+        .header("x-forwarded-for", "203.0.113.7")
+        .json(&json!({ "name": "editor", "description": "Edits projects" }))
+        .send()
+        .await
+        .expect("Failed to post role")
+        .json::<Role>()
+        .await
+        .unwrap();
+
+    let events = list_events(
+        &app,
+        AuditEventFilter {
+            columns: AuditColumnFilter {
+                action: Some("role.create".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(events.total, 1);
+    let event = &events.results[0];
+    assert_eq!(event.actor_id, Some(app.test_user.user_id));
+    assert_eq!(event.organization_id, Some(app.organization_id));
+    assert_eq!(event.target_type, "role");
+    assert_eq!(event.target_id, Some(role.role_id.to_string()));
+    assert!(event.before.is_none());
+    assert_eq!(event.after.as_ref().unwrap()["name"], "editor");
+    assert_eq!(event.request_id.as_deref(), Some("req-42"));
+    assert_eq!(event.ip.as_deref(), Some("127.0.0.1"));
+
+    let response = app
+        .api_client
+        .delete(format!(
+            "{}/rbac-demo/projects/{}",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to delete project");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let events = list_events(
+        &app,
+        AuditEventFilter {
+            columns: AuditColumnFilter {
+                target_type: Some("project".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(events.total, 0);
+}
+
+#[tokio::test]
+async fn failed_mutations_record_nothing() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    let permission = json!({ "resource": "report", "action": "export", "scope": "*" });
+
+    for expected in [StatusCode::OK, StatusCode::CONFLICT] {
+        let response = app
+            .api_client
+            .post(format!("{}/rbac-demo/permissions", &app.address))
+            .json(&permission)
+            .send()
+            .await
+            .expect("Failed to post permission");
+        assert_eq!(response.status(), expected);
+    }
+
+    let events = list_events(
+        &app,
+        AuditEventFilter {
+            columns: AuditColumnFilter {
+                action: Some("permission.create".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(events.total, 1);
+}
+
+#[tokio::test]
+async fn logins_are_recorded() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_login(&json!({
+            "username": app.test_user.username,
+            "password": "wrong-password",
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    app.post_login(&json!({ "username": "nobody", "password": "secret" }))
+        .await;
+    app.login().await;
+
+    let filter = AuditEventFilter {
+        actor_id: Some(app.test_user.user_id),
+        ..Default::default()
+    };
+    let events = list_events(&app, filter).await;
+    let actions = events
+        .results
+        .iter()
+        .map(|e| e.action.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(actions, ["auth.login", "auth.login_failed", "auth.login"]);
+    assert!(events.results.iter().all(|e| e.organization_id.is_none()));
+
+    // Unknown usernames are recorded without an actor, listed to nobody.
+    let unknown = sqlx::query_scalar!(
+        "SELECT count(*) FROM audit_events WHERE target_id = 'nobody' AND actor_id IS NULL"
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(unknown, Some(1));
+}
+
+#[tokio::test]
+async fn password_changes_are_recorded() {
+    let app = spawn_app().await;
+    let form = json!({
+        "current_password": app.test_user.password,
+        "new_password": "correct horse battery",
+    });
+    let anonymous = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .build()
+        .unwrap();
+    let response = anonymous
+        .post(format!("{}/admin/password", &app.address))
+        .form(&form)
+        .send()
+        .await
+        .unwrap();
+    assert_is_redirect_to(&response, "/login");
+
+    let response = app
+        .post_change_password(&json!({
+            "current_password": "wrong-password",
+            "new_password": "correct horse battery",
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = app.post_change_password(&form).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .post_login(&json!({
+            "username": app.test_user.username,
+            "password": "correct horse battery",
+        }))
+        .await;
+    assert_is_redirect_to(&response, "/admin/dashboard");
+    let filter = AuditEventFilter {
+        actor_id: Some(app.test_user.user_id),
+        ..Default::default()
+    };
+    let actions = list_events(&app, filter)
+        .await
+        .results
+        .into_iter()
+        .map(|e| e.action)
+        .collect::<Vec<_>>();
+    assert!(actions.contains(&"auth.password_change".to_string()));
+}
+
+#[tokio::test]
+async fn events_are_listed_to_their_organization_only() {
+    let app = spawn_app().await;
+    let outsider = TestUser::generate();
+    outsider.store(&app.pool).await;
+    create_organization(&app.pool, &outsider).await;
+
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .no_proxy()
+        .build()
+        .unwrap();
+    client
+        .post(format!("{}/login", &app.address))
+        .form(&json!({ "username": outsider.username, "password": outsider.password }))
+        .send()
+        .await
+        .expect("Failed to log in");
+    client
+        .post(format!("{}/rbac-demo/members", &app.address))
+        .json(&json!({ "first_name": "Ada", "last_name": "Lovelace" }))
+        .send()
+        .await
+        .expect("Failed to post member");
+
+    let events = list_events(&app, AuditEventFilter::default()).await;
+    let outsider_id = Some(outsider.user_id);
+    assert!(events.results.iter().all(|e| e.actor_id != outsider_id));
+    assert_eq!(events.total, events.results.len() as u64);
+}
+
+#[tokio::test]
+async fn system_admins_verify_the_chain_outside_organizations() {
+    let app = spawn_app().await;
+    let url = format!("{}/rbac-demo/audit-events/system/verify", &app.address);
+    let response = app.api_client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    app.make_system_admin().await;
+    let response = app.api_client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let report = response.json::<ChainReport>().await.unwrap();
+    assert_eq!(report.chain_id, uuid::Uuid::nil());
+    assert!(report.first_break.is_none());
+    // The login of the test user.
+    assert!(report.events > 0);
+}
+
+#[tokio::test]
+async fn chain_reports_the_first_altered_event() {
+    let app = spawn_app().await;
+    create_roles(&app, &["editor", "viewer", "auditor"]).await;
+
+    let report = verify_chain(&app).await;
+    assert!(report.first_break.is_none());
+    assert_eq!(report.events, 3);
+
+    let update = sqlx::query("UPDATE audit_events SET after = '{}' WHERE chain_id = $1")
+        .bind(app.organization_id)
+        .execute(&app.pool)
+        .await;
+    assert!(update.is_err(), "audit events are append-only");
+
+    // Bypass the triggers, as someone with direct access to the database.
+    let mut transaction = app.pool.begin().await.unwrap();
+    sqlx::query("SET LOCAL session_replication_role = replica")
+        .execute(&mut *transaction)
+        .await
+        .unwrap();
+    let event_id = sqlx::query_scalar!(
+        r#"
+        UPDATE audit_events SET after = jsonb_set(after, '{name}', '"admin"')
+        WHERE chain_id = $1 AND sequence = 2
+        RETURNING event_id
+        "#,
+        app.organization_id
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .unwrap();
+    transaction.commit().await.unwrap();
+
+    let report = verify_chain(&app).await;
+    assert_eq!(report.events, 1);
+    let first_break = report.first_break.expect("Tampering went unnoticed");
+    assert_eq!(first_break.sequence, 2);
+    assert_eq!(first_break.event_id, Some(event_id));
+    assert_eq!(first_break.reason, BreakReason::ContentAltered);
+}
+
+#[tokio::test]
+async fn checkpoints_are_exported_signed() {
+    let app = spawn_app().await;
+    let signer = CheckpointSigner::new(
+        backend::configuration::get_config()
+            .unwrap()
+            .audit
+            .signing_key
+            .expect("The local configuration has a signing key"),
+    );
+    create_roles(&app, &["editor", "viewer"]).await;
+    create_checkpoints(&app.pool, &signer).await.unwrap();
+    assert_eq!(create_checkpoints(&app.pool, &signer).await.unwrap(), 0);
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/audit-events/checkpoints/export",
+            &app.address
+        ))
+        .send()
+        .await
+        .expect("Failed to export checkpoints");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .starts_with("attachment")
+    );
+    let checkpoints = response.json::<Vec<AuditCheckpoint>>().await.unwrap();
+    assert_eq!(checkpoints.len(), 1);
+    let checkpoint = &checkpoints[0];
+    assert_eq!(checkpoint.chain_id, app.organization_id);
+    assert_eq!(checkpoint.sequence, 2);
+    assert!(signer.verify(
+        checkpoint.chain_id,
+        checkpoint.sequence,
+        &hex::decode(&checkpoint.hash).unwrap(),
+        &hex::decode(&checkpoint.signature).unwrap(),
+    ));
+
+    let report = verify_chain(&app).await;
+    assert_eq!(report.checkpoints, 1);
+    assert!(report.first_break.is_none());
+
+    // A checkpoint forged without the key.
+    create_roles(&app, &["auditor"]).await;
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_checkpoints (chain_id, sequence, hash, signature)
+        SELECT chain_id, sequence, hash, '\x00' FROM audit_chain_heads WHERE chain_id = $1
+        "#,
+        app.organization_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let first_break = verify_chain(&app).await.first_break.unwrap();
+    assert_eq!(first_break.sequence, 3);
+    assert_eq!(first_break.reason, BreakReason::InvalidSignature);
+}
+
+async fn export_events(app: &TestApp, query: &AuditExportQuery) -> String {
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/audit-events/export", &app.address))
+        .query(query)
+        .send()
+        .await
+        .expect("Failed to export audit events");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        query.format.content_type()
+    );
+    response.text().await.unwrap()
+}
+
+#[tokio::test]
+async fn export_streams_every_event_oldest_first() {
+    let app = spawn_app().await;
+    create_roles(&app, &["editor"]).await;
+    // More events than fit in one batch of the cursor.
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_events (organization_id, action, target_type, target_id, occurred_at)
+        SELECT $1, 'bulk.test', 'item', i::text, now() + make_interval(secs => i)
+        FROM generate_series(1, 1200) as i
+        "#,
+        app.organization_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let ndjson = export_events(&app, &AuditExportQuery::default()).await;
+    let events = ndjson
+        .lines()
+        .map(|line| serde_json::from_str::<AuditLogEntry>(line).unwrap())
+        .collect::<Vec<_>>();
+    // The login of the test user, the role, then the bulk.
+    assert_eq!(events.len(), 1202);
+    assert_eq!(events[1].action, "role.create");
+    assert!(
+        events
+            .windows(2)
+            .all(|w| w[0].occurred_at <= w[1].occurred_at)
+    );
+    assert_eq!(events[1201].target_id.as_deref(), Some("1200"));
+
+    let csv = export_events(
+        &app,
+        &AuditExportQuery {
+            format: ExportFormat::Csv,
+            since: Some(events[1001].occurred_at),
+        },
+    )
+    .await;
+    let mut lines = csv.lines();
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with("event_id,organization_id,actor_id,action")
+    );
+    assert_eq!(lines.count(), 201);
+}
+
+#[tokio::test]
+async fn empty_csv_export_has_a_header() {
+    let app = spawn_app().await;
+    create_roles(&app, &["editor"]).await;
+
+    let query = AuditExportQuery {
+        format: ExportFormat::Csv,
+        since: Some(chrono::Utc::now() + chrono::Duration::days(1)),
+    };
+    let csv = export_events(&app, &query).await;
+    assert_eq!(csv.lines().count(), 1);
+
+    let query = AuditExportQuery {
+        format: ExportFormat::Ndjson,
+        ..query
+    };
+    assert!(export_events(&app, &query).await.is_empty());
+}
@@ -0,0 +1,217 @@
+use crate::helper::{TestApp, insert_roles, spawn_app};
+use axum::http::StatusCode;
+use backend::rbac_demo::rbac::authz::models::AuthzDecision;
+use serde_json::json;
+
+async fn check(app: &TestApp, query: &[(&str, String)]) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/rbac-demo/authz/check", &app.address))
+        .query(query)
+        .send()
+        .await
+        .expect("Failed to get request")
+}
+
+async fn is_allowed(app: &TestApp, resource: &str, action: &str, scope: &str) -> bool {
+    let query = [
+        ("resource", resource.to_string()),
+        ("action", action.to_string()),
+        ("scope", scope.to_string()),
+    ];
+    check(app, &query)
+        .await
+        .json::<AuthzDecision>()
+        .await
+        .expect("Failed to parse response body")
+        .allowed
+}
+
+#[tokio::test]
+async fn wildcard_actions_cover_every_action() {
+    let app = spawn_app().await;
+    app.grant_permission("project", "*", "team").await;
+
+    assert!(is_allowed(&app, "project", "read", "team").await);
+    assert!(is_allowed(&app, "project", "delete", "team").await);
+    assert!(!is_allowed(&app, "project", "read", "*").await);
+    assert!(!is_allowed(&app, "member", "read", "team").await);
+}
+
+#[tokio::test]
+async fn resources_cover_their_children() {
+    let app = spawn_app().await;
+    app.grant_permission("project", "read", "*").await;
+
+    assert!(is_allowed(&app, "project/tasks", "read", "*").await);
+    assert!(is_allowed(&app, "project/tasks/comments", "read", "self").await);
+    assert!(!is_allowed(&app, "projects", "read", "*").await);
+}
+
+#[tokio::test]
+async fn matched_permissions_are_reported() {
+    let app = spawn_app().await;
+    app.grant_permission("project/*", "read", "*").await;
+    app.grant_permission("*", "read", "*").await;
+
+    let query = [
+        ("resource", "project/tasks".to_string()),
+        ("action", "read".to_string()),
+        ("scope", "*".to_string()),
+    ];
+    let decision = check(&app, &query)
+        .await
+        .json::<AuthzDecision>()
+        .await
+        .expect("Failed to parse response body");
+
+    assert!(decision.allowed);
+    assert_eq!(decision.matched.len(), 2);
+}
+
+#[tokio::test]
+async fn project_grants_are_checked_on_their_project_only() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let project_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO projects (project_id, name, description, organization_id)
+        VALUES (gen_random_uuid(), 'apollo', '', $1)
+        RETURNING project_id
+        "#,
+        app.organization_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        WITH permission AS (
+            INSERT INTO permissions (permission_id, resource, action, scope)
+            VALUES (gen_random_uuid(), 'project/tasks', 'write', '*')
+            RETURNING permission_id
+        )
+        INSERT INTO roles_permissions (role_id, permission_id)
+        SELECT $1, permission_id FROM permission
+        "#,
+        role.role_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[("project_id", project_id)])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let mut query = vec![
+        ("resource", "project/tasks".to_string()),
+        ("action", "write".to_string()),
+        ("scope", "*".to_string()),
+    ];
+    assert!(!is_allowed(&app, "project/tasks", "write", "*").await);
+    query.push(("project_id", project_id.to_string()));
+    let decision = check(&app, &query)
+        .await
+        .json::<AuthzDecision>()
+        .await
+        .expect("Failed to parse response body");
+    assert!(decision.allowed);
+}
+
+#[tokio::test]
+async fn conditions_are_evaluated_against_attributes() {
+    let app = spawn_app().await;
+    app.grant_conditional_permission(
+        "report",
+        "view",
+        "*",
+        Some("resource.owner_id == user.id && resource.pages < 100"),
+    )
+    .await;
+
+    let check_owned_by = |owner_id: String, pages: u32| {
+        let query = serde_qs::to_string(&json!({
+            "resource": "report",
+            "action": "view",
+            "scope": "*",
+            "attributes": {
+                "resource.owner_id": owner_id,
+                "resource.pages": pages.to_string(),
+            },
+        }))
+        .unwrap();
+        app.api_client
+            .get(format!("{}/rbac-demo/authz/check?{}", &app.address, query))
+            .send()
+    };
+
+    let own = check_owned_by(app.test_user.user_id.to_string(), 12)
+        .await
+        .expect("Failed to get request")
+        .json::<AuthzDecision>()
+        .await
+        .expect("Failed to parse response body");
+    assert!(own.allowed);
+    assert_eq!(own.matched.len(), 1);
+
+    let foreign = check_owned_by(uuid::Uuid::new_v4().to_string(), 12)
+        .await
+        .expect("Failed to get request")
+        .json::<AuthzDecision>()
+        .await
+        .expect("Failed to parse response body");
+    assert!(!foreign.allowed);
+    assert_eq!(foreign.unmet.len(), 1);
+
+    let long = check_owned_by(app.test_user.user_id.to_string(), 300)
+        .await
+        .expect("Failed to get request")
+        .json::<AuthzDecision>()
+        .await
+        .expect("Failed to parse response body");
+    assert!(!long.allowed);
+}
+
+#[tokio::test]
+async fn user_attributes_cannot_be_overridden() {
+    let app = spawn_app().await;
+
+    let query = serde_qs::to_string(&json!({
+        "resource": "report",
+        "action": "view",
+        "scope": "*",
+        "attributes": { "user.id": uuid::Uuid::new_v4() },
+    }))
+    .unwrap();
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/authz/check?{}", &app.address, query))
+        .send()
+        .await
+        .expect("Failed to get request");
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn checking_users_outside_the_organization_returns_404() {
+    let app = spawn_app().await;
+
+    let query = [
+        ("user_id", uuid::Uuid::new_v4().to_string()),
+        ("resource", "project".to_string()),
+        ("action", "read".to_string()),
+        ("scope", "*".to_string()),
+    ];
+    let response = check(&app, &query).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
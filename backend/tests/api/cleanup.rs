@@ -0,0 +1,269 @@
+use crate::helper::{TestApp, spawn_app};
+use axum::http::StatusCode;
+use backend::rbac_demo::rbac::cleanup::models::{
+    CleanupAuditEntry, CleanupEvent, CleanupKind, OrphanReport,
+};
+use serde_json::{Value, json};
+
+/// An `editor` role holding a component the frontend dropped, one it still
+/// has, a permission no route or component needs, and one a route needs.
+async fn import_editor(app: &TestApp) {
+    let document = r#"
+version: 1
+permissions:
+  - { resource: report, action: export, scope: "*" }
+  - { resource: project, action: read, scope: "*" }
+components: [comp_legacy_report, comp_board_view]
+roles:
+  - name: editor
+    description: Edits projects
+    permissions:
+      - { resource: report, action: export, scope: "*" }
+      - { resource: project, action: read, scope: "*" }
+    components: [comp_legacy_report, comp_board_view]
+"#;
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/rbac/import", &app.address))
+        .header("Content-Type", "application/yaml")
+        .body(document)
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn sync(app: &TestApp, registry: Value) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo/rbac/registry/sync", &app.address))
+        .json(&registry)
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+fn board_registry() -> Value {
+    json!({ "components": [{ "code": "comp_board_view" }] })
+}
+
+async fn report(app: &TestApp) -> OrphanReport {
+    app.api_client
+        .get(format!("{}/rbac-demo/rbac/cleanup/report", &app.address))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<OrphanReport>()
+        .await
+        .unwrap()
+}
+
+async fn deprecate(app: &TestApp, grace_period_days: u32) -> Vec<CleanupAuditEntry> {
+    app.api_client
+        .post(format!("{}/rbac-demo/rbac/cleanup/deprecate", &app.address))
+        .json(&json!({ "grace_period_days": grace_period_days }))
+        .send()
+        .await
+        .expect("Failed to post request")
+        .json::<Vec<CleanupAuditEntry>>()
+        .await
+        .unwrap()
+}
+
+async fn purge(app: &TestApp) -> Vec<CleanupAuditEntry> {
+    app.api_client
+        .post(format!("{}/rbac-demo/rbac/cleanup/purge", &app.address))
+        .send()
+        .await
+        .expect("Failed to post request")
+        .json::<Vec<CleanupAuditEntry>>()
+        .await
+        .unwrap()
+}
+
+async fn component_codes(app: &TestApp) -> Vec<String> {
+    sqlx::query_scalar!("SELECT code FROM components ORDER BY code")
+        .fetch_all(&app.pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn report_lists_orphans_and_the_roles_holding_them() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import_editor(&app).await;
+
+    let before_sync = report(&app).await;
+    assert!(before_sync.registry_synced_at.is_none());
+    assert!(before_sync.components.is_empty());
+
+    let response = sync(&app, board_registry()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let report = report(&app).await;
+
+    assert!(report.registry_synced_at.is_some());
+    let components = report
+        .components
+        .iter()
+        .map(|c| c.code.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(components, ["comp_legacy_report"]);
+    let holders = report.components[0]
+        .roles
+        .iter()
+        .map(|r| r.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(holders, ["editor"]);
+
+    let permissions = report
+        .permissions
+        .iter()
+        .map(|p| (p.permission.resource.as_str(), p.roles.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(permissions, [("report", 1)]);
+}
+
+#[tokio::test]
+async fn registry_permissions_are_not_orphans() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import_editor(&app).await;
+
+    let registry = json!({ "components": [{
+        "code": "comp_board_view",
+        "permissions": [{ "resource": "report", "action": "export", "scope": "*" }]
+    }] });
+    sync(&app, registry).await;
+
+    assert!(report(&app).await.permissions.is_empty());
+
+    let invalid = json!({ "components": [{ "code": "" }] });
+    assert_eq!(sync(&app, invalid).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn orphans_are_purged_after_the_grace_period() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import_editor(&app).await;
+    sync(&app, board_registry()).await;
+
+    let deprecated = deprecate(&app, 0).await;
+    let events = deprecated
+        .iter()
+        .map(|e| (e.event, e.kind, e.name.as_str(), e.role_ids.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        [
+            (
+                CleanupEvent::Deprecated,
+                CleanupKind::Component,
+                "comp_legacy_report",
+                1
+            ),
+            (
+                CleanupEvent::Deprecated,
+                CleanupKind::Permission,
+                "report:export:*",
+                1
+            ),
+        ]
+    );
+    assert!(deprecate(&app, 0).await.is_empty());
+
+    let purged = purge(&app).await;
+    assert_eq!(purged.len(), 2);
+    assert!(purged.iter().all(|e| e.event == CleanupEvent::Purged));
+    assert_eq!(component_codes(&app).await, ["comp_board_view"]);
+
+    let version = sqlx::query_scalar!("SELECT version FROM roles WHERE name = 'editor'")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(version, 2);
+
+    let audited = sqlx::query_scalar!("SELECT count(*) FROM rbac_cleanup_audit")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(audited, Some(4));
+    let report = report(&app).await;
+    assert!(report.components.is_empty());
+    assert!(report.permissions.is_empty());
+}
+
+#[tokio::test]
+async fn deprecated_orphans_are_kept_during_the_grace_period_and_restored_by_a_sync() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import_editor(&app).await;
+    sync(&app, board_registry()).await;
+
+    assert_eq!(deprecate(&app, 14).await.len(), 2);
+    assert!(purge(&app).await.is_empty());
+    assert_eq!(
+        component_codes(&app).await,
+        ["comp_board_view", "comp_legacy_report"]
+    );
+
+    let registry = json!({ "components": [
+        { "code": "comp_board_view" },
+        { "code": "comp_legacy_report" }
+    ] });
+    sync(&app, registry).await;
+
+    let deprecated =
+        sqlx::query_scalar!("SELECT deprecated FROM components WHERE code = 'comp_legacy_report'")
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert!(!deprecated);
+    let report = report(&app).await;
+    assert!(report.components.is_empty());
+    assert!(report.permissions[0].permission.deprecated);
+}
+
+#[tokio::test]
+async fn only_system_admins_change_the_registry() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import_editor(&app).await;
+    sqlx::query!(
+        "UPDATE users SET is_system_admin = FALSE WHERE user_id = $1",
+        app.test_user.user_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        sync(&app, board_registry()).await.status(),
+        StatusCode::FORBIDDEN
+    );
+    for route in ["deprecate", "purge"] {
+        let response = app
+            .api_client
+            .post(format!("{}/rbac-demo/rbac/cleanup/{route}", &app.address))
+            .json(&json!({ "grace_period_days": 14 }))
+            .send()
+            .await
+            .expect("Failed to post request");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+#[tokio::test]
+async fn grace_periods_beyond_ten_years_are_rejected() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/rbac/cleanup/deprecate", &app.address))
+        .json(&json!({ "grace_period_days": u32::MAX }))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -4,11 +4,10 @@ use argon2::password_hash::SaltString;
 use argon2::password_hash::rand_core::OsRng;
 use backend::configuration::{DBSettings, Settings};
 use backend::rbac_demo::rbac::permissions::models::Permission;
+use backend::rbac_demo::rbac::roles::models::Role;
 use backend::startup::Application;
 use backend::telemetry::{get_subscriber, init_subscriber};
 use fake::Fake;
-use fake::faker::internet::en::SafeEmail;
-use fake::faker::name;
 use once_cell::sync::Lazy;
 use rand::distr::{Distribution, slice::Choose};
 use rand::rng;
@@ -17,8 +16,9 @@ use reqwest::Url;
 use reqwest::redirect::Policy;
 use serde_json::{Value, json};
 use sqlx::{Connection, Executor, PgConnection, PgPool};
-use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU64;
 use uuid::Uuid;
+use wiremock::MockServer;
 
 pub struct TestUser {
     pub user_id: Uuid,
@@ -27,7 +27,7 @@ pub struct TestUser {
 }
 
 impl TestUser {
-    fn generate() -> Self {
+    pub fn generate() -> Self {
         TestUser {
             user_id: Uuid::new_v4(),
             username: Uuid::new_v4().to_string(),
@@ -35,7 +35,7 @@ impl TestUser {
         }
     }
 
-    async fn store(&self, pool: &PgPool) {
+    pub async fn store(&self, pool: &PgPool) {
         let salt = SaltString::generate(&mut OsRng);
         let password_hash = Argon2::default()
             .hash_password(self.password.as_bytes(), &salt)
@@ -62,7 +62,12 @@ pub struct TestApp {
     pub port: u16,
     pub pool: PgPool,
     pub test_user: TestUser,
+    /// The only organization of `test_user`.
+    pub organization_id: Uuid,
+    /// Logged in as `test_user`, with `organization_id` selected.
     pub api_client: reqwest::Client,
+    /// Stands in for the email delivery API.
+    pub email_server: MockServer,
 }
 
 pub struct ConfirmationLinks {
@@ -71,6 +76,24 @@ pub struct ConfirmationLinks {
 }
 
 impl TestApp {
+    /// The links of an email sent through `email_server`.
+    pub fn get_confirmation_links(&self, email_request: &wiremock::Request) -> ConfirmationLinks {
+        let body: Value = serde_json::from_slice(&email_request.body).unwrap();
+        let get_link = |s: &str| {
+            let links: Vec<_> = linkify::LinkFinder::new()
+                .links(s)
+                .filter(|l| *l.kind() == linkify::LinkKind::Url)
+                .collect();
+            assert_eq!(links.len(), 1);
+            Url::parse(links[0].as_str()).unwrap()
+        };
+
+        ConfirmationLinks {
+            html: get_link(body["HtmlBody"].as_str().unwrap()),
+            pain_text: get_link(body["TextBody"].as_str().unwrap()),
+        }
+    }
+
     pub async fn post_login(&self, body: &Value) -> reqwest::Response {
         self.api_client
             .post(format!("{}/login", &self.address))
@@ -88,12 +111,90 @@ impl TestApp {
         .await;
     }
 
-    pub async fn post_logout(&self) -> reqwest::Response {
-        self.api_client
-            .post(format!("{}/admin/logout", &self.address))
+    /// Stores a new user in the test organization and returns a client with
+    /// its own session, logged in as that user.
+    pub async fn login_new_user(&self) -> (TestUser, reqwest::Client) {
+        let user = TestUser::generate();
+        user.store(&self.pool).await;
+        sqlx::query!(
+            "INSERT INTO organizations_users (organization_id, user_id) VALUES ($1, $2)",
+            self.organization_id,
+            user.user_id
+        )
+        .execute(&self.pool)
+        .await
+        .expect("Failed to join organization.");
+
+        let client = reqwest::Client::builder()
+            .cookie_store(true)
+            .redirect(Policy::none())
+            .no_proxy()
+            .build()
+            .unwrap();
+        client
+            .post(format!("{}/login", &self.address))
+            .form(&json!({
+                "username": user.username,
+                "password": user.password,
+            }))
             .send()
             .await
-            .expect("Failed to execute request.")
+            .expect("Failed to execute request.");
+
+        (user, client)
+    }
+
+    /// Grants `test_user` an organization-wide role holding the permission.
+    pub async fn grant_permission(&self, resource: &str, action: &str, scope: &str) {
+        self.grant_conditional_permission(resource, action, scope, None)
+            .await;
+    }
+
+    pub async fn grant_conditional_permission(
+        &self,
+        resource: &str,
+        action: &str,
+        scope: &str,
+        condition: Option<&str>,
+    ) {
+        sqlx::query!(
+            r#"
+            WITH permission AS (
+                INSERT INTO permissions (permission_id, resource, action, scope, condition)
+                VALUES (gen_random_uuid(), $1, $2, $3, $6)
+                RETURNING permission_id
+            ), role AS (
+                INSERT INTO roles (role_id, name, description, organization_id)
+                VALUES (gen_random_uuid(), $1 || ':' || $2 || ':' || $3, 'test grant', $4)
+                RETURNING role_id
+            ), bound AS (
+                INSERT INTO roles_permissions (role_id, permission_id)
+                SELECT role_id, permission_id FROM role, permission
+            )
+            INSERT INTO users_roles (user_id, role_id)
+            SELECT $5, role_id FROM role
+            "#,
+            resource,
+            action,
+            scope,
+            self.organization_id,
+            self.test_user.user_id,
+            condition
+        )
+        .execute(&self.pool)
+        .await
+        .expect("Failed to grant permission.");
+    }
+
+    /// Lets `test_user` change what every organization shares.
+    pub async fn make_system_admin(&self) {
+        sqlx::query!(
+            "UPDATE users SET is_system_admin = TRUE WHERE user_id = $1",
+            self.test_user.user_id
+        )
+        .execute(&self.pool)
+        .await
+        .expect("Failed to make a system admin.");
     }
 
     pub async fn post_change_password<Body>(&self, body: &Body) -> Response
@@ -125,7 +226,9 @@ static INIT_SUBSCRIBER: Lazy<()> = Lazy::new(|| {
 pub async fn spawn_app() -> TestApp {
     Lazy::force(&INIT_SUBSCRIBER);
 
-    let app_config = get_test_config();
+    let email_server = MockServer::start().await;
+    let mut app_config = get_test_config();
+    app_config.email_client.base_url = email_server.uri();
 
     let pool = configure_database(&app_config.database).await;
 
@@ -140,6 +243,7 @@ pub async fn spawn_app() -> TestApp {
 
     let test_user = TestUser::generate();
     test_user.store(&pool).await;
+    let organization_id = create_organization(&pool, &test_user).await;
 
     let api_client = reqwest::Client::builder()
         .cookie_store(true)
@@ -150,18 +254,45 @@ pub async fn spawn_app() -> TestApp {
         .build()
         .unwrap();
 
-    TestApp {
+    let app = TestApp {
         address: app_url,
         port: app_port,
         pool,
         test_user,
+        organization_id,
         api_client,
-    }
+        email_server,
+    };
+    app.login().await;
+
+    app
+}
+
+/// Creates an organization with `user` as its only member.
+pub async fn create_organization(pool: &PgPool, user: &TestUser) -> Uuid {
+    let organization_id = sqlx::query_scalar!(
+        "INSERT INTO organizations (name) VALUES ($1) RETURNING organization_id",
+        Uuid::new_v4().to_string()
+    )
+    .fetch_one(pool)
+    .await
+    .expect("Failed to create organization.");
+    sqlx::query!(
+        "INSERT INTO organizations_users (organization_id, user_id) VALUES ($1, $2)",
+        organization_id,
+        user.user_id
+    )
+    .execute(pool)
+    .await
+    .expect("Failed to join organization.");
+
+    organization_id
 }
 
 fn get_test_config() -> Settings {
     let mut c = backend::configuration::get_config().expect("Failed to load configuration");
     c.app_settings.port = 0;
+    c.role_grants.purge_interval_seconds = NonZeroU64::MIN;
 
     c.database.database_name = format!(
         "test_{}",
@@ -196,16 +327,6 @@ async fn configure_database(configuration: &DBSettings) -> PgPool {
     pool
 }
 
-pub fn valid_subscriber() -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    let name: String = name::en::Name().fake();
-    let email: String = SafeEmail().fake();
-    map.insert("name".to_string(), name);
-    map.insert("email".to_string(), email);
-
-    map
-}
-
 pub fn assert_is_redirect_to(response: &reqwest::Response, url: &str) {
     let status = response.status();
     assert!(
@@ -235,12 +356,15 @@ pub async fn insert_permissions(pool: &sqlx::PgPool, amount: u64) -> Vec<Permiss
     let scopes_dist = Choose::new(&scopes).unwrap();
     let mut rng = rng();
 
+    // suffix the resource with its index to keep (resource, action, scope) unique
     let permissions: Vec<Permission> = (1..=amount)
-        .map(|_| Permission {
+        .map(|i| Permission {
             permission_id: uuid::Uuid::new_v4(),
-            resource: fake::faker::lorem::en::Word().fake::<String>(),
+            resource: format!("{}_{}", fake::faker::lorem::en::Word().fake::<String>(), i),
             action: (*actions_dist.sample(&mut rng)).to_string(),
             scope: (*scopes_dist.sample(&mut rng)).to_string(),
+            deprecated: false,
+            condition: None,
         })
         .collect::<Vec<_>>();
 
@@ -259,3 +383,29 @@ pub async fn insert_permissions(pool: &sqlx::PgPool, amount: u64) -> Vec<Permiss
 
     permissions
 }
+
+pub async fn insert_roles(pgpool: &sqlx::PgPool, organization_id: Uuid, amount: u64) -> Vec<Role> {
+    let roles: Vec<Role> = (1..=amount)
+        .map(|_| Role {
+            role_id: uuid::Uuid::new_v4(),
+            name: fake::faker::lorem::en::Word().fake::<String>(),
+            description: fake::faker::lorem::en::Sentence(1..5).fake::<String>(),
+            version: 1,
+            is_system: false,
+        })
+        .collect::<Vec<_>>();
+
+    let mut query_builder =
+        sqlx::QueryBuilder::new("INSERT INTO roles (role_id, name, description, organization_id) ");
+    query_builder.push_values(roles.clone(), |mut query, role| {
+        query
+            .push_bind(role.role_id)
+            .push_bind(role.name)
+            .push_bind(role.description)
+            .push_bind(organization_id);
+    });
+    let query = query_builder.build();
+    query.execute(pgpool).await.unwrap();
+
+    roles
+}
@@ -0,0 +1,274 @@
+use backend::rbac_demo::invitations::models::{Invitation, InvitationStatus};
+use backend::rbac_demo::members::models::{Member, MemberProject};
+use reqwest::StatusCode;
+use reqwest::redirect::Policy;
+use serde_json::{Value, json};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helper::{TestApp, insert_roles, spawn_app};
+
+async fn post(app: &TestApp, path: &str, body: Value) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo{}", app.address, path))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to send request")
+}
+
+async fn mock_email_api(app: &TestApp, status: u16) {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(&app.email_server)
+        .await;
+}
+
+/// The token of the link in the `index`-th email sent.
+async fn sent_token(app: &TestApp, index: usize) -> String {
+    let requests = app.email_server.received_requests().await.unwrap();
+    let links = app.get_confirmation_links(&requests[index]);
+    assert_eq!(links.html, links.pain_text);
+    links
+        .html
+        .query_pairs()
+        .find(|(key, _)| key == "token")
+        .map(|(_, token)| token.into_owned())
+        .expect("Link has a token")
+}
+
+async fn accept(app: &TestApp, token: &str, password: &str) -> reqwest::Response {
+    post(
+        app,
+        "/invitations/accept",
+        json!({ "token": token, "password": password }),
+    )
+    .await
+}
+
+async fn invite(app: &TestApp, email: &str, body: Value) -> Invitation {
+    let mut body = body;
+    body["email"] = json!(email);
+    body["first_name"] = json!("Ada");
+    body["last_name"] = json!("Lovelace");
+    let response = post(app, "/members/invite", body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn invitee_sets_a_password_and_gets_roles_and_projects() {
+    let app = spawn_app().await;
+    mock_email_api(&app, 200).await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let project = post(
+        &app,
+        "/projects",
+        json!({ "name": "apollo", "description": "" }),
+    )
+    .await
+    .json::<Value>()
+    .await
+    .unwrap();
+
+    let invitation = invite(
+        &app,
+        "ada@example.com",
+        json!({
+            "role_ids": [role.role_id],
+            "projects": [{ "project_id": project["project_id"], "role": "maintainer" }],
+        }),
+    )
+    .await;
+    assert_eq!(invitation.status, InvitationStatus::Pending);
+    let token = sent_token(&app, 0).await;
+
+    // Pending users cannot log in, whatever the password.
+    let response = app
+        .post_login(&json!({ "username": "ada@example.com", "password": "" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = accept(&app, &token, "short").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = accept(&app, &token, "correct horse battery").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let accepted = response.json::<Invitation>().await.unwrap();
+    assert_eq!(accepted.status, InvitationStatus::Accepted);
+    let response = accept(&app, &token, "correct horse battery").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let user_id = invitation.user_id.unwrap();
+    let roles = sqlx::query_scalar!(
+        "SELECT role_id FROM users_roles WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(roles, vec![role.role_id]);
+    let projects = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/members/{}/projects",
+            app.address, invitation.member_id
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<MemberProject>>()
+        .await
+        .unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "apollo");
+
+    let invitee = reqwest::Client::builder()
+        .cookie_store(true)
+        .redirect(Policy::none())
+        .no_proxy()
+        .build()
+        .unwrap();
+    let response = invitee
+        .post(format!("{}/login", app.address))
+        .form(&json!({ "username": "ada@example.com", "password": "correct horse battery" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_redirection());
+}
+
+#[tokio::test]
+async fn resent_or_revoked_invitation_links_stop_working() {
+    let app = spawn_app().await;
+    mock_email_api(&app, 200).await;
+    let invitation = invite(&app, "bob@example.com", json!({})).await;
+    let first = sent_token(&app, 0).await;
+
+    // No waiting: the resend bumps the version even within the same second.
+    let resend = format!("/invitations/{}/resend", invitation.invitation_id);
+    let response = post(&app, &resend, json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let second = sent_token(&app, 1).await;
+    assert_ne!(first, second);
+
+    let response = accept(&app, &first, "correct horse battery").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = accept(&app, "not-a-token", "correct horse battery").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let revoke = format!("/invitations/{}/revoke", invitation.invitation_id);
+    let response = post(&app, &revoke, json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let revoked = response.json::<Invitation>().await.unwrap();
+    assert_eq!(revoked.status, InvitationStatus::Revoked);
+    let response = accept(&app, &second, "correct horse battery").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response = post(&app, &resend, json!({})).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let pending_user =
+        sqlx::query_scalar!("SELECT 1 FROM users WHERE username = $1", "bob@example.com")
+            .fetch_optional(&app.pool)
+            .await
+            .unwrap();
+    assert!(pending_user.is_none());
+}
+
+#[tokio::test]
+async fn existing_members_are_invited_without_a_duplicate() {
+    let app = spawn_app().await;
+    mock_email_api(&app, 200).await;
+    let member = post(
+        &app,
+        "/members",
+        json!({ "first_name": "Grace", "last_name": "Hopper" }),
+    )
+    .await
+    .json::<Member>()
+    .await
+    .unwrap();
+
+    let body = json!({ "email": "grace@example.com", "member_id": member.member_id });
+    let response = post(&app, "/members/invite", body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let invitation = response.json::<Invitation>().await.unwrap();
+    assert_eq!(invitation.member_id, member.member_id);
+
+    let members = sqlx::query!(
+        "SELECT user_id FROM members WHERE organization_id = $1",
+        app.organization_id
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].user_id, invitation.user_id);
+
+    let body = json!({ "email": "grace2@example.com", "member_id": member.member_id });
+    let response = post(&app, "/members/invite", body).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response = post(
+        &app,
+        "/members/invite",
+        json!({ "email": "ada@example.com" }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn failed_logins_as_the_invitee_do_not_block_revoking() {
+    let app = spawn_app().await;
+    mock_email_api(&app, 200).await;
+    let invitation = invite(&app, "eve@example.com", json!({})).await;
+
+    let response = app
+        .post_login(&json!({ "username": "eve@example.com", "password": "guess" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let revoke = format!("/invitations/{}/revoke", invitation.invitation_id);
+    let response = post(&app, &revoke, json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let revoked = response.json::<Invitation>().await.unwrap();
+    assert_eq!(revoked.status, InvitationStatus::Revoked);
+}
+
+#[tokio::test]
+async fn invitations_whose_email_cannot_be_sent_are_kept_to_be_resent() {
+    let app = spawn_app().await;
+    mock_email_api(&app, 500).await;
+
+    let invitation = invite(&app, "eve@example.com", json!({})).await;
+    assert!(invitation.sent_at.is_none());
+    assert!(invitation.send_error.is_some());
+
+    app.email_server.reset().await;
+    mock_email_api(&app, 200).await;
+    let resend = format!("/invitations/{}/resend", invitation.invitation_id);
+    let response = post(&app, &resend, json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let resent = response.json::<Invitation>().await.unwrap();
+    assert!(resent.sent_at.is_some());
+    assert!(resent.send_error.is_none());
+    let token = sent_token(&app, 0).await;
+    let response = accept(&app, &token, "correct horse battery").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn emailed_links_open_the_accept_page() {
+    let app = spawn_app().await;
+    mock_email_api(&app, 200).await;
+    invite(&app, "joe@example.com", json!({})).await;
+
+    let requests = app.email_server.received_requests().await.unwrap();
+    let mut link = app.get_confirmation_links(&requests[0]).html;
+    link.set_port(Some(app.port)).unwrap();
+    let response = reqwest::get(link).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await.unwrap().contains("password"));
+}
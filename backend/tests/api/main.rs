@@ -1,6 +1,18 @@
+mod access_requests;
+mod audit_events;
+mod authz;
+mod cleanup;
 mod health_check;
 mod helper;
+mod invitations;
 mod members;
+mod organizations;
 mod permissions;
+mod presets;
+mod project_members;
 mod projects;
+mod rbac_document;
 mod roles;
+mod sod_rules;
+mod tasks;
+mod users;
@@ -1,13 +1,15 @@
 use std::collections::HashMap;
 
-use backend::models::ListResponse;
-use backend::rbac_demo::members::models::Member;
+use backend::models::{ExportRequest, ListRequest, ListResponse};
+use backend::rbac_demo::members::models::{
+    ImportReport, Member, MemberColumnFilter, MemberFilter, Profile,
+};
 use fake::{Fake, faker};
 use reqwest::StatusCode;
 use serde_json::json;
 use sqlx::PgPool;
 
-use crate::helper::spawn_app;
+use crate::helper::{TestApp, spawn_app};
 
 #[tokio::test]
 async fn return_200_for_valid_member_data() {
@@ -63,11 +65,11 @@ async fn return_valid_members_list() {
     let app = spawn_app().await;
 
     let amount = 9;
-    insert_members(&app.pool, amount).await;
+    insert_members(&app.pool, app.organization_id, amount).await;
 
     let response = app
         .api_client
-        .get(&format!("{}/rbac-demo/members", app.address))
+        .get(format!("{}/rbac-demo/members", app.address))
         .query(&[("current_page", "1"), ("page_size", "10")])
         .send()
         .await
@@ -84,12 +86,12 @@ async fn return_valid_members_list() {
 async fn return_204_if_successfully_deleted() {
     let app = spawn_app().await;
 
-    let members = insert_members(&app.pool, 1).await;
+    let members = insert_members(&app.pool, app.organization_id, 1).await;
     let member_id = members.first().unwrap().member_id;
 
     let response = app
         .api_client
-        .delete(&format!("{}/rbac-demo/members/{}", app.address, member_id))
+        .delete(format!("{}/rbac-demo/members/{}", app.address, member_id))
         .send()
         .await
         .unwrap();
@@ -102,7 +104,7 @@ async fn return_404_if_member_not_found() {
 
     let response = app
         .api_client
-        .delete(&format!(
+        .delete(format!(
             "{}/rbac-demo/members/{}",
             app.address,
             uuid::Uuid::new_v4()
@@ -113,20 +115,363 @@ async fn return_404_if_member_not_found() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
-async fn insert_members(pool: &PgPool, amount: u64) -> Vec<Member> {
+#[tokio::test]
+async fn member_is_updated_wholly_or_partially() {
+    let app = spawn_app().await;
+    let member_id = insert_members(&app.pool, app.organization_id, 1).await[0].member_id;
+    let url = format!("{}/rbac-demo/members/{}", app.address, member_id);
+
+    let response = app
+        .api_client
+        .put(&url)
+        .json(&json!({ "first_name": "Ada", "last_name": "Lovelace" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .api_client
+        .patch(&url)
+        .json(&json!({ "last_name": "Byron" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let member = app
+        .api_client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .json::<Member>()
+        .await
+        .unwrap();
+    assert_eq!(member.first_name, "Ada");
+    assert_eq!(member.last_name, "Byron");
+
+    let response = app
+        .api_client
+        .patch(format!(
+            "{}/rbac-demo/members/{}",
+            app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .json(&json!({ "last_name": "Byron" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn invalid_names_are_rejected_by_every_write() {
+    let app = spawn_app().await;
+    let member_id = insert_members(&app.pool, app.organization_id, 1).await[0].member_id;
+    let url = format!("{}/rbac-demo/members/{}", app.address, member_id);
+    let long = "a".repeat(256);
+
+    for body in [
+        json!({ "first_name": "", "last_name": "Lovelace" }),
+        json!({ "first_name": "Ada", "last_name": long }),
+    ] {
+        let response = app
+            .api_client
+            .post(format!("{}/rbac-demo/members", app.address))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "POST {body}");
+
+        let response = app.api_client.put(&url).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "PUT {body}");
+    }
+    for body in [json!({ "first_name": "" }), json!({ "last_name": long })] {
+        let response = app.api_client.patch(&url).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "PATCH {body}");
+    }
+}
+
+#[tokio::test]
+async fn deleted_member_is_kept_until_restored() {
+    let app = spawn_app().await;
+    let members = insert_members(&app.pool, app.organization_id, 2).await;
+    let member_id = members[0].member_id;
+    let url = format!("{}/rbac-demo/members/{}", app.address, member_id);
+
+    let response = app.api_client.delete(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = app.api_client.delete(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let listed = list_members(&app, MemberFilter::default()).await;
+    assert_eq!(listed.total, 1);
+    assert_eq!(listed.results[0].member_id, members[1].member_id);
+    let filter = MemberFilter {
+        include_deleted: true,
+        ..Default::default()
+    };
+    assert_eq!(list_members(&app, filter).await.total, 2);
+
+    let member = app
+        .api_client
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .json::<Member>()
+        .await
+        .unwrap();
+    assert!(member.deleted_at.is_some());
+    let response = app
+        .api_client
+        .put(&url)
+        .json(&json!({ "first_name": "Ada", "last_name": "Lovelace" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let restore = format!("{url}/restore");
+    let response = app.api_client.post(&restore).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .json::<Member>()
+            .await
+            .unwrap()
+            .deleted_at
+            .is_none()
+    );
+    let response = app.api_client.post(&restore).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(list_members(&app, MemberFilter::default()).await.total, 2);
+}
+
+#[tokio::test]
+async fn me_returns_the_linked_member() {
+    let app = spawn_app().await;
+    let me_url = format!("{}/rbac-demo/me", app.address);
+
+    let profile = app
+        .api_client
+        .get(&me_url)
+        .send()
+        .await
+        .unwrap()
+        .json::<Profile>()
+        .await
+        .unwrap();
+    assert_eq!(profile.user_id, app.test_user.user_id);
+    assert_eq!(profile.organization_id, Some(app.organization_id));
+    assert!(profile.member.is_none());
+
+    let member = insert_members(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    sqlx::query!(
+        "UPDATE members SET user_id = $1 WHERE member_id = $2",
+        app.test_user.user_id,
+        member.member_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let profile = app
+        .api_client
+        .get(&me_url)
+        .send()
+        .await
+        .unwrap()
+        .json::<Profile>()
+        .await
+        .unwrap();
+    let linked = profile.member.expect("Member profile");
+    assert_eq!(linked.member_id, member.member_id);
+    assert_eq!(linked.user_id, Some(app.test_user.user_id));
+}
+
+#[tokio::test]
+async fn import_reports_invalid_rows_per_mode() {
+    let app = spawn_app().await;
+    let file = "first_name,last_name\nAda,Lovelace\n,Hopper\nAlan\nGrace,Hopper\n";
+    let import = |mode: &'static str| {
+        let form = reqwest::multipart::Form::new().part(
+            "file",
+            reqwest::multipart::Part::text(file).file_name("members.csv"),
+        );
+        app.api_client
+            .post(format!("{}/rbac-demo/members/import", app.address))
+            .query(&[("mode", mode)])
+            .multipart(form)
+            .send()
+    };
+
+    let response = import("all_or_nothing").await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let report = response.json::<ImportReport>().await.unwrap();
+    assert!(report.imported.is_empty());
+    let lines = report.errors.iter().map(|e| e.line).collect::<Vec<_>>();
+    assert_eq!(lines, vec![3, 4]);
+    let filter = MemberFilter::default();
+    assert_eq!(list_members(&app, filter).await.total, 0);
+
+    let response = import("best_effort").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let report = response.json::<ImportReport>().await.unwrap();
+    assert_eq!(report.imported.len(), 2);
+    assert_eq!(report.errors.len(), 2);
+    let members = list_members(&app, MemberFilter::default()).await;
+    let names = members
+        .results
+        .iter()
+        .map(|m| m.first_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Grace", "Ada"]);
+}
+
+#[tokio::test]
+async fn export_honours_the_list_filter() {
+    let app = spawn_app().await;
+    let members = insert_members(&app.pool, app.organization_id, 3).await;
+    let request = ExportRequest {
+        filter: Some(MemberFilter {
+            columns: MemberColumnFilter {
+                last_name: Some(members[0].last_name.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    };
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/members/export.csv?{}",
+            app.address,
+            serde_qs::to_string(&request).unwrap()
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/csv");
+
+    let body = response.text().await.unwrap();
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec![
+            "member_id",
+            "first_name",
+            "last_name",
+            "user_id",
+            "deleted_at"
+        ]
+    );
+    let exported = reader
+        .records()
+        .map(|record| record.unwrap()[0].parse::<uuid::Uuid>().unwrap())
+        .collect::<Vec<_>>();
+    let expected = members
+        .iter()
+        .filter(|m| m.last_name == members[0].last_name)
+        .count();
+    assert_eq!(exported.len(), expected);
+    assert!(exported.contains(&members[0].member_id));
+}
+
+#[tokio::test]
+async fn export_streams_every_member_once() {
+    let app = spawn_app().await;
+    let members = insert_members(&app.pool, app.organization_id, 1201).await;
+
+    let body = export_members(&app).await;
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let mut exported = reader
+        .records()
+        .map(|record| record.unwrap()[0].parse::<uuid::Uuid>().unwrap())
+        .collect::<Vec<_>>();
+    exported.sort();
+    let mut expected = members.iter().map(|m| m.member_id).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(exported, expected);
+}
+
+#[tokio::test]
+async fn export_keeps_spreadsheets_from_evaluating_names() {
+    let app = spawn_app().await;
+    sqlx::query(
+        "INSERT INTO members (member_id, first_name, last_name, organization_id) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind("=HYPERLINK(\"http://evil\")")
+    .bind("-1+2")
+    .bind(app.organization_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let body = export_members(&app).await;
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let record = reader.records().next().unwrap().unwrap();
+    assert_eq!(&record[1], "'=HYPERLINK(\"http://evil\")");
+    assert_eq!(&record[2], "'-1+2");
+}
+
+async fn export_members(app: &TestApp) -> String {
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/members/export.csv", app.address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    response.text().await.unwrap()
+}
+
+async fn list_members(app: &TestApp, filter: MemberFilter) -> ListResponse<Member> {
+    let request = ListRequest {
+        filter: Some(filter),
+        current_page: 1,
+        page_size: 10,
+    };
+    app.api_client
+        .get(format!(
+            "{}/rbac-demo/members?{}",
+            app.address,
+            serde_qs::to_string(&request).unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to list members")
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn insert_members(pool: &PgPool, organization_id: uuid::Uuid, amount: u64) -> Vec<Member> {
     let members = (0..amount)
         .map(|_| Member {
             member_id: uuid::Uuid::new_v4(),
             first_name: faker::name::zh_cn::FirstName().fake::<String>(),
             last_name: faker::name::zh_cn::LastName().fake::<String>(),
+            user_id: None,
+            deleted_at: None,
         })
         .collect::<Vec<Member>>();
 
-    let mut qb = sqlx::QueryBuilder::new("INSERT INTO members (member_id, first_name, last_name) ");
+    let mut qb = sqlx::QueryBuilder::new(
+        "INSERT INTO members (member_id, first_name, last_name, organization_id) ",
+    );
     qb.push_values(members.clone(), |mut b, member| {
         b.push_bind(member.member_id)
             .push_bind(member.first_name)
-            .push_bind(member.last_name);
+            .push_bind(member.last_name)
+            .push_bind(organization_id);
     });
     qb.build().execute(pool).await.unwrap();
 
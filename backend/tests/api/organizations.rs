@@ -0,0 +1,192 @@
+use crate::helper::{TestApp, TestUser, create_organization, insert_roles, spawn_app};
+use axum::http::StatusCode;
+use backend::rbac_demo::organizations::models::Organization;
+use backend::rbac_demo::rbac::roles::models::Role;
+use reqwest::redirect::Policy;
+use serde_json::json;
+
+async fn select(app: &TestApp, organization_id: uuid::Uuid) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/organizations/{}/select",
+            &app.address, organization_id
+        ))
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+async fn get_role(app: &TestApp, role_id: uuid::Uuid) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/rbac-demo/roles/{}", &app.address, role_id))
+        .send()
+        .await
+        .expect("Failed to get role")
+}
+
+#[tokio::test]
+async fn create_organization_makes_caller_a_member() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/organizations", &app.address))
+        .json(&json!({ "name": "acme" }))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let created = response
+        .json::<Organization>()
+        .await
+        .expect("Failed to parse response body");
+
+    let organizations = app
+        .api_client
+        .get(format!("{}/rbac-demo/organizations", &app.address))
+        .send()
+        .await
+        .expect("Failed to get organizations")
+        .json::<Vec<Organization>>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(organizations.len(), 2);
+    assert!(
+        organizations
+            .iter()
+            .any(|o| o.organization_id == created.organization_id)
+    );
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/organizations", &app.address))
+        .json(&json!({ "name": "acme" }))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn roles_of_other_organizations_are_invisible() {
+    let app = spawn_app().await;
+    let other_organization = create_organization(&app.pool, &app.test_user).await;
+    let own = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let foreign = insert_roles(&app.pool, other_organization, 1)
+        .await
+        .remove(0);
+
+    assert_eq!(get_role(&app, own.role_id).await.status(), StatusCode::OK);
+    assert_eq!(
+        get_role(&app, foreign.role_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let response = select(&app, other_organization).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(
+        get_role(&app, own.role_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    let role = get_role(&app, foreign.role_id)
+        .await
+        .json::<Role>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(role.role_id, foreign.role_id);
+}
+
+#[tokio::test]
+async fn selecting_an_organization_requires_membership() {
+    let app = spawn_app().await;
+    let outsider = TestUser::generate();
+    outsider.store(&app.pool).await;
+    let foreign = create_organization(&app.pool, &outsider).await;
+
+    let response = select(&app, foreign).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = select(&app, uuid::Uuid::new_v4()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn tenant_endpoints_require_a_selected_organization() {
+    let app = spawn_app().await;
+    // A second organization means login can no longer pick one.
+    create_organization(&app.pool, &app.test_user).await;
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .redirect(Policy::none())
+        .no_proxy()
+        .build()
+        .unwrap();
+    client
+        .post(format!("{}/login", &app.address))
+        .form(&json!({
+            "username": app.test_user.username,
+            "password": app.test_user.password,
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let response = client
+        .get(format!("{}/rbac-demo/roles", &app.address))
+        .send()
+        .await
+        .expect("Failed to get roles");
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn removed_users_lose_their_roles_in_the_organization() {
+    let app = spawn_app().await;
+    let (user, client) = app.login_new_user().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, user.user_id
+        ))
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/organizations/{}/users/remove",
+            &app.address, app.organization_id
+        ))
+        .json(&[user.user_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let held = sqlx::query_scalar!(
+        "SELECT count(*) FROM users_roles WHERE user_id = $1",
+        user.user_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(held, Some(0));
+
+    let response = client
+        .get(format!("{}/rbac-demo/roles", &app.address))
+        .send()
+        .await
+        .expect("Failed to get roles");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
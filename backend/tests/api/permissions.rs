@@ -1,7 +1,10 @@
-use crate::helper::{insert_permissions, spawn_app};
+use crate::helper::{insert_permissions, insert_roles, spawn_app};
 use backend::models::{ListRequest, ListResponse};
 use backend::rbac_demo::rbac::permissions::get::PermissionFilter;
 use backend::rbac_demo::rbac::permissions::models::Permission;
+use backend::rbac_demo::rbac::roles::models::Role;
+use reqwest::StatusCode;
+use serde_json::json;
 use std::collections::HashSet;
 
 #[tokio::test]
@@ -13,7 +16,7 @@ async fn return_valid_permissions_list() {
 
     let reponse = app
         .api_client
-        .get(&format!("{}/rbac-demo/permissions", &app.address))
+        .get(format!("{}/rbac-demo/permissions", &app.address))
         .query(&[("current_page", "1"), ("page_size", "10")])
         .send()
         .await
@@ -42,12 +45,13 @@ async fn return_valid_permissions_list_with_filter() {
         page_size: 10,
         filter: Some(PermissionFilter {
             resource: Some(resources.iter().next().unwrap().clone()),
+            deprecated: None,
         }),
     };
 
     let reponse = app
         .api_client
-        .get(&format!(
+        .get(format!(
             "{}/rbac-demo/permissions?{}",
             &app.address,
             serde_qs::to_string(&request).unwrap()
@@ -67,6 +71,428 @@ async fn return_valid_permissions_list_with_filter() {
     }
 }
 
+#[tokio::test]
+async fn create_permission_success() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/permissions", &app.address))
+        .json(&json!({
+            "resource": "project",
+            "action": "read",
+            "scope": "*",
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let permission = response.json::<Permission>().await.unwrap();
+    let saved = sqlx::query!(
+        "SELECT resource, action, scope, deprecated FROM permissions WHERE permission_id = $1",
+        permission.permission_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .expect("Failed to fetch saved permission");
+    assert_eq!(saved.resource, "project");
+    assert_eq!(saved.action, "read");
+    assert_eq!(saved.scope, "*");
+    assert!(!saved.deprecated);
+}
+
+#[tokio::test]
+async fn only_system_admins_edit_the_catalog() {
+    let app = spawn_app().await;
+    let permission = insert_permissions(&app.pool, 1).await.pop().unwrap();
+    let url = format!(
+        "{}/rbac-demo/permissions/{}",
+        &app.address, permission.permission_id
+    );
+    let body = json!({ "resource": "project", "action": "read", "scope": "*" });
+
+    let responses = [
+        app.api_client
+            .post(format!("{}/rbac-demo/permissions", &app.address))
+            .json(&body),
+        app.api_client.put(&url).json(&body),
+        app.api_client.delete(&url),
+    ];
+    for request in responses {
+        let response = request.send().await.expect("Failed to send request");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+#[tokio::test]
+async fn create_duplicate_permission_returns_409() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    let existing = insert_permissions(&app.pool, 1).await.pop().unwrap();
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/permissions", &app.address))
+        .json(&json!({
+            "resource": existing.resource,
+            "action": existing.action,
+            "scope": existing.scope,
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn create_permission_with_empty_fields_returns_400() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/permissions", &app.address))
+        .json(&json!({
+            "resource": "",
+            "action": "read",
+            "scope": "*",
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn create_permission_with_malformed_pattern_returns_400() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    for (resource, action) in [
+        ("project/", "read"),
+        ("pro*ject", "read"),
+        ("project", "re*d"),
+    ] {
+        let response = app
+            .api_client
+            .post(format!("{}/rbac-demo/permissions", &app.address))
+            .json(&json!({
+                "resource": resource,
+                "action": action,
+                "scope": "*",
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(
+            response.status(),
+            StatusCode::BAD_REQUEST,
+            "{resource}:{action}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn create_permission_with_condition() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/permissions", &app.address))
+        .json(&json!({
+            "resource": "report",
+            "action": "view",
+            "scope": "*",
+            "condition": "resource.owner_id == user.id",
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let permission = response.json::<Permission>().await.unwrap();
+    assert_eq!(
+        permission.condition.as_deref(),
+        Some("resource.owner_id == user.id")
+    );
+
+    // The same permission without the condition is a different one.
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/permissions", &app.address))
+        .json(&json!({
+            "resource": "report",
+            "action": "view",
+            "scope": "*",
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn create_permission_with_invalid_condition_returns_400() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    for condition in ["owner_id == user.id", "resource.owner_id ==", "user.id = 1"] {
+        let response = app
+            .api_client
+            .post(format!("{}/rbac-demo/permissions", &app.address))
+            .json(&json!({
+                "resource": "report",
+                "action": "view",
+                "scope": "*",
+                "condition": condition,
+            }))
+            .send()
+            .await
+            .expect("Failed to send request");
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{condition}");
+    }
+}
+
+#[tokio::test]
+async fn update_permission_success() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    let permission = insert_permissions(&app.pool, 1).await.pop().unwrap();
+
+    let response = app
+        .api_client
+        .put(format!(
+            "{}/rbac-demo/permissions/{}",
+            &app.address, permission.permission_id
+        ))
+        .json(&json!({
+            "resource": "member",
+            "action": "update",
+            "scope": "team",
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let updated = response.json::<Permission>().await.unwrap();
+    assert_eq!(updated.permission_id, permission.permission_id);
+    assert_eq!(updated.resource, "member");
+    assert_eq!(updated.action, "update");
+    assert_eq!(updated.scope, "team");
+}
+
+#[tokio::test]
+async fn update_permission_to_existing_triple_returns_409() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    let permissions = insert_permissions(&app.pool, 2).await;
+
+    let response = app
+        .api_client
+        .put(format!(
+            "{}/rbac-demo/permissions/{}",
+            &app.address, permissions[0].permission_id
+        ))
+        .json(&json!({
+            "resource": permissions[1].resource,
+            "action": permissions[1].action,
+            "scope": permissions[1].scope,
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn update_unknown_permission_returns_404() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let response = app
+        .api_client
+        .put(format!(
+            "{}/rbac-demo/permissions/{}",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .json(&json!({
+            "resource": "member",
+            "action": "update",
+            "scope": "team",
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_unreferenced_permission_removes_it() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    let permission = insert_permissions(&app.pool, 1).await.pop().unwrap();
+
+    let response = app
+        .api_client
+        .delete(format!(
+            "{}/rbac-demo/permissions/{}",
+            &app.address, permission.permission_id
+        ))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let count = sqlx::query_scalar!(r#"SELECT count(*) as "count!" FROM permissions"#)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn delete_referenced_permission_deprecates_it() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    let permission = insert_permissions(&app.pool, 1).await.pop().unwrap();
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!([permission.permission_id]))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    let response = app
+        .api_client
+        .delete(format!(
+            "{}/rbac-demo/permissions/{}",
+            &app.address, permission.permission_id
+        ))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.json::<Permission>().await.unwrap().deprecated);
+
+    let deprecated = sqlx::query_scalar!(
+        "SELECT deprecated FROM permissions WHERE permission_id = $1",
+        permission.permission_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert!(deprecated);
+}
+
+#[tokio::test]
+async fn deprecated_permission_cannot_be_added_to_role() {
+    let app = spawn_app().await;
+    let permission = insert_permissions(&app.pool, 1).await.pop().unwrap();
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    sqlx::query!(
+        "UPDATE permissions SET deprecated = TRUE WHERE permission_id = $1",
+        permission.permission_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!([permission.permission_id]))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn list_permission_roles_success() {
+    let app = spawn_app().await;
+    let permission = insert_permissions(&app.pool, 1).await.pop().unwrap();
+    let roles = insert_roles(&app.pool, app.organization_id, 3).await;
+    for role in &roles[..2] {
+        app.api_client
+            .post(format!(
+                "{}/rbac-demo/roles/{}/permissions/add",
+                &app.address, role.role_id
+            ))
+            .header("If-Match", "\"1\"")
+            .json(&json!([permission.permission_id]))
+            .send()
+            .await
+            .expect("Failed to send request");
+    }
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/permissions/{}/roles",
+            &app.address, permission.permission_id
+        ))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let role_ids = response
+        .json::<Vec<Role>>()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.role_id)
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        role_ids,
+        roles[..2].iter().map(|r| r.role_id).collect::<HashSet<_>>()
+    );
+}
+
+#[tokio::test]
+async fn list_roles_of_unknown_permission_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/permissions/{}/roles",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 fn extract_resources(permissions: Vec<Permission>) -> HashSet<String> {
     permissions
         .into_iter()
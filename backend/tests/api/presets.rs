@@ -0,0 +1,190 @@
+use crate::helper::{TestApp, spawn_app};
+use axum::http::StatusCode;
+use backend::bootstrap;
+use backend::configuration::BootstrapSettings;
+use backend::rbac_demo::organizations::models::Organization;
+use backend::rbac_demo::rbac::presets::{ADMIN, EXTERNAL_AUDITOR, MANAGER, VIEWER, seed_presets};
+use reqwest::redirect::Policy;
+use secrecy::SecretString;
+use serde_json::json;
+use uuid::Uuid;
+
+async fn system_roles(app: &TestApp, organization_id: Uuid) -> Vec<String> {
+    sqlx::query_scalar!(
+        "SELECT name FROM roles WHERE organization_id = $1 AND is_system ORDER BY name",
+        organization_id
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap()
+}
+
+async fn held_roles(app: &TestApp, user_id: Uuid) -> Vec<String> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT r.name FROM users_roles as ur
+        JOIN roles as r ON r.role_id = ur.role_id
+        WHERE ur.user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn new_organizations_start_with_the_presets() {
+    let app = spawn_app().await;
+
+    let organization = app
+        .api_client
+        .post(format!("{}/rbac-demo/organizations", &app.address))
+        .json(&json!({ "name": "acme" }))
+        .send()
+        .await
+        .expect("Failed to post request")
+        .json::<Organization>()
+        .await
+        .unwrap();
+
+    let mut expected = [ADMIN, EXTERNAL_AUDITOR, MANAGER, VIEWER];
+    expected.sort();
+    assert_eq!(
+        system_roles(&app, organization.organization_id).await,
+        expected
+    );
+    assert_eq!(held_roles(&app, app.test_user.user_id).await, [ADMIN]);
+
+    let components = sqlx::query_scalar!(
+        r#"
+        SELECT c.code FROM roles_components as rc
+        JOIN components as c ON c.component_id = rc.component_id
+        JOIN roles as r ON r.role_id = rc.role_id
+        WHERE r.organization_id = $1 AND r.name = $2
+        ORDER BY c.code
+        "#,
+        organization.organization_id,
+        VIEWER
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(components, ["comp_board_view", "comp_member_list"]);
+
+    let mut connection = app.pool.acquire().await.unwrap();
+    let changes = seed_presets(&mut connection, organization.organization_id)
+        .await
+        .unwrap();
+    assert!(changes.is_empty());
+}
+
+#[tokio::test]
+async fn system_roles_cannot_be_deleted() {
+    let app = spawn_app().await;
+    let mut connection = app.pool.acquire().await.unwrap();
+    seed_presets(&mut connection, app.organization_id)
+        .await
+        .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/rbac/import", &app.address))
+        .body("version: 1")
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let deleted = sqlx::query!(
+        "DELETE FROM roles WHERE organization_id = $1 AND is_system",
+        app.organization_id
+    )
+    .execute(&app.pool)
+    .await;
+    assert!(deleted.is_err());
+    assert_eq!(system_roles(&app, app.organization_id).await.len(), 4);
+}
+
+#[tokio::test]
+async fn bootstrap_creates_the_admin_user_once() {
+    let app = spawn_app().await;
+    let username = Uuid::new_v4().to_string();
+    let settings = BootstrapSettings {
+        admin_username: Some(username.clone()),
+        admin_password: Some(SecretString::from("first-password".to_string())),
+        organization: Uuid::new_v4().to_string(),
+    };
+
+    bootstrap::run(&app.pool, &settings).await.unwrap();
+    let second = BootstrapSettings {
+        admin_password: Some(SecretString::from("second-password".to_string())),
+        ..settings
+    };
+    bootstrap::run(&app.pool, &second).await.unwrap();
+
+    let user_id = sqlx::query_scalar!("SELECT user_id FROM users WHERE username = $1", username)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(held_roles(&app, user_id).await, [ADMIN]);
+
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .redirect(Policy::none())
+        .no_proxy()
+        .build()
+        .unwrap();
+    client
+        .post(format!("{}/login", &app.address))
+        .form(&json!({ "username": username, "password": "first-password" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = client
+        .get(format!("{}/rbac-demo/roles", &app.address))
+        .send()
+        .await
+        .expect("Failed to get roles");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let incomplete = BootstrapSettings {
+        admin_username: Some(username),
+        admin_password: None,
+        organization: "Default".to_string(),
+    };
+    assert!(bootstrap::run(&app.pool, &incomplete).await.is_err());
+}
+
+#[tokio::test]
+async fn seeding_ignores_the_rest_of_the_configuration() {
+    let app = spawn_app().await;
+    sqlx::query!(
+        r#"
+        INSERT INTO permissions (permission_id, resource, action, scope)
+        VALUES (gen_random_uuid(), 'legacy resource!', 'read', '*')
+        "#
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    for _ in 0..2 {
+        sqlx::query!(
+            r#"
+            INSERT INTO roles (role_id, name, description, organization_id)
+            VALUES (gen_random_uuid(), 'twin', '', $1)
+            "#,
+            app.organization_id
+        )
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    }
+
+    let mut connection = app.pool.acquire().await.unwrap();
+    seed_presets(&mut connection, app.organization_id)
+        .await
+        .unwrap();
+
+    assert_eq!(system_roles(&app, app.organization_id).await.len(), 4);
+}
@@ -0,0 +1,160 @@
+use backend::models::AssociationResult;
+use backend::rbac_demo::members::models::MemberProject;
+use backend::rbac_demo::projects::models::{ProjectMember, ProjectRole};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::helper::{TestApp, spawn_app};
+
+async fn post(app: &TestApp, path: &str, body: Value) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo{}", app.address, path))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to send request")
+}
+
+async fn get<T: serde::de::DeserializeOwned>(app: &TestApp, path: &str) -> T {
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo{}", app.address, path))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+async fn create(app: &TestApp, path: &str, body: Value, id: &str) -> uuid::Uuid {
+    let created = post(app, path, body).await.json::<Value>().await.unwrap();
+    created[id].as_str().unwrap().parse().unwrap()
+}
+
+async fn create_member(app: &TestApp, first_name: &str) -> uuid::Uuid {
+    let body = json!({ "first_name": first_name, "last_name": "Doe" });
+    create(app, "/members", body, "member_id").await
+}
+
+async fn create_project(app: &TestApp, name: &str) -> uuid::Uuid {
+    let body = json!({ "name": name, "description": "" });
+    create(app, "/projects", body, "project_id").await
+}
+
+#[tokio::test]
+async fn members_are_added_to_and_removed_from_projects() {
+    let app = spawn_app().await;
+    let project_id = create_project(&app, "apollo").await;
+    let (ada, bob) = (
+        create_member(&app, "Ada").await,
+        create_member(&app, "Bob").await,
+    );
+    let add = format!("/projects/{project_id}/members/add");
+
+    let response = post(
+        &app,
+        &add,
+        json!([
+            { "member_id": ada, "role": "owner" },
+            { "member_id": bob, "role": "contributor" },
+        ]),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = post(&app, &add, json!([{ "member_id": bob, "role": "owner" }])).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let result = response
+        .json::<AssociationResult<uuid::Uuid>>()
+        .await
+        .unwrap();
+    assert_eq!(result.skipped, [bob]);
+
+    let unknown = uuid::Uuid::new_v4();
+    let response = post(
+        &app,
+        &add,
+        json!([{ "member_id": unknown, "role": "owner" }]),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let members = get::<Vec<ProjectMember>>(&app, &format!("/projects/{project_id}/members")).await;
+    let roles = members
+        .iter()
+        .map(|m| (m.first_name.as_str(), m.role))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roles,
+        [
+            ("Ada", ProjectRole::Owner),
+            ("Bob", ProjectRole::Contributor)
+        ]
+    );
+    let projects = get::<Vec<MemberProject>>(&app, &format!("/members/{bob}/projects")).await;
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].project_id, project_id);
+
+    let remove = format!("/projects/{project_id}/members/remove");
+    let response = post(&app, &remove, json!([bob])).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = post(&app, &remove, json!([bob])).await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert!(
+        get::<Vec<MemberProject>>(&app, &format!("/members/{bob}/projects"))
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn deleting_a_member_or_project_ends_its_assignments() {
+    let app = spawn_app().await;
+    let (apollo, gemini) = (
+        create_project(&app, "apollo").await,
+        create_project(&app, "gemini").await,
+    );
+    let (ada, bob) = (
+        create_member(&app, "Ada").await,
+        create_member(&app, "Bob").await,
+    );
+    for project_id in [apollo, gemini] {
+        let response = post(
+            &app,
+            &format!("/projects/{project_id}/members/add"),
+            json!([
+                { "member_id": ada, "role": "maintainer" },
+                { "member_id": bob, "role": "contributor" },
+            ]),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    app.api_client
+        .delete(format!("{}/rbac-demo/projects/{}", app.address, gemini))
+        .send()
+        .await
+        .unwrap();
+    let projects = get::<Vec<MemberProject>>(&app, &format!("/members/{ada}/projects")).await;
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "apollo");
+
+    app.api_client
+        .delete(format!("{}/rbac-demo/members/{}", app.address, bob))
+        .send()
+        .await
+        .unwrap();
+    let members = get::<Vec<ProjectMember>>(&app, &format!("/projects/{apollo}/members")).await;
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].member_id, ada);
+
+    // A deleted member can no longer be assigned.
+    let response = post(
+        &app,
+        &format!("/projects/{apollo}/members/add"),
+        json!([{ "member_id": bob, "role": "contributor" }]),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 
-use backend::models::ListResponse;
-use backend::rbac_demo::projects::models::Project;
+use backend::models::{ExportRequest, ListRequest, ListResponse};
+use backend::rbac_demo::projects::models::{Project, ProjectFilter};
 use fake::{Fake, faker};
 use reqwest::StatusCode;
 use serde_json::json;
 use sqlx::PgPool;
 
-use crate::helper::spawn_app;
+use crate::helper::{TestApp, insert_roles, spawn_app};
 
 #[tokio::test]
 async fn return_200_for_valid_project_data() {
@@ -62,12 +62,13 @@ async fn persist_the_new_project() {
 async fn return_valid_projects_list() {
     let app = spawn_app().await;
 
+    app.grant_permission("project", "read", "*").await;
     let amount = 9;
-    insert_projects(&app.pool, amount).await;
+    insert_projects(&app.pool, app.organization_id, amount).await;
 
     let response = app
         .api_client
-        .get(&format!("{}/rbac-demo/projects", app.address))
+        .get(format!("{}/rbac-demo/projects", app.address))
         .query(&[("current_page", "1"), ("page_size", "10")])
         .send()
         .await
@@ -81,22 +82,154 @@ async fn return_valid_projects_list() {
 }
 
 #[tokio::test]
-async fn return_204_if_successfully_deleted() {
+async fn wildcard_grants_list_every_project() {
     let app = spawn_app().await;
+    app.grant_permission("*", "*", "*").await;
+    insert_projects(&app.pool, app.organization_id, 3).await;
 
-    let projects = insert_projects(&app.pool, 1).await;
-    let project_id = projects.first().unwrap().project_id;
+    let response_body = app
+        .api_client
+        .get(format!("{}/rbac-demo/projects", app.address))
+        .query(&[("current_page", "1"), ("page_size", "10")])
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<ListResponse<Project>>()
+        .await
+        .unwrap();
+
+    assert_eq!(response_body.total, 3);
+}
+
+#[tokio::test]
+async fn conditional_grants_list_the_projects_they_hold_for() {
+    let app = spawn_app().await;
+    app.grant_conditional_permission("project", "read", "*", Some("resource.name == 'apollo'"))
+        .await;
+    insert_projects(&app.pool, app.organization_id, 3).await;
+    sqlx::query!(
+        "INSERT INTO projects (project_id, name, description, organization_id) VALUES ($1, 'apollo', '', $2)",
+        uuid::Uuid::new_v4(),
+        app.organization_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let response_body = app
+        .api_client
+        .get(format!("{}/rbac-demo/projects", app.address))
+        .query(&[("current_page", "1"), ("page_size", "10")])
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<ListResponse<Project>>()
+        .await
+        .unwrap();
+
+    assert_eq!(response_body.total, 1);
+    assert_eq!(response_body.results[0].name, "apollo");
+}
 
+#[tokio::test]
+async fn projects_list_is_limited_to_projects_with_a_role() {
+    let app = spawn_app().await;
+    let projects = insert_projects(&app.pool, app.organization_id, 3).await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
     let response = app
         .api_client
-        .delete(&format!(
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[("project_id", projects[1].project_id)])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response_body = app
+        .api_client
+        .get(format!("{}/rbac-demo/projects", app.address))
+        .query(&[("current_page", "1"), ("page_size", "10")])
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<ListResponse<Project>>()
+        .await
+        .unwrap();
+
+    assert_eq!(response_body.total, 1);
+    assert_eq!(response_body.results.len(), 1);
+    assert_eq!(response_body.results[0].project_id, projects[1].project_id);
+}
+
+#[tokio::test]
+async fn deleting_a_project_revokes_its_role_grants() {
+    let app = spawn_app().await;
+    let project = insert_projects(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[("project_id", project.project_id)])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let response = app
+        .api_client
+        .delete(format!(
             "{}/rbac-demo/projects/{}",
-            app.address, project_id
+            app.address, project.project_id
         ))
         .send()
         .await
         .unwrap();
     assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let held = sqlx::query_scalar!(
+        "SELECT count(*) FROM users_roles WHERE user_id = $1",
+        app.test_user.user_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(held, Some(0));
+    let revoked = sqlx::query_scalar!(
+        "SELECT count(*) FROM users_roles_audit WHERE project_id = $1 AND event = 'revoked'",
+        project.project_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(revoked, Some(1));
+}
+
+#[tokio::test]
+async fn return_204_if_successfully_deleted() {
+    let app = spawn_app().await;
+
+    let projects = insert_projects(&app.pool, app.organization_id, 1).await;
+    let project_id = projects.first().unwrap().project_id;
+
+    let response = app
+        .api_client
+        .delete(format!("{}/rbac-demo/projects/{}", app.address, project_id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
 }
 
 #[tokio::test]
@@ -105,7 +238,7 @@ async fn return_404_if_project_not_found() {
 
     let response = app
         .api_client
-        .delete(&format!(
+        .delete(format!(
             "{}/rbac-demo/projects/{}",
             app.address,
             uuid::Uuid::new_v4()
@@ -116,20 +249,211 @@ async fn return_404_if_project_not_found() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
-async fn insert_projects(pool: &PgPool, amount: u64) -> Vec<Project> {
+#[tokio::test]
+async fn patch_keeps_absent_fields_and_bumps_updated_at() {
+    let app = spawn_app().await;
+    let project = create_project(&app, "apollo").await;
+    assert_eq!(project.created_by, Some(app.test_user.user_id));
+
+    let response = app
+        .api_client
+        .patch(format!(
+            "{}/rbac-demo/projects/{}",
+            app.address, project.project_id
+        ))
+        .json(&json!({ "name": "artemis" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let patched = response.json::<Project>().await.unwrap();
+    assert_eq!(patched.name, "artemis");
+    assert_eq!(patched.description, project.description);
+    assert_eq!(patched.created_at, project.created_at);
+    assert!(patched.updated_at > project.updated_at);
+}
+
+#[tokio::test]
+async fn archived_projects_are_hidden_and_read_only() {
+    let app = spawn_app().await;
+    app.grant_permission("project", "read", "*").await;
+    let project = create_project(&app, "apollo").await;
+    create_project(&app, "gemini").await;
+    let url = format!("{}/rbac-demo/projects/{}", app.address, project.project_id);
+
+    let response = app
+        .api_client
+        .post(format!("{url}/archive"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .api_client
+        .post(format!("{url}/archive"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let listed = list_projects(&app, ProjectFilter::default()).await;
+    assert_eq!(listed.total, 1);
+    assert_eq!(listed.results[0].name, "gemini");
+    let filter = ProjectFilter {
+        include_archived: true,
+        ..Default::default()
+    };
+    assert_eq!(list_projects(&app, filter).await.total, 2);
+
+    let response = app
+        .api_client
+        .patch(&url)
+        .json(&json!({ "description": "Moon" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+        .api_client
+        .post(format!("{url}/restore"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .json::<Project>()
+            .await
+            .unwrap()
+            .archived_at
+            .is_none()
+    );
+    assert_eq!(list_projects(&app, ProjectFilter::default()).await.total, 2);
+}
+
+#[tokio::test]
+async fn conditions_may_grant_the_projects_one_created() {
+    let app = spawn_app().await;
+    app.grant_conditional_permission(
+        "project",
+        "read",
+        "*",
+        Some("resource.created_by == user.id"),
+    )
+    .await;
+    insert_projects(&app.pool, app.organization_id, 2).await;
+    create_project(&app, "apollo").await;
+
+    let listed = list_projects(&app, ProjectFilter::default()).await;
+    assert_eq!(listed.total, 1);
+    assert_eq!(listed.results[0].name, "apollo");
+}
+
+#[tokio::test]
+async fn conditions_comparing_columns_with_numbers_grant_nothing() {
+    let app = spawn_app().await;
+    app.grant_conditional_permission("project", "read", "*", Some("resource.name > 1"))
+        .await;
+    insert_projects(&app.pool, app.organization_id, 2).await;
+
+    let listed = list_projects(&app, ProjectFilter::default()).await;
+    assert_eq!(listed.total, 0);
+}
+
+#[tokio::test]
+async fn export_lists_the_projects_the_list_shows() {
+    let app = spawn_app().await;
+    app.grant_conditional_permission(
+        "project",
+        "read",
+        "*",
+        Some("resource.created_by == user.id"),
+    )
+    .await;
+    insert_projects(&app.pool, app.organization_id, 2).await;
+    let apollo = create_project(&app, "apollo").await;
+    create_project(&app, "gemini").await;
+    let request = ExportRequest {
+        filter: Some(ProjectFilter {
+            name: Some("apollo".to_string()),
+            ..Default::default()
+        }),
+    };
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/projects/export.csv?{}",
+            app.address,
+            serde_qs::to_string(&request).unwrap()
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.text().await.unwrap();
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let exported = reader.records().map(Result::unwrap).collect::<Vec<_>>();
+    assert_eq!(exported.len(), 1);
+    assert_eq!(&exported[0][0], apollo.project_id.to_string());
+    assert_eq!(&exported[0][1], "apollo");
+}
+
+async fn create_project(app: &TestApp, name: &str) -> Project {
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/projects", app.address))
+        .json(&json!({ "name": name, "description": "A mission" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+async fn list_projects(app: &TestApp, filter: ProjectFilter) -> ListResponse<Project> {
+    let request = ListRequest {
+        filter: Some(filter),
+        current_page: 1,
+        page_size: 10,
+    };
+    app.api_client
+        .get(format!(
+            "{}/rbac-demo/projects?{}",
+            app.address,
+            serde_qs::to_string(&request).unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to list projects")
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn insert_projects(pool: &PgPool, organization_id: uuid::Uuid, amount: u64) -> Vec<Project> {
     let projects = (0..amount)
         .map(|_| Project {
             project_id: uuid::Uuid::new_v4(),
             name: faker::lorem::en::Word().fake::<String>(),
             description: faker::lorem::en::Sentence(3..5).fake::<String>(),
+            created_by: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            archived_at: None,
         })
         .collect::<Vec<Project>>();
 
-    let mut qb = sqlx::QueryBuilder::new("INSERT INTO projects (project_id, name, description) ");
+    let mut qb = sqlx::QueryBuilder::new(
+        "INSERT INTO projects (project_id, name, description, organization_id) ",
+    );
     qb.push_values(projects.clone(), |mut b, project| {
         b.push_bind(project.project_id)
             .push_bind(project.name)
-            .push_bind(project.description);
+            .push_bind(project.description)
+            .push_bind(organization_id);
     });
     qb.build().execute(pool).await.unwrap();
 
@@ -0,0 +1,305 @@
+use crate::helper::{TestApp, spawn_app};
+use axum::http::StatusCode;
+use backend::rbac_demo::rbac::document::models::{
+    ChangeKind, DocumentFormat, ImportPlan, ObjectKind, RbacDocument,
+};
+
+async fn export(app: &TestApp, format: &str) -> reqwest::Response {
+    app.api_client
+        .get(format!("{}/rbac-demo/rbac/export", &app.address))
+        .query(&[("format", format)])
+        .send()
+        .await
+        .expect("Failed to get request")
+}
+
+async fn import(app: &TestApp, document: &str, dry_run: bool) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo/rbac/import", &app.address))
+        .query(&[("dry_run", dry_run)])
+        .header("Content-Type", "application/yaml")
+        .body(document.to_string())
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+async fn exported(app: &TestApp) -> RbacDocument {
+    let body = export(app, "yaml").await.text().await.unwrap();
+    DocumentFormat::Yaml.parse(&body).unwrap()
+}
+
+fn editor_document(app: &TestApp) -> String {
+    format!(
+        r#"
+version: 1
+permissions:
+  - {{ resource: project, action: write, scope: "*" }}
+  - {{ resource: project, action: read, scope: "*" }}
+components: [comp_project_editor]
+roles:
+  - name: editor
+    description: Edits projects
+    permissions:
+      - {{ resource: project, action: write, scope: "*" }}
+    components: [comp_project_editor]
+bindings:
+  - {{ user: {}, role: editor }}
+"#,
+        app.test_user.username
+    )
+}
+
+#[tokio::test]
+async fn dry_run_returns_the_plan_without_applying_it() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let response = import(&app, &editor_document(&app), true).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let plan = response.json::<ImportPlan>().await.unwrap();
+
+    assert!(!plan.applied);
+    let kinds = plan
+        .changes
+        .iter()
+        .map(|c| (c.change, c.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            (ChangeKind::Create, ObjectKind::Permission),
+            (ChangeKind::Create, ObjectKind::Permission),
+            (ChangeKind::Create, ObjectKind::Component),
+            (ChangeKind::Create, ObjectKind::Role),
+            (ChangeKind::Create, ObjectKind::RolePermission),
+            (ChangeKind::Create, ObjectKind::RoleComponent),
+            (ChangeKind::Create, ObjectKind::Binding),
+        ]
+    );
+    assert!(exported(&app).await.roles.is_empty());
+}
+
+#[tokio::test]
+async fn imported_documents_export_identically() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+
+    let plan = import(&app, &editor_document(&app), false)
+        .await
+        .json::<ImportPlan>()
+        .await
+        .unwrap();
+    assert!(plan.applied);
+
+    let mut expected = DocumentFormat::Yaml.parse(&editor_document(&app)).unwrap();
+    // Only the permissions the roles use are exported.
+    expected.permissions.retain(|p| p.action == "write");
+    expected.normalize();
+    let yaml = export(&app, "yaml").await.text().await.unwrap();
+    assert_eq!(DocumentFormat::Yaml.parse(&yaml).unwrap(), expected);
+    assert_eq!(export(&app, "yaml").await.text().await.unwrap(), yaml);
+
+    let response = export(&app, "json").await;
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "application/json"
+    );
+    let json = response.json::<RbacDocument>().await.unwrap();
+    assert_eq!(json, expected);
+
+    let plan = import(&app, &yaml, false)
+        .await
+        .json::<ImportPlan>()
+        .await
+        .unwrap();
+    assert!(plan.changes.is_empty());
+    assert!(!plan.applied);
+}
+
+#[tokio::test]
+async fn roles_missing_from_the_document_are_deleted() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import(&app, &editor_document(&app), false).await;
+
+    let document = r#"
+version: 1
+permissions:
+  - { resource: project, action: read, scope: "*" }
+roles:
+  - name: viewer
+    permissions:
+      - { resource: project, action: read, scope: "*" }
+"#;
+    let plan = import(&app, document, false)
+        .await
+        .json::<ImportPlan>()
+        .await
+        .unwrap();
+    assert!(plan.applied);
+    let deleted = plan
+        .changes
+        .iter()
+        .filter(|c| c.change == ChangeKind::Delete)
+        .map(|c| (c.kind, c.key.as_str()))
+        .collect::<Vec<_>>();
+    let binding = format!("{} -> editor", app.test_user.username);
+    assert_eq!(
+        deleted,
+        [
+            (ObjectKind::Binding, binding.as_str()),
+            (ObjectKind::Role, "editor")
+        ]
+    );
+
+    let current = exported(&app).await;
+    assert_eq!(current.roles.len(), 1);
+    assert_eq!(current.roles[0].name, "viewer");
+    assert!(current.bindings.is_empty());
+    // The catalog is shared, nothing is removed from it.
+    assert!(
+        current
+            .components
+            .contains(&"comp_project_editor".to_string())
+    );
+    let permissions =
+        sqlx::query_scalar!("SELECT count(*) FROM permissions WHERE resource = 'project'")
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(permissions, Some(2));
+}
+
+#[tokio::test]
+async fn exports_leave_out_permissions_the_roles_do_not_use() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    import(&app, &editor_document(&app), false).await;
+    sqlx::query!(
+        r#"
+        INSERT INTO permissions (permission_id, resource, action, scope, condition)
+        VALUES (gen_random_uuid(), 'project', 'read', 'own', 'owner == "someone else"')
+        "#
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let current = exported(&app).await;
+    let permissions = current
+        .permissions
+        .iter()
+        .map(|p| p.action.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(permissions, ["write"]);
+}
+
+#[tokio::test]
+async fn imports_never_reactivate_deprecated_permissions() {
+    let app = spawn_app().await;
+    app.make_system_admin().await;
+    sqlx::query!(
+        r#"
+        INSERT INTO permissions (permission_id, resource, action, scope, deprecated)
+        VALUES (gen_random_uuid(), 'project', 'read', '*', TRUE)
+        "#
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let plan = import(&app, &editor_document(&app), false)
+        .await
+        .json::<ImportPlan>()
+        .await
+        .unwrap();
+    let created = plan
+        .changes
+        .iter()
+        .filter(|c| c.kind == ObjectKind::Permission)
+        .count();
+    assert_eq!(created, 1);
+
+    let document = r#"
+version: 1
+permissions:
+  - { resource: project, action: read, scope: "*" }
+roles:
+  - name: viewer
+    permissions:
+      - { resource: project, action: read, scope: "*" }
+"#;
+    let response = import(&app, document, false).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let deprecated = sqlx::query_scalar!(
+        "SELECT deprecated FROM permissions WHERE resource = 'project' AND action = 'read'"
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert!(deprecated);
+}
+
+#[tokio::test]
+async fn only_system_admins_add_to_the_catalog() {
+    let app = spawn_app().await;
+
+    let response = import(&app, &editor_document(&app), false).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let permissions = sqlx::query_scalar!("SELECT count(*) FROM permissions")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(permissions, Some(0));
+
+    app.make_system_admin().await;
+    import(&app, &editor_document(&app), false).await;
+    sqlx::query!(
+        "UPDATE users SET is_system_admin = FALSE WHERE user_id = $1",
+        app.test_user.user_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    // Documents using only what the catalog has need no system admin.
+    let document = editor_document(&app)
+        .replace("name: editor", "name: author")
+        .replace("role: editor", "role: author");
+    let response = import(&app, &document, false).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(exported(&app).await.roles[0].name, "author");
+}
+
+#[tokio::test]
+async fn invalid_documents_are_rejected() {
+    let app = spawn_app().await;
+    let cases = [
+        ("version: 2", "unsupported version"),
+        ("version: 1\nunknown: []", "unknown field"),
+        (
+            "version: 1\nroles:\n  - name: a\n    components: [comp_x]",
+            "undeclared component",
+        ),
+        (
+            "version: 1\nroles:\n  - name: a\n  - name: a",
+            "duplicate role",
+        ),
+        (
+            "version: 1\nroles:\n  - name: a\nbindings:\n  - { user: nobody, role: a }",
+            "unknown user",
+        ),
+        (
+            "version: 1\npermissions:\n  - { resource: 'pro*ject', action: read, scope: '*' }",
+            "invalid permission",
+        ),
+    ];
+
+    for (document, case) in cases {
+        let response = import(&app, document, false).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{case}");
+    }
+    assert!(exported(&app).await.roles.is_empty());
+}
@@ -1,10 +1,12 @@
-use crate::helper::{insert_permissions, spawn_app};
+use crate::helper::{TestApp, insert_permissions, insert_roles, spawn_app};
 use axum::http::StatusCode;
-use backend::models::ListResponse;
+use backend::models::{AssociationResult, ListResponse};
+use backend::rbac_demo::rbac::components::models::Component;
 use backend::rbac_demo::rbac::permissions::models::Permission;
-use backend::rbac_demo::rbac::roles::models::Role;
-use fake::Fake;
-use serde_json::json;
+use backend::rbac_demo::rbac::roles::models::{
+    Role, RoleComparison, RoleVersion, RoleVersionDiff, UnknownPermissions,
+};
+use serde_json::{Value, json};
 
 #[tokio::test]
 async fn roles_return_200_for_valid_data() {
@@ -39,6 +41,7 @@ async fn persist_the_new_roles() {
         .send()
         .await
         .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
 
     let saved = sqlx::query!(
         r#"
@@ -59,7 +62,7 @@ async fn add_permissions_to_role_success() {
     let app = spawn_app().await;
     let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
     let role = {
-        let mut roles = insert_roles(&app.pool, 2).await;
+        let mut roles = insert_roles(&app.pool, app.organization_id, 2).await;
         roles.pop().unwrap()
     };
     let response = app
@@ -68,6 +71,7 @@ async fn add_permissions_to_role_success() {
             "{}/rbac-demo/roles/{}/permissions/add",
             &app.address, role.role_id
         ))
+        .header("If-Match", "\"1\"")
         .json(&json!(permissions))
         .send()
         .await
@@ -81,7 +85,7 @@ async fn remove_permissions_from_role_success() {
     let app = spawn_app().await;
     let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
     let role = {
-        let mut roles = insert_roles(&app.pool, 2).await;
+        let mut roles = insert_roles(&app.pool, app.organization_id, 2).await;
         roles.pop().unwrap()
     };
 
@@ -90,6 +94,7 @@ async fn remove_permissions_from_role_success() {
             "{}/rbac-demo/roles/{}/permissions/add",
             &app.address, role.role_id
         ))
+        .header("If-Match", "\"1\"")
         .json(&json!(permissions))
         .send()
         .await
@@ -101,6 +106,7 @@ async fn remove_permissions_from_role_success() {
             "{}/rbac-demo/roles/{}/permissions/remove",
             &app.address, role.role_id
         ))
+        .header("If-Match", "\"2\"")
         .json(&json!(permissions))
         .send()
         .await
@@ -114,7 +120,7 @@ async fn invalid_role_will_should_rejected() {
     let app = spawn_app().await;
     let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
     let role = {
-        let mut roles = insert_roles(&app.pool, 2).await;
+        let mut roles = insert_roles(&app.pool, app.organization_id, 2).await;
         roles.pop().unwrap()
     };
 
@@ -125,12 +131,21 @@ async fn invalid_role_will_should_rejected() {
             &app.address,
             uuid::Uuid::new_v4()
         ))
+        .header("If-Match", "\"1\"")
         .json(&json!(permissions))
         .send()
         .await
         .expect("Failed to post request");
 
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let granted = sqlx::query_scalar!(
+        "SELECT count(*) FROM roles_permissions WHERE role_id = $1",
+        role.role_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(granted, Some(0));
 }
 
 #[tokio::test]
@@ -138,7 +153,7 @@ async fn invalid_permissions_should_be_rejected() {
     let app = spawn_app().await;
     let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
     let role = {
-        let mut roles = insert_roles(&app.pool, 2).await;
+        let mut roles = insert_roles(&app.pool, app.organization_id, 2).await;
         roles.pop().unwrap()
     };
 
@@ -148,6 +163,7 @@ async fn invalid_permissions_should_be_rejected() {
             "{}/rbac-demo/roles/{}/permissions/add",
             &app.address, role.role_id
         ))
+        .header("If-Match", "\"1\"")
         .json(&json!([vec![uuid::Uuid::new_v4()], permissions].concat()))
         .send()
         .await
@@ -159,7 +175,7 @@ async fn invalid_permissions_should_be_rejected() {
 #[tokio::test]
 async fn list_roles_success() {
     let app = spawn_app().await;
-    let roles = insert_roles(&app.pool, 2).await;
+    let roles = insert_roles(&app.pool, app.organization_id, 2).await;
     let response = app
         .api_client
         .get(format!("{}/rbac-demo/roles", &app.address))
@@ -180,7 +196,7 @@ async fn list_roles_success() {
 async fn list_role_permissions_success() {
     let app = spawn_app().await;
     let role = {
-        let mut roles = insert_roles(&app.pool, 2).await;
+        let mut roles = insert_roles(&app.pool, app.organization_id, 2).await;
         roles.pop().unwrap()
     };
     let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
@@ -189,6 +205,7 @@ async fn list_role_permissions_success() {
             "{}/rbac-demo/roles/{}/permissions/add",
             &app.address, role.role_id
         ))
+        .header("If-Match", "\"1\"")
         .json(&json!(permissions))
         .send()
         .await
@@ -211,29 +228,581 @@ async fn list_role_permissions_success() {
     assert_eq!(permissions.len(), 2);
 }
 
-async fn insert_roles(pgpool: &sqlx::PgPool, amount: u64) -> Vec<Role> {
-    let roles: Vec<Role> = (1..=amount)
-        .map(|_| Role {
-            role_id: uuid::Uuid::new_v4(),
-            name: fake::faker::lorem::en::Word().fake::<String>(),
-            description: fake::faker::lorem::en::Sentence(1..5).fake::<String>(),
-        })
+#[tokio::test]
+async fn get_role_returns_version_as_etag() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/roles/{}", &app.address, role.role_id))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ETag"], "\"1\"");
+    let body = response.json::<Role>().await.unwrap();
+    assert_eq!(body.role_id, role.role_id);
+    assert_eq!(body.version, 1);
+}
+
+#[tokio::test]
+async fn get_unknown_role_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/roles/{}",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn editing_role_bumps_its_version() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(permissions))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ETag"], "\"2\"");
+    let version = sqlx::query_scalar!("SELECT version FROM roles WHERE role_id = $1", role.role_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(version, 2);
+}
+
+#[tokio::test]
+async fn editing_role_without_if_match_returns_428() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .json(&json!(permissions))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_REQUIRED);
+}
+
+#[tokio::test]
+async fn editing_stale_role_version_returns_412() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    // two admins loaded version 1, the first one saves
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(permissions[..1]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/remove",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(permissions[..1]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    let count = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM roles_permissions WHERE role_id = $1"#,
+        role.role_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1, "The stale edit must not be applied");
+}
+
+#[tokio::test]
+async fn add_and_remove_role_components_success() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(["comp_member_list", "comp_board_view"]))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ETag"], "\"2\"");
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/remove",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"2\"")
+        .json(&json!(["comp_board_view"]))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ETag"], "\"3\"");
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/roles/{}/components",
+            &app.address, role.role_id
+        ))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let codes = response
+        .json::<Vec<Component>>()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.code)
         .collect::<Vec<_>>();
+    assert_eq!(codes, vec!["comp_member_list"]);
+}
+
+#[tokio::test]
+async fn editing_components_of_stale_role_returns_412() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"0\"")
+        .json(&json!(["comp_member_list"]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[tokio::test]
+async fn adding_partly_present_permissions_returns_207() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(permissions[..1]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"2\"")
+        .json(&json!(permissions))
+        .send()
+        .await
+        .expect("Failed to post request");
 
-    let mut query_builder =
-        sqlx::QueryBuilder::new("INSERT INTO roles (role_id, name, description) ");
-    query_builder.push_values(roles.clone(), |mut query, role| {
-        query
-            .push_bind(role.role_id)
-            .push_bind(role.name)
-            .push_bind(role.description);
-    });
-    let query = query_builder.build();
-    query.execute(pgpool).await.unwrap();
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    assert_eq!(response.headers()["ETag"], "\"3\"");
+    let result = response
+        .json::<AssociationResult<uuid::Uuid>>()
+        .await
+        .unwrap();
+    assert_eq!(result.added, permissions[1..]);
+    assert_eq!(result.skipped, permissions[..1]);
+}
 
-    roles
+#[tokio::test]
+async fn adding_only_present_permissions_keeps_role_version() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 1).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    for version in ["\"1\"", "\"2\""] {
+        app.api_client
+            .post(format!(
+                "{}/rbac-demo/roles/{}/permissions/add",
+                &app.address, role.role_id
+            ))
+            .header("If-Match", version)
+            .json(&json!(permissions))
+            .send()
+            .await
+            .expect("Failed to post request");
+    }
+
+    let version = sqlx::query_scalar!("SELECT version FROM roles WHERE role_id = $1", role.role_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(version, 2);
+}
+
+#[tokio::test]
+async fn removing_partly_missing_permissions_returns_207() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(permissions[..1]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/remove",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"2\"")
+        .json(&json!(permissions))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    let result = response
+        .json::<AssociationResult<uuid::Uuid>>()
+        .await
+        .unwrap();
+    assert_eq!(result.removed, permissions[..1]);
+    assert_eq!(result.missing, permissions[1..]);
+}
+
+#[tokio::test]
+async fn unknown_permissions_are_named_in_404_body() {
+    let app = spawn_app().await;
+    let permissions = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    let unknown = uuid::Uuid::new_v4();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!([vec![unknown], permissions].concat()))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = response.json::<UnknownPermissions>().await.unwrap();
+    assert_eq!(body.permission_ids, vec![unknown]);
+}
+
+#[tokio::test]
+async fn adding_partly_present_components_returns_207() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(["comp_member_list"]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"2\"")
+        .json(&json!(["comp_member_list", "comp_board_view"]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    let result = response.json::<AssociationResult<String>>().await.unwrap();
+    assert_eq!(result.added, vec!["comp_board_view"]);
+    assert_eq!(result.skipped, vec!["comp_member_list"]);
+}
+
+#[tokio::test]
+async fn removing_missing_components_returns_412() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/components/remove",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&json!(["comp_member_list"]))
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(response.headers()["ETag"], "\"1\"");
+    let result = response.json::<AssociationResult<String>>().await.unwrap();
+    assert_eq!(result.missing, vec!["comp_member_list"]);
 }
 
 fn extract_permission_ids(permissions: Vec<Permission>) -> Vec<uuid::Uuid> {
     permissions.into_iter().map(|p| p.permission_id).collect()
 }
+
+async fn edit_role(app: &TestApp, role_id: uuid::Uuid, path: &str, version: i32, body: Value) {
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/{}",
+            &app.address, role_id, path
+        ))
+        .header("If-Match", format!("\"{version}\""))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn compare_roles_returns_set_differences() {
+    let app = spawn_app().await;
+    let permissions = insert_permissions(&app.pool, 3).await;
+    let ids = extract_permission_ids(permissions.clone());
+    let mut roles = insert_roles(&app.pool, app.organization_id, 2).await;
+    let (manager, viewer) = (roles.remove(0), roles.remove(0));
+    edit_role(&app, manager.role_id, "permissions/add", 1, json!(ids[..2])).await;
+    edit_role(&app, viewer.role_id, "permissions/add", 1, json!(ids[1..])).await;
+    edit_role(
+        &app,
+        manager.role_id,
+        "components/add",
+        2,
+        json!(["comp_a", "comp_b"]),
+    )
+    .await;
+    edit_role(&app, viewer.role_id, "components/add", 2, json!(["comp_b"])).await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/roles/compare", &app.address))
+        .query(&[("a", manager.role_id), ("b", viewer.role_id)])
+        .send()
+        .await
+        .expect("Failed to get request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let comparison = response.json::<RoleComparison>().await.unwrap();
+
+    assert_eq!(comparison.a.role_id, manager.role_id);
+    let only_in_a = extract_permission_ids(comparison.permissions.only_in_a);
+    let only_in_b = extract_permission_ids(comparison.permissions.only_in_b);
+    let common = extract_permission_ids(comparison.permissions.common);
+    assert_eq!(only_in_a, [ids[0]]);
+    assert_eq!(only_in_b, [ids[2]]);
+    assert_eq!(common, [ids[1]]);
+    assert_eq!(comparison.components.only_in_a, ["comp_a"]);
+    assert!(comparison.components.only_in_b.is_empty());
+    assert_eq!(comparison.components.common, ["comp_b"]);
+
+    let response = app
+        .api_client
+        .get(format!("{}/rbac-demo/roles/compare", &app.address))
+        .query(&[("a", manager.role_id), ("b", uuid::Uuid::new_v4())])
+        .send()
+        .await
+        .expect("Failed to get request");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn history_diff_shows_changes_between_versions() {
+    let app = spawn_app().await;
+    let ids = extract_permission_ids(insert_permissions(&app.pool, 2).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    edit_role(&app, role.role_id, "permissions/add", 1, json!(ids)).await;
+    edit_role(&app, role.role_id, "components/add", 2, json!(["comp_a"])).await;
+    edit_role(&app, role.role_id, "permissions/remove", 3, json!([ids[0]])).await;
+
+    let history = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/roles/{}/history",
+            &app.address, role.role_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<Vec<RoleVersion>>()
+        .await
+        .unwrap();
+    let versions = history.iter().map(|v| v.version).collect::<Vec<_>>();
+    assert_eq!(versions, [1, 2, 3, 4]);
+
+    let diff = |from: i32, to: i32| {
+        app.api_client
+            .get(format!(
+                "{}/rbac-demo/roles/{}/history/diff",
+                &app.address, role.role_id
+            ))
+            .query(&[("from", from), ("to", to)])
+            .send()
+    };
+
+    let response = diff(2, 4).await.expect("Failed to get request");
+    assert_eq!(response.status(), StatusCode::OK);
+    let changes = response.json::<RoleVersionDiff>().await.unwrap();
+    assert!(changes.permissions.added.is_empty());
+    assert_eq!(
+        extract_permission_ids(changes.permissions.removed),
+        [ids[0]]
+    );
+    assert_eq!(changes.components.added, ["comp_a"]);
+    assert!(changes.components.removed.is_empty());
+    assert!(changes.description.is_none());
+
+    let back = diff(4, 1)
+        .await
+        .expect("Failed to get request")
+        .json::<RoleVersionDiff>()
+        .await
+        .unwrap();
+    assert_eq!(extract_permission_ids(back.permissions.removed), [ids[1]]);
+    assert_eq!(back.components.removed, ["comp_a"]);
+
+    let response = diff(1, 9).await.expect("Failed to get request");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn edits_rolled_back_leave_no_history() {
+    let app = spawn_app().await;
+    let ids = extract_permission_ids(insert_permissions(&app.pool, 1).await);
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .pop()
+        .unwrap();
+    edit_role(&app, role.role_id, "permissions/add", 1, json!(ids)).await;
+
+    // Adding a permission already held changes nothing and keeps version 2.
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"2\"")
+        .json(&json!(ids))
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let versions = sqlx::query_scalar!(
+        "SELECT version FROM roles_history WHERE role_id = $1 ORDER BY version",
+        role.role_id
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(versions, [1, 2]);
+}
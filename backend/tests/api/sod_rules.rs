@@ -0,0 +1,224 @@
+use crate::helper::{TestApp, insert_roles, spawn_app};
+use axum::http::StatusCode;
+use backend::rbac_demo::rbac::sod_rules::models::{SodConflict, SodRule, SodViolation};
+use serde_json::{Value, json};
+
+async fn create_rule(app: &TestApp, rule: Value) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo/sod-rules", &app.address))
+        .json(&rule)
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+async fn grant(app: &TestApp, user_id: uuid::Uuid, roles: &[uuid::Uuid]) -> reqwest::Response {
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, user_id
+        ))
+        .json(roles)
+        .send()
+        .await
+        .expect("Failed to post request")
+}
+
+#[tokio::test]
+async fn mutually_exclusive_roles_cannot_be_held_together() {
+    let app = spawn_app().await;
+    let roles = insert_roles(&app.pool, app.organization_id, 2).await;
+    let response = create_rule(
+        &app,
+        json!({
+            "kind": "mutually_exclusive",
+            "role_id": roles[0].role_id,
+            "other_role_id": roles[1].role_id,
+            "description": "editors do not audit their own work",
+        }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = grant(&app, app.test_user.user_id, &[roles[0].role_id]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = grant(&app, app.test_user.user_id, &[roles[1].role_id]).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let conflict = response
+        .json::<SodConflict>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(conflict.violations.len(), 1);
+    let message = &conflict.violations[0].message;
+    assert!(message.contains(&roles[0].name));
+    assert!(message.contains(&roles[1].name));
+    assert!(message.contains("editors do not audit their own work"));
+
+    let held = sqlx::query_scalar!(
+        "SELECT count(*) FROM users_roles WHERE user_id = $1",
+        app.test_user.user_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .expect("Failed to count grants");
+    assert_eq!(held, Some(1));
+}
+
+#[tokio::test]
+async fn roles_cannot_exceed_their_max_holders() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    create_rule(
+        &app,
+        json!({
+            "kind": "max_holders",
+            "role_id": role.role_id,
+            "max_holders": 1,
+        }),
+    )
+    .await;
+    let (other, _) = app.login_new_user().await;
+
+    let response = grant(&app, app.test_user.user_id, &[role.role_id]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = grant(&app, other.user_id, &[role.role_id]).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let conflict = response
+        .json::<SodConflict>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(conflict.violations[0].user_ids.len(), 2);
+}
+
+#[tokio::test]
+async fn existing_violations_are_reported() {
+    let app = spawn_app().await;
+    let roles = insert_roles(&app.pool, app.organization_id, 2).await;
+    grant(
+        &app,
+        app.test_user.user_id,
+        &[roles[0].role_id, roles[1].role_id],
+    )
+    .await;
+    let rule = create_rule(
+        &app,
+        json!({
+            "kind": "mutually_exclusive",
+            "role_id": roles[0].role_id,
+            "other_role_id": roles[1].role_id,
+        }),
+    )
+    .await
+    .json::<SodRule>()
+    .await
+    .expect("Failed to parse response body");
+
+    let violations = app
+        .api_client
+        .get(format!("{}/rbac-demo/sod-rules/violations", &app.address))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<Vec<SodViolation>>()
+        .await
+        .expect("Failed to parse response body");
+
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].sod_rule_id, rule.sod_rule_id);
+    assert_eq!(violations[0].user_ids, vec![app.test_user.user_id]);
+}
+
+#[tokio::test]
+async fn invalid_rules_return_400() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let cases = [
+        json!({ "kind": "mutually_exclusive", "role_id": role.role_id }),
+        json!({
+            "kind": "mutually_exclusive",
+            "role_id": role.role_id,
+            "other_role_id": role.role_id,
+        }),
+        json!({ "kind": "max_holders", "role_id": role.role_id, "max_holders": 0 }),
+    ];
+
+    for case in cases {
+        let response = create_rule(&app, case.clone()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{case}");
+    }
+}
+
+#[tokio::test]
+async fn the_same_pair_cannot_be_constrained_twice() {
+    let app = spawn_app().await;
+    let roles = insert_roles(&app.pool, app.organization_id, 2).await;
+    create_rule(
+        &app,
+        json!({
+            "kind": "mutually_exclusive",
+            "role_id": roles[0].role_id,
+            "other_role_id": roles[1].role_id,
+        }),
+    )
+    .await;
+
+    let response = create_rule(
+        &app,
+        json!({
+            "kind": "mutually_exclusive",
+            "role_id": roles[1].role_id,
+            "other_role_id": roles[0].role_id,
+        }),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn deleted_rules_no_longer_apply() {
+    let app = spawn_app().await;
+    let roles = insert_roles(&app.pool, app.organization_id, 2).await;
+    let rule = create_rule(
+        &app,
+        json!({
+            "kind": "mutually_exclusive",
+            "role_id": roles[0].role_id,
+            "other_role_id": roles[1].role_id,
+        }),
+    )
+    .await
+    .json::<SodRule>()
+    .await
+    .expect("Failed to parse response body");
+
+    let url = format!("{}/rbac-demo/sod-rules/{}", &app.address, rule.sod_rule_id);
+    let response = app
+        .api_client
+        .delete(&url)
+        .send()
+        .await
+        .expect("Failed to delete request");
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = app
+        .api_client
+        .delete(&url)
+        .send()
+        .await
+        .expect("Failed to delete request");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = grant(
+        &app,
+        app.test_user.user_id,
+        &[roles[0].role_id, roles[1].role_id],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,286 @@
+use backend::models::{ListRequest, ListResponse};
+use backend::rbac_demo::tasks::models::{ArchivedTasks, Task, TaskFilter, TaskStatus};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::helper::{TestApp, spawn_app};
+
+async fn create_project(app: &TestApp) -> uuid::Uuid {
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/projects", app.address))
+        .json(&json!({ "name": "apollo", "description": "" }))
+        .send()
+        .await
+        .expect("Failed to post project");
+    response.json::<Value>().await.unwrap()["project_id"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+async fn create_task(app: &TestApp, body: Value) -> reqwest::Response {
+    app.api_client
+        .post(format!("{}/rbac-demo/tasks", app.address))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to post task")
+}
+
+async fn create_tasks(app: &TestApp, project_id: uuid::Uuid, titles: &[&str]) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    for title in titles {
+        let response = create_task(app, json!({ "project_id": project_id, "title": title })).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        tasks.push(response.json().await.unwrap());
+    }
+    tasks
+}
+
+async fn move_task(app: &TestApp, task_id: uuid::Uuid, status: &str, position: u32) -> Task {
+    let response = app
+        .api_client
+        .post(format!("{}/rbac-demo/tasks/{}/move", app.address, task_id))
+        .json(&json!({ "status": status, "position": position }))
+        .send()
+        .await
+        .expect("Failed to move task");
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+async fn list_tasks(app: &TestApp, filter: TaskFilter) -> ListResponse<Task> {
+    let request = ListRequest {
+        filter: Some(filter),
+        current_page: 1,
+        page_size: 50,
+    };
+    app.api_client
+        .get(format!(
+            "{}/rbac-demo/tasks?{}",
+            app.address,
+            serde_qs::to_string(&request).unwrap()
+        ))
+        .send()
+        .await
+        .expect("Failed to list tasks")
+        .json()
+        .await
+        .unwrap()
+}
+
+/// Titles of a column, in board order.
+async fn column(app: &TestApp, project_id: uuid::Uuid, status: TaskStatus) -> Vec<String> {
+    let filter = TaskFilter {
+        project_id: Some(project_id),
+        status: Some(status),
+        ..Default::default()
+    };
+    list_tasks(app, filter)
+        .await
+        .results
+        .into_iter()
+        .enumerate()
+        .map(|(i, task)| {
+            assert_eq!(task.position, i as i32, "positions are dense");
+            task.title
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn tasks_are_created_at_the_end_of_their_column() {
+    let app = spawn_app().await;
+    let project_id = create_project(&app).await;
+
+    let tasks = create_tasks(&app, project_id, &["design", "build"]).await;
+    assert_eq!(tasks[0].status, TaskStatus::Todo);
+    assert_eq!(tasks[1].position, 1);
+    assert_eq!(
+        column(&app, project_id, TaskStatus::Todo).await,
+        ["design", "build"]
+    );
+
+    let response = create_task(
+        &app,
+        json!({ "project_id": uuid::Uuid::new_v4(), "title": "lost" }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = create_task(&app, json!({ "project_id": project_id, "title": "" })).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn task_is_reassigned_and_unassigned() {
+    let app = spawn_app().await;
+    let project_id = create_project(&app).await;
+    let member = app
+        .api_client
+        .post(format!("{}/rbac-demo/members", app.address))
+        .json(&json!({ "first_name": "Ada", "last_name": "Lovelace" }))
+        .send()
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap();
+    let task = &create_tasks(&app, project_id, &["design"]).await[0];
+    let url = format!("{}/rbac-demo/tasks/{}", app.address, task.task_id);
+
+    let patched = app
+        .api_client
+        .patch(&url)
+        .json(&json!({ "assignee_id": member["member_id"] }))
+        .send()
+        .await
+        .unwrap()
+        .json::<Task>()
+        .await
+        .unwrap();
+    assert_eq!(
+        patched.assignee_id.map(|id| id.to_string()),
+        member["member_id"].as_str().map(str::to_string)
+    );
+
+    let response = app
+        .api_client
+        .patch(&url)
+        .json(&json!({ "title": "redesign" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.json::<Task>().await.unwrap().assignee_id,
+        patched.assignee_id
+    );
+
+    let response = app
+        .api_client
+        .patch(&url)
+        .json(&json!({ "assignee_id": null }))
+        .send()
+        .await
+        .unwrap();
+    let task = response.json::<Task>().await.unwrap();
+    assert_eq!(task.title, "redesign");
+    assert!(task.assignee_id.is_none());
+
+    let response = app
+        .api_client
+        .patch(&url)
+        .json(&json!({ "assignee_id": uuid::Uuid::new_v4() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn tasks_move_within_and_between_columns() {
+    let app = spawn_app().await;
+    let project_id = create_project(&app).await;
+    let tasks = create_tasks(&app, project_id, &["a", "b", "c", "d"]).await;
+
+    move_task(&app, tasks[2].task_id, "todo", 0).await;
+    assert_eq!(
+        column(&app, project_id, TaskStatus::Todo).await,
+        ["c", "a", "b", "d"]
+    );
+    move_task(&app, tasks[2].task_id, "todo", 2).await;
+    assert_eq!(
+        column(&app, project_id, TaskStatus::Todo).await,
+        ["a", "b", "c", "d"]
+    );
+
+    let moved = move_task(&app, tasks[1].task_id, "in_progress", 7).await;
+    assert_eq!(moved.position, 0);
+    move_task(&app, tasks[3].task_id, "in_progress", 0).await;
+    assert_eq!(column(&app, project_id, TaskStatus::Todo).await, ["a", "c"]);
+    assert_eq!(
+        column(&app, project_id, TaskStatus::InProgress).await,
+        ["d", "b"]
+    );
+
+    let response = app
+        .api_client
+        .delete(format!(
+            "{}/rbac-demo/tasks/{}",
+            app.address, tasks[0].task_id
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(column(&app, project_id, TaskStatus::Todo).await, ["c"]);
+}
+
+#[tokio::test]
+async fn completed_tasks_are_archived_in_bulk() {
+    let app = spawn_app().await;
+    let project_id = create_project(&app).await;
+    let tasks = create_tasks(&app, project_id, &["a", "b", "c"]).await;
+    move_task(&app, tasks[0].task_id, "done", 0).await;
+    move_task(&app, tasks[2].task_id, "done", 0).await;
+
+    let response = app
+        .api_client
+        .put(format!("{}/rbac-demo/tasks/archive", app.address))
+        .json(&json!({ "project_id": project_id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut archived = response.json::<ArchivedTasks>().await.unwrap().archived;
+    archived.sort();
+    let mut expected = vec![tasks[0].task_id, tasks[2].task_id];
+    expected.sort();
+    assert_eq!(archived, expected);
+
+    assert_eq!(list_tasks(&app, TaskFilter::default()).await.total, 1);
+    let filter = TaskFilter {
+        include_archived: true,
+        ..Default::default()
+    };
+    assert_eq!(list_tasks(&app, filter).await.total, 3);
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/tasks/{}/move",
+            app.address, tasks[0].task_id
+        ))
+        .json(&json!({ "status": "todo", "position": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn tasks_of_archived_projects_are_read_only() {
+    let app = spawn_app().await;
+    let project_id = create_project(&app).await;
+    let task = &create_tasks(&app, project_id, &["a"]).await[0];
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/projects/{}/archive",
+            app.address, project_id
+        ))
+        .send()
+        .await
+        .unwrap();
+
+    let response = create_task(&app, json!({ "project_id": project_id, "title": "b" })).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response = app
+        .api_client
+        .patch(format!("{}/rbac-demo/tasks/{}", app.address, task.task_id))
+        .json(&json!({ "title": "renamed" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
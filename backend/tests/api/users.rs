@@ -0,0 +1,524 @@
+use crate::helper::{insert_permissions, insert_roles, spawn_app};
+use axum::http::StatusCode;
+use backend::authorization::EffectivePermissions;
+use backend::models::AssociationResult;
+use backend::rbac_demo::rbac::users::models::{RoleGrant, UnknownRoles};
+use chrono::Utc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn assigned_roles_are_listed() {
+    let app = spawn_app().await;
+    let roles = insert_roles(&app.pool, app.organization_id, 2).await;
+    let role_ids = roles.iter().map(|r| r.role_id).collect::<Vec<_>>();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .json(&role_ids)
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let listed = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/users/{}/roles",
+            &app.address, app.test_user.user_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<Vec<RoleGrant>>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(listed.len(), 2);
+}
+
+#[tokio::test]
+async fn assigning_a_role_twice_returns_409() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let url = format!(
+        "{}/rbac-demo/users/{}/roles/add",
+        &app.address, app.test_user.user_id
+    );
+    app.api_client
+        .post(&url)
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let response = app
+        .api_client
+        .post(&url)
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let result = response
+        .json::<AssociationResult<uuid::Uuid>>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(result.skipped, vec![role.role_id]);
+}
+
+#[tokio::test]
+async fn assigning_unknown_roles_returns_404_naming_them() {
+    let app = spawn_app().await;
+    let unknown = uuid::Uuid::new_v4();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .json(&[unknown])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = response
+        .json::<UnknownRoles>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(body.role_ids, vec![unknown]);
+}
+
+#[tokio::test]
+async fn unknown_user_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/users/{}/permissions",
+            &app.address,
+            uuid::Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to get request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn cached_permissions_follow_role_changes() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let permissions = insert_permissions(&app.pool, 2).await;
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&[permissions[0].permission_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let first = get_permissions(&app).await;
+    assert_eq!(first.permissions.len(), 1);
+    // Served from the cache this time.
+    assert_eq!(get_permissions(&app).await.permissions.len(), 1);
+
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"2\"")
+        .json(&[permissions[1].permission_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    // Invalidation is asynchronous, give the listener a moment.
+    let mut updated = get_permissions(&app).await;
+    for _ in 0..50 {
+        if updated.permissions.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        updated = get_permissions(&app).await;
+    }
+    assert_eq!(updated.permissions.len(), 2);
+
+    let metrics = app
+        .api_client
+        .get(format!("{}/metrics", &app.address))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .text()
+        .await
+        .expect("Failed to read response body");
+    assert!(metrics.contains("permission_cache_hits_total"));
+    assert!(!metrics.contains("permission_cache_hits_total 0\n"));
+}
+
+#[tokio::test]
+async fn users_leaving_the_organization_are_evicted_from_the_cache() {
+    let app = spawn_app().await;
+    let (user, _) = app.login_new_user().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let permission = insert_permissions(&app.pool, 1).await.remove(0);
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&[permission.permission_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, user.user_id
+        ))
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    let url = format!(
+        "{}/rbac-demo/users/{}/permissions",
+        &app.address, user.user_id
+    );
+    let permissions_of_user = || async {
+        app.api_client
+            .get(&url)
+            .send()
+            .await
+            .expect("Failed to get request")
+            .json::<EffectivePermissions>()
+            .await
+            .expect("Failed to parse response body")
+    };
+    assert_eq!(permissions_of_user().await.permissions.len(), 1);
+
+    let organization_users = format!(
+        "{}/rbac-demo/organizations/{}/users",
+        &app.address, app.organization_id
+    );
+    for change in ["remove", "add"] {
+        let response = app
+            .api_client
+            .post(format!("{organization_users}/{change}"))
+            .json(&[user.user_id])
+            .send()
+            .await
+            .expect("Failed to post request");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Invalidation is asynchronous, give the listener a moment.
+    let mut rejoined = permissions_of_user().await;
+    for _ in 0..50 {
+        if rejoined.permissions.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        rejoined = permissions_of_user().await;
+    }
+    assert!(rejoined.permissions.is_empty());
+}
+
+#[tokio::test]
+async fn grants_are_effective_only_within_their_window() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let permission = insert_permissions(&app.pool, 1).await.remove(0);
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&[permission.permission_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    let valid_from = Utc::now() + chrono::Duration::days(1);
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[("valid_from", valid_from.to_rfc3339())])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let grants = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/users/{}/roles",
+            &app.address, app.test_user.user_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<Vec<RoleGrant>>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].valid_from.timestamp(), valid_from.timestamp());
+    assert!(get_permissions(&app).await.permissions.is_empty());
+}
+
+#[tokio::test]
+async fn grants_ending_in_the_past_are_rejected() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[(
+            "valid_until",
+            (Utc::now() - chrono::Duration::hours(1)).to_rfc3339(),
+        )])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn expired_grants_are_purged_and_audited() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let permission = insert_permissions(&app.pool, 1).await.remove(0);
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/roles/{}/permissions/add",
+            &app.address, role.role_id
+        ))
+        .header("If-Match", "\"1\"")
+        .json(&[permission.permission_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    app.api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[(
+            "valid_until",
+            (Utc::now() + chrono::Duration::seconds(2)).to_rfc3339(),
+        )])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(get_permissions(&app).await.permissions.len(), 1);
+
+    let mut events = Vec::new();
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        events = sqlx::query_scalar!(
+            "SELECT event FROM users_roles_audit WHERE user_id = $1 ORDER BY occurred_at",
+            app.test_user.user_id
+        )
+        .fetch_all(&app.pool)
+        .await
+        .expect("Failed to fetch audit trail");
+        if events.len() == 2 {
+            break;
+        }
+    }
+
+    assert_eq!(events, vec!["granted", "expired"]);
+    let remaining = sqlx::query_scalar!(
+        "SELECT count(*) FROM users_roles WHERE user_id = $1",
+        app.test_user.user_id
+    )
+    .fetch_one(&app.pool)
+    .await
+    .expect("Failed to count grants");
+    assert_eq!(remaining, Some(0));
+    assert!(get_permissions(&app).await.permissions.is_empty());
+}
+
+#[tokio::test]
+async fn project_grants_apply_only_to_their_project() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+    let permission = insert_permissions(&app.pool, 1).await.remove(0);
+    sqlx::query!(
+        "INSERT INTO roles_permissions (role_id, permission_id) VALUES ($1, $2)",
+        role.role_id,
+        permission.permission_id
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let projects = sqlx::query_scalar!(
+        r#"
+        INSERT INTO projects (project_id, name, description, organization_id)
+        SELECT gen_random_uuid(), 'project ' || n, '', $1
+        FROM generate_series(1, 2) as n
+        RETURNING project_id
+        "#,
+        app.organization_id
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap();
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[("project_id", projects[0])])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let permissions = get_permissions(&app).await;
+    assert!(permissions.permissions.is_empty());
+    assert_eq!(permissions.projects.len(), 1);
+    assert_eq!(permissions.projects[0].project_id, projects[0]);
+
+    let on_project = |project_id: uuid::Uuid| {
+        app.api_client
+            .get(format!(
+                "{}/rbac-demo/users/{}/permissions",
+                &app.address, app.test_user.user_id
+            ))
+            .query(&[("project_id", project_id)])
+            .send()
+    };
+    let granted = on_project(projects[0])
+        .await
+        .expect("Failed to get request")
+        .json::<EffectivePermissions>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(granted.permissions.len(), 1);
+    assert_eq!(
+        granted.permissions[0].permission_id,
+        permission.permission_id
+    );
+    let other = on_project(projects[1])
+        .await
+        .expect("Failed to get request")
+        .json::<EffectivePermissions>()
+        .await
+        .expect("Failed to parse response body");
+    assert!(other.permissions.is_empty());
+
+    let listed = app
+        .api_client
+        .get(format!(
+            "{}/rbac-demo/users/{}/roles",
+            &app.address, app.test_user.user_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json::<Vec<RoleGrant>>()
+        .await
+        .expect("Failed to parse response body");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].project_id, Some(projects[0]));
+
+    // The organization-wide grant of the same role is a different one.
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/remove",
+            &app.address, app.test_user.user_id
+        ))
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+}
+
+#[tokio::test]
+async fn granting_on_an_unknown_project_returns_404() {
+    let app = spawn_app().await;
+    let role = insert_roles(&app.pool, app.organization_id, 1)
+        .await
+        .remove(0);
+
+    let response = app
+        .api_client
+        .post(format!(
+            "{}/rbac-demo/users/{}/roles/add",
+            &app.address, app.test_user.user_id
+        ))
+        .query(&[("project_id", uuid::Uuid::new_v4())])
+        .json(&[role.role_id])
+        .send()
+        .await
+        .expect("Failed to post request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+async fn get_permissions(app: &crate::helper::TestApp) -> EffectivePermissions {
+    app.api_client
+        .get(format!(
+            "{}/rbac-demo/users/{}/permissions",
+            &app.address, app.test_user.user_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get request")
+        .json()
+        .await
+        .expect("Failed to parse response body")
+}